@@ -0,0 +1,3 @@
+ALTER TABLE endpoints
+    ADD COLUMN last_purged_at TIMESTAMPTZ,
+    ADD COLUMN last_purge_count BIGINT NOT NULL DEFAULT 0;
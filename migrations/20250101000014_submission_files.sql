@@ -0,0 +1,15 @@
+CREATE TABLE submission_files (
+    id UUID PRIMARY KEY DEFAULT uuidv7(),
+    -- Nulled when the submission is deleted; the retention job then removes the blob.
+    submission_id UUID REFERENCES submissions(id) ON DELETE SET NULL,
+    field_name VARCHAR(255) NOT NULL,
+    filename VARCHAR(255) NOT NULL,
+    content_type VARCHAR(255) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    storage_key TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_submission_files_submission ON submission_files(submission_id);
+CREATE INDEX idx_submission_files_orphaned ON submission_files(created_at)
+    WHERE submission_id IS NULL;
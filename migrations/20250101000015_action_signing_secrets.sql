@@ -0,0 +1,4 @@
+ALTER TABLE actions
+    ADD COLUMN signing_secret_enc BYTEA,
+    ADD COLUMN previous_signing_secret_enc BYTEA,
+    ADD COLUMN previous_secret_expires_at TIMESTAMPTZ;
@@ -0,0 +1,3 @@
+ALTER TABLE endpoints
+    ADD COLUMN signature_failures BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN last_signature_failure_at TIMESTAMPTZ;
@@ -0,0 +1,13 @@
+-- Verbatim request capture for endpoints with settings.capture_raw enabled.
+CREATE TABLE submission_requests (
+    submission_id UUID PRIMARY KEY REFERENCES submissions(id) ON DELETE CASCADE,
+    method VARCHAR(10) NOT NULL,
+    content_type VARCHAR(255),
+    -- Ordered [name, value] pairs, so repeated headers survive.
+    headers JSONB NOT NULL DEFAULT '[]',
+    body BYTEA NOT NULL,
+    -- Size of the original body; larger than length(body) when truncated.
+    body_size BIGINT NOT NULL,
+    truncated BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1 @@
+ALTER TABLE submissions ADD COLUMN validation_warnings JSONB;
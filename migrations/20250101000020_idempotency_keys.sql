@@ -0,0 +1,13 @@
+-- Keys claimed per endpoint (settings.idempotency). The primary key is what
+-- rejects a concurrent repeat, whichever replica receives it.
+CREATE TABLE idempotency_keys (
+    endpoint_id UUID NOT NULL REFERENCES endpoints(id) ON DELETE CASCADE,
+    idempotency_key VARCHAR(255) NOT NULL,
+    -- Set in the transaction that claims the key, once the submission is stored.
+    submission_id UUID REFERENCES submissions(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (endpoint_id, idempotency_key)
+);
+
+CREATE INDEX idx_idempotency_keys_expires ON idempotency_keys(expires_at);
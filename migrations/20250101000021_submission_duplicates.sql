@@ -0,0 +1,6 @@
+-- Content-hash duplicate detection (settings.duplicates)
+ALTER TABLE submissions ADD COLUMN content_hash TEXT;
+ALTER TABLE submissions ADD COLUMN duplicate BOOLEAN NOT NULL DEFAULT false;
+
+CREATE INDEX idx_submissions_content_hash ON submissions(endpoint_id, content_hash, created_at)
+    WHERE content_hash IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Spam scoring (settings.spam): tenant-wide IP blocklist and quarantined submissions
+ALTER TABLE tenants ADD COLUMN ip_blocklist TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE submissions ADD COLUMN quarantined BOOLEAN NOT NULL DEFAULT false;
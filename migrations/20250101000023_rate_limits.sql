@@ -0,0 +1,11 @@
+-- Token buckets for the Postgres rate limit store (WEBHOOKER_RATE_LIMIT_STORE=postgres),
+-- shared by every replica. A missing row is a full bucket.
+CREATE TABLE rate_limits (
+    key TEXT PRIMARY KEY,
+    tokens DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- When the bucket will have refilled; the row can be deleted after this.
+    full_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_rate_limits_full_at ON rate_limits(full_at);
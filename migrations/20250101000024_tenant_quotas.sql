@@ -0,0 +1,33 @@
+-- Tenant quotas, set by system admins. NULL falls back to the instance default
+-- (WEBHOOKER_QUOTA_*), and no default means unlimited.
+ALTER TABLE tenants
+    ADD COLUMN quota_daily_submissions BIGINT,
+    ADD COLUMN quota_monthly_submissions BIGINT,
+    ADD COLUMN quota_stored_submissions BIGINT,
+    ADD COLUMN quota_storage_bytes BIGINT,
+    -- Incremented as submissions are stored; recounted by the retention job,
+    -- which is when deletions show up.
+    ADD COLUMN stored_submissions BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN stored_bytes BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN usage_counted_at TIMESTAMPTZ;
+
+-- Submissions stored per tenant per UTC day; the month is the sum of its days.
+CREATE TABLE tenant_daily_usage (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    submissions BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (tenant_id, day)
+);
+
+-- Quota warnings already raised (and emailed to owners, with system SMTP),
+-- so each is raised once per period.
+-- period is the day ('2025-01-31') or month ('2025-01') for submission counts,
+-- and '' for stored totals, whose rows are cleared when usage drops below 80%.
+CREATE TABLE tenant_quota_warnings (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    quota VARCHAR(30) NOT NULL,
+    period VARCHAR(10) NOT NULL,
+    percent SMALLINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (tenant_id, quota, period, percent)
+);
@@ -0,0 +1,5 @@
+-- Client access rules: tenant-wide denylist, and rejections counted per endpoint
+ALTER TABLE tenants ADD COLUMN ip_denylist TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE endpoints
+    ADD COLUMN access_denials BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN last_access_denial_at TIMESTAMPTZ;
@@ -0,0 +1,11 @@
+-- Each submission's size as counted against its tenant's storage quota: the
+-- JSON columns as serialized, uploaded files and the captured request body.
+-- The pipeline sets it on insert, deletes subtract it from tenants.stored_bytes
+-- in the same statement, and the retention job's recount sums it.
+ALTER TABLE submissions ADD COLUMN size_bytes BIGINT NOT NULL DEFAULT 0;
+
+UPDATE submissions s
+SET size_bytes = octet_length(s.data::text) + octet_length(s.extras::text)
+    + octet_length(s.raw::text) + octet_length(s.metadata::text)
+    + COALESCE((SELECT SUM(f.size_bytes) FROM submission_files f WHERE f.submission_id = s.id), 0)
+    + COALESCE((SELECT octet_length(r.body) FROM submission_requests r WHERE r.submission_id = s.id), 0);
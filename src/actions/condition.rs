@@ -0,0 +1,603 @@
+//! Action conditions: a small boolean expression language over the submission.
+//!
+//! ```text
+//! data.country == "US" && data.budget > 5000
+//! extras.utm_source in ["ads", "fb"]
+//! metadata.spam.score < 3 || !(data.tags contains "test")
+//! ```
+//!
+//! Paths start at `data`, `extras`, `metadata` or `raw` and may use `.key`,
+//! `[index]` and `.index` (`data.items.0.name`). Missing paths are `null`.
+//! Comparisons between a number and a numeric string compare numerically,
+//! since form values arrive as strings.
+
+use serde_json::{json, Value};
+
+const ROOTS: &[&str] = &["data", "extras", "metadata", "raw"];
+
+/// Longest accepted source, in characters.
+const MAX_SOURCE_LEN: usize = 4096;
+
+/// Deepest nesting of parentheses, lists and `!`, so a hostile condition
+/// can't exhaust the stack of the recursive-descent parser.
+const MAX_DEPTH: usize = 64;
+
+/// A parsed condition, ready to evaluate.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Box<Expr>, Op, Box<Expr>),
+    Path(Vec<Segment>),
+    Literal(Value),
+    List(Vec<Expr>),
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    NotIn,
+    Contains,
+}
+
+impl Condition {
+    /// Parse a condition. Errors name the problem and its character position.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        Self::parse_scoped(source, ROOTS)
+    }
+
+    /// Parse with a custom set of path roots. Templates use this to allow
+    /// `endpoint`, `submission` and loop variables; no roots means literals only.
+    pub(crate) fn parse_scoped(source: &str, roots: &[&str]) -> Result<Self, String> {
+        Self::parse_with(source, roots, false)
+    }
+
+    /// Parse letting any name start a path; names missing from the context
+    /// evaluate to `null`. Templates are rendered this way, so ones saved
+    /// before names were checked still render.
+    pub(crate) fn parse_lenient(source: &str) -> Result<Self, String> {
+        Self::parse_with(source, &[], true)
+    }
+
+    fn parse_with(source: &str, roots: &[&str], any_root: bool) -> Result<Self, String> {
+        let len = source.chars().count();
+        if len > MAX_SOURCE_LEN {
+            return Err(format!(
+                "Condition is {len} characters long, the limit is {MAX_SOURCE_LEN}"
+            ));
+        }
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            len,
+            depth: 0,
+            roots: roots.iter().map(|r| r.to_string()).collect(),
+            any_root,
+        };
+        let expr = parser.expr()?;
+        if let Some(tok) = parser.peek() {
+            return Err(format!(
+                "Unexpected {} at position {}",
+                tok.kind.describe(),
+                tok.pos
+            ));
+        }
+        Ok(Self { expr })
+    }
+
+    /// Evaluate against a submission.
+    pub fn eval(&self, data: &Value, extras: &Value, metadata: &Value, raw: &Value) -> bool {
+        let ctx = json!({
+            "data": data,
+            "extras": extras,
+            "metadata": metadata,
+            "raw": raw,
+        });
+        self.matches(&ctx)
+    }
+
+    /// Evaluate against a context object keyed by root name, as a boolean.
+    pub(crate) fn matches(&self, ctx: &Value) -> bool {
+        truthy(&eval(&self.expr, ctx))
+    }
+
+    /// Evaluate against a context object keyed by root name.
+    pub(crate) fn value(&self, ctx: &Value) -> Value {
+        eval(&self.expr, ctx)
+    }
+}
+
+// ── Lexer ───────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Op(&'static str),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Dot,
+}
+
+impl Kind {
+    fn describe(&self) -> String {
+        match self {
+            Kind::Ident(s) => format!("'{s}'"),
+            Kind::Str(s) => format!("string \"{s}\""),
+            Kind::Num(n) => format!("number {n}"),
+            Kind::Op(op) => format!("'{op}'"),
+            Kind::LParen => "'('".to_string(),
+            Kind::RParen => "')'".to_string(),
+            Kind::LBracket => "'['".to_string(),
+            Kind::RBracket => "']'".to_string(),
+            Kind::Comma => "','".to_string(),
+            Kind::Dot => "'.'".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: Kind,
+    pos: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let two: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        let op = match two.as_str() {
+            "==" => Some("=="),
+            "!=" => Some("!="),
+            "<=" => Some("<="),
+            ">=" => Some(">="),
+            "&&" => Some("&&"),
+            "||" => Some("||"),
+            _ => None,
+        };
+        if let Some(op) = op {
+            tokens.push(Token { kind: Kind::Op(op), pos: start });
+            i += 2;
+            continue;
+        }
+
+        // A key follows a dot, even one that reads as a number or keyword
+        // (`items.0.name`, `data.in`), so `0.` isn't lexed as a float
+        let after_dot = matches!(tokens.last(), Some(Token { kind: Kind::Dot, .. }));
+        if after_dot && (c.is_alphanumeric() || c == '_') {
+            let is_key = |ch: char| ch.is_alphanumeric() || ch == '_' || ch == '-';
+            while i < chars.len() && is_key(chars[i]) {
+                i += 1;
+            }
+            let key = chars[start..i].iter().collect();
+            tokens.push(Token { kind: Kind::Ident(key), pos: start });
+            continue;
+        }
+
+        let kind = match c {
+            '<' => Kind::Op("<"),
+            '>' => Kind::Op(">"),
+            '!' => Kind::Op("!"),
+            '(' => Kind::LParen,
+            ')' => Kind::RParen,
+            '[' => Kind::LBracket,
+            ']' => Kind::RBracket,
+            ',' => Kind::Comma,
+            '.' => Kind::Dot,
+            '=' => return Err(format!("Unexpected '=' at position {start} (use '==')")),
+            '"' | '\'' => {
+                let quote = c;
+                let mut s = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(format!("Unterminated string starting at position {start}")),
+                        Some('\\') => {
+                            match chars.get(i + 1) {
+                                Some('n') => s.push('\n'),
+                                Some('t') => s.push('\t'),
+                                Some(&other) => s.push(other),
+                                None => return Err(format!("Unterminated string starting at position {start}")),
+                            }
+                            i += 2;
+                        }
+                        Some(&ch) if ch == quote => break,
+                        Some(&ch) => {
+                            s.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+                tokens.push(Token { kind: Kind::Str(s), pos: start });
+                continue;
+            }
+            c if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) => {
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let n = text
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid number '{text}' at position {start}"))?;
+                tokens.push(Token { kind: Kind::Num(n), pos: start });
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '-') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let kind = match word.as_str() {
+                    "and" => Kind::Op("&&"),
+                    "or" => Kind::Op("||"),
+                    "not" => Kind::Op("!"),
+                    "in" => Kind::Op("in"),
+                    "contains" => Kind::Op("contains"),
+                    _ => Kind::Ident(word),
+                };
+                tokens.push(Token { kind, pos: start });
+                continue;
+            }
+            other => return Err(format!("Unexpected character '{other}' at position {start}")),
+        };
+        tokens.push(Token { kind, pos: start });
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+// ── Parser ──────────────────────────────────────────────────────
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Source length, reported as the position of "end of input" errors.
+    len: usize,
+    /// Current nesting, bounded by `MAX_DEPTH`.
+    depth: usize,
+    roots: Vec<String>,
+    /// Accept any name as a path root (see `Condition::parse_lenient`).
+    any_root: bool,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_kind(&self) -> Option<&Kind> {
+        self.peek().map(|t| &t.kind)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    fn eat(&mut self, kind: &Kind) -> bool {
+        if self.peek_kind() == Some(kind) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: Kind, what: &str) -> Result<(), String> {
+        if self.eat(&kind) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {what}")))
+        }
+    }
+
+    fn error(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(tok) => format!(
+                "Unexpected {} at position {}: {expected}",
+                tok.kind.describe(),
+                tok.pos
+            ),
+            None => format!("Unexpected end of condition at position {}: {expected}", self.len),
+        }
+    }
+
+    /// Go one level deeper after consuming an opening token.
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            let pos = self.tokens[self.pos - 1].pos;
+            return Err(format!(
+                "Nested too deeply at position {pos}: at most {MAX_DEPTH} levels"
+            ));
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat(&Kind::Op("||")) {
+            let right = self.and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while self.eat(&Kind::Op("&&")) {
+            let right = self.unary()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat(&Kind::Op("!")) {
+            self.enter()?;
+            let inner = self.unary()?;
+            self.depth -= 1;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.primary()?;
+        let op = match self.peek_kind() {
+            Some(Kind::Op("==")) => Op::Eq,
+            Some(Kind::Op("!=")) => Op::Ne,
+            Some(Kind::Op("<")) => Op::Lt,
+            Some(Kind::Op("<=")) => Op::Le,
+            Some(Kind::Op(">")) => Op::Gt,
+            Some(Kind::Op(">=")) => Op::Ge,
+            Some(Kind::Op("in")) => Op::In,
+            Some(Kind::Op("contains")) => Op::Contains,
+            // `!in` / `not in` after a value is a comparison, not negation
+            Some(Kind::Op("!"))
+                if self.tokens.get(self.pos + 1).map(|t| &t.kind) == Some(&Kind::Op("in")) =>
+            {
+                self.pos += 1;
+                Op::NotIn
+            }
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.primary()?;
+        Ok(Expr::Compare(Box::new(left), op, Box::new(right)))
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let Some(tok) = self.next() else {
+            return Err(format!(
+                "Unexpected end of condition at position {}: expected a value",
+                self.len
+            ));
+        };
+        match tok.kind {
+            Kind::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Kind::Num(n) => Ok(Expr::Literal(json!(n))),
+            Kind::LParen => {
+                self.enter()?;
+                let inner = self.expr()?;
+                self.expect(Kind::RParen, "')'")?;
+                self.depth -= 1;
+                Ok(inner)
+            }
+            Kind::LBracket => {
+                self.enter()?;
+                let mut items = Vec::new();
+                if !self.eat(&Kind::RBracket) {
+                    loop {
+                        items.push(self.primary()?);
+                        if self.eat(&Kind::RBracket) {
+                            break;
+                        }
+                        self.expect(Kind::Comma, "',' or ']'")?;
+                    }
+                }
+                self.depth -= 1;
+                Ok(Expr::List(items))
+            }
+            Kind::Ident(word) => match word.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                root if self.any_root || self.roots.iter().any(|r| r == root) => self.path(word),
+                _ if self.roots.is_empty() => Err(format!(
+                    "Unexpected '{word}' at position {}: expected a literal",
+                    tok.pos
+                )),
+                _ => Err(format!(
+                    "Unknown name '{word}' at position {}: paths must start with {}",
+                    tok.pos,
+                    join_or(&self.roots)
+                )),
+            },
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected a value"))
+            }
+        }
+    }
+
+    fn path(&mut self, root: String) -> Result<Expr, String> {
+        let mut segments = vec![Segment::Key(root)];
+        loop {
+            if self.eat(&Kind::Dot) {
+                match self.next() {
+                    Some(Token { kind: Kind::Ident(key), .. }) => segments.push(Segment::Key(key)),
+                    _ => {
+                        self.pos -= 1;
+                        return Err(self.error("expected a field name after '.'"));
+                    }
+                }
+            } else if self.peek_kind() == Some(&Kind::LBracket) {
+                self.pos += 1;
+                match self.next() {
+                    Some(Token { kind: Kind::Num(n), .. }) if n.fract() == 0.0 && n >= 0.0 => {
+                        segments.push(Segment::Index(n as usize))
+                    }
+                    Some(Token { kind: Kind::Str(key), .. }) => segments.push(Segment::Key(key)),
+                    _ => {
+                        self.pos -= 1;
+                        return Err(self.error("expected an index or quoted key"));
+                    }
+                }
+                self.expect(Kind::RBracket, "']'")?;
+            } else {
+                break;
+            }
+        }
+        Ok(Expr::Path(segments))
+    }
+}
+
+/// `a, b or c`
+fn join_or(names: &[String]) -> String {
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {last}", rest.join(", ")),
+        Some((last, _)) => last.clone(),
+        None => String::new(),
+    }
+}
+
+// ── Evaluation ──────────────────────────────────────────────────
+
+fn eval(expr: &Expr, ctx: &Value) -> Value {
+    match expr {
+        Expr::Or(l, r) => Value::Bool(truthy(&eval(l, ctx)) || truthy(&eval(r, ctx))),
+        Expr::And(l, r) => Value::Bool(truthy(&eval(l, ctx)) && truthy(&eval(r, ctx))),
+        Expr::Not(e) => Value::Bool(!truthy(&eval(e, ctx))),
+        Expr::Literal(v) => v.clone(),
+        Expr::List(items) => Value::Array(items.iter().map(|e| eval(e, ctx)).collect()),
+        Expr::Path(segments) => {
+            let mut cur = ctx;
+            for seg in segments {
+                let next = match seg {
+                    // `items.0` indexes an array like `items[0]`
+                    Segment::Key(k) if cur.is_array() => {
+                        k.parse::<usize>().ok().and_then(|i| cur.get(i))
+                    }
+                    Segment::Key(k) => cur.get(k.as_str()),
+                    Segment::Index(i) => cur.get(*i),
+                };
+                match next {
+                    Some(v) => cur = v,
+                    None => return Value::Null,
+                }
+            }
+            cur.clone()
+        }
+        Expr::Compare(l, op, r) => {
+            let (l, r) = (eval(l, ctx), eval(r, ctx));
+            Value::Bool(compare(&l, *op, &r))
+        }
+    }
+}
+
+fn compare(l: &Value, op: Op, r: &Value) -> bool {
+    match op {
+        Op::Eq => loose_eq(l, r),
+        Op::Ne => !loose_eq(l, r),
+        Op::Lt | Op::Le | Op::Gt | Op::Ge => {
+            let ord = match (as_number(l), as_number(r)) {
+                (Some(a), Some(b)) => a.partial_cmp(&b),
+                _ => match (l, r) {
+                    (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                    _ => None,
+                },
+            };
+            let Some(ord) = ord else {
+                return false;
+            };
+            match op {
+                Op::Lt => ord.is_lt(),
+                Op::Le => ord.is_le(),
+                Op::Gt => ord.is_gt(),
+                _ => ord.is_ge(),
+            }
+        }
+        Op::In => contains(r, l),
+        Op::NotIn => !contains(r, l),
+        Op::Contains => contains(l, r),
+    }
+}
+
+/// `haystack` is an array containing `needle`, or a string containing it.
+fn contains(haystack: &Value, needle: &Value) -> bool {
+    match haystack {
+        Value::Array(items) => items.iter().any(|item| loose_eq(item, needle)),
+        Value::String(s) => match needle {
+            Value::String(n) => s.contains(n.as_str()),
+            Value::Number(n) => s.contains(&n.to_string()),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn loose_eq(a: &Value, b: &Value) -> bool {
+    if (a.is_number() || b.is_number())
+        && let (Some(x), Some(y)) = (as_number(a), as_number(b))
+    {
+        return x == y;
+    }
+    a == b
+}
+
+fn as_number(v: &Value) -> Option<f64> {
+    match v {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok().filter(|n| n.is_finite()),
+        _ => None,
+    }
+}
+
+fn truthy(v: &Value) -> bool {
+    match v {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
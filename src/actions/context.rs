@@ -5,4 +5,6 @@ pub struct ActionContext {
     pub endpoint: Endpoint,
     pub project: Project,
     pub tenant: Tenant,
+    /// Decrypted signing secrets, active first. Empty if the action has none.
+    pub signing_secrets: Vec<String>,
 }
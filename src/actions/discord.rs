@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use ipnet::IpNet;
+use serde_json::{json, Value};
+
+use super::context::ActionContext;
+use super::notify::{self, HttpTarget};
+use super::template::{self, Escape};
+use super::{ActionError, ActionModule, ActionResult};
+use crate::config::SsrfMode;
+
+/// Discord embed limits.
+const MAX_FIELDS: usize = 25;
+const MAX_TITLE: usize = 256;
+const MAX_FIELD_NAME: usize = 256;
+const MAX_FIELD_VALUE: usize = 1024;
+/// Values up to this length are laid out side by side.
+const INLINE_MAX: usize = 40;
+const DEFAULT_COLOR: u64 = 0x5865F2;
+
+/// Posts an embed to a Discord webhook, with one embed field per submission field.
+pub struct DiscordModule {
+    http: HttpTarget,
+    base_url: String,
+}
+
+impl DiscordModule {
+    pub fn new(ssrf_mode: SsrfMode, allowed_cidrs: Vec<IpNet>, base_url: String) -> Self {
+        Self {
+            http: HttpTarget::new(ssrf_mode, allowed_cidrs),
+            base_url,
+        }
+    }
+
+    fn payload(&self, ctx: &ActionContext, config: &Value) -> Result<Value, ActionError> {
+        let title = match config["title"].as_str().filter(|s| !s.is_empty()) {
+            Some(t) => template::render(t, ctx, Escape::None)?,
+            None => format!("New submission: {}", ctx.endpoint.name),
+        };
+
+        let fields: Vec<Value> = notify::field_pairs(&ctx.submission.data)
+            .into_iter()
+            .take(MAX_FIELDS)
+            .map(|(name, value)| {
+                // Discord rejects empty field values
+                let value = if value.is_empty() { "-".to_string() } else { value };
+                json!({
+                    "name": notify::truncate(&name, MAX_FIELD_NAME),
+                    "inline": value.chars().count() <= INLINE_MAX,
+                    "value": notify::truncate(&value, MAX_FIELD_VALUE),
+                })
+            })
+            .collect();
+
+        let mut payload = json!({
+            "embeds": [{
+                "title": notify::truncate(&title, MAX_TITLE),
+                "url": notify::submission_url(&self.base_url, ctx.submission.id),
+                "color": config["color"].as_u64().unwrap_or(DEFAULT_COLOR),
+                "fields": fields,
+                "footer": { "text": format!("{} / {}", ctx.project.name, ctx.endpoint.name) },
+                "timestamp": ctx.submission.created_at.to_rfc3339(),
+            }],
+            // Submitted text must never ping @everyone or roles
+            "allowed_mentions": { "parse": [] },
+        });
+
+        if let Some(content) = config["content"].as_str().filter(|s| !s.is_empty()) {
+            payload["content"] = json!(template::render(content, ctx, Escape::None)?);
+        }
+        for key in ["username", "avatar_url"] {
+            if let Some(v) = config[key].as_str().filter(|s| !s.is_empty()) {
+                payload[key] = json!(v);
+            }
+        }
+        Ok(payload)
+    }
+}
+
+#[async_trait]
+impl ActionModule for DiscordModule {
+    fn id(&self) -> &str {
+        "discord"
+    }
+
+    fn name(&self) -> &str {
+        "Discord"
+    }
+
+    fn config_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "webhook_url": { "type": "string", "description": "Discord webhook URL (stored encrypted)" },
+                "title": { "type": "string", "description": "Embed title (supports template vars). Default: New submission: <endpoint>" },
+                "content": { "type": "string", "description": "Message text above the embed (supports template vars)" },
+                "username": { "type": "string", "description": "Override the webhook's display name" },
+                "avatar_url": { "type": "string", "description": "Override the webhook's avatar" },
+                "color": { "type": "integer", "description": "Embed color as a decimal RGB value", "default": DEFAULT_COLOR }
+            },
+            "required": ["webhook_url"]
+        })
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<(), ActionError> {
+        notify::require_url(config, "webhook_url")?;
+        template::validate(config, &["title", "content"])?;
+        if let Some(color) = config.get("color").filter(|v| !v.is_null())
+            && color.as_u64().is_none_or(|c| c > 0xFFFFFF)
+        {
+            return Err(ActionError::from("color must be an integer between 0 and 16777215"));
+        }
+        Ok(())
+    }
+
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &["webhook_url"]
+    }
+
+    async fn execute(&self, ctx: &ActionContext, config: &Value) -> Result<ActionResult, ActionError> {
+        let url = config["webhook_url"]
+            .as_str()
+            .ok_or_else(|| ActionError::from("webhook_url is required"))?;
+        let req = self.http.client.post(url).json(&self.payload(ctx, config)?);
+        self.http.send(url, req).await
+    }
+}
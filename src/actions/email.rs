@@ -6,7 +6,7 @@ use serde_json::json;
 use sqlx::PgPool;
 
 use super::context::ActionContext;
-use super::template;
+use super::template::{self, Escape};
 use super::{ActionError, ActionModule, ActionResult, ActionStatus};
 use crate::{crypto, db};
 
@@ -52,7 +52,7 @@ impl ActionModule for EmailModule {
                 .filter(|s| !s.is_empty())
                 .ok_or_else(|| ActionError::from(format!("{field} is required")))?;
         }
-        Ok(())
+        template::validate(config, &["to", "subject", "body"])
     }
 
     async fn execute(
@@ -67,23 +67,19 @@ impl ActionModule for EmailModule {
         let to = template::render(
             config["to"].as_str().unwrap_or_default(),
             ctx,
-        );
+            Escape::Header,
+        )?;
         let subject = template::render(
             config["subject"].as_str().unwrap_or_default(),
             ctx,
-        );
+            Escape::Header,
+        )?;
         let is_html = config["html"].as_bool().unwrap_or(false);
-        let body = if is_html {
-            template::render_html(
-                config["body"].as_str().unwrap_or_default(),
-                ctx,
-            )
-        } else {
-            template::render(
-                config["body"].as_str().unwrap_or_default(),
-                ctx,
-            )
-        };
+        let body = template::render(
+            config["body"].as_str().unwrap_or_default(),
+            ctx,
+            if is_html { Escape::Html } else { Escape::None },
+        )?;
 
         let from = if let Some(name) = &smtp_config.from_name {
             format!("{} <{}>", name, smtp_config.from_address)
@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use ipnet::IpNet;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use super::context::ActionContext;
+use super::notify::{self, HttpTarget};
+use super::template::{self, Escape};
+use super::{ActionError, ActionModule, ActionResult};
+use crate::config::SsrfMode;
+
+/// Sends an `m.room.message` event to a Matrix room via the client-server API.
+pub struct MatrixModule {
+    http: HttpTarget,
+    base_url: String,
+}
+
+impl MatrixModule {
+    pub fn new(ssrf_mode: SsrfMode, allowed_cidrs: Vec<IpNet>, base_url: String) -> Self {
+        Self {
+            http: HttpTarget::new(ssrf_mode, allowed_cidrs),
+            base_url,
+        }
+    }
+}
+
+/// Transaction id derived from the submission and room, so a retried delivery
+/// is deduplicated by the homeserver instead of posting twice.
+fn txn_id(ctx: &ActionContext, room_id: &str) -> String {
+    let room_hash = hex::encode(&Sha256::digest(room_id.as_bytes())[..8]);
+    format!("webhooker-{}-{room_hash}", ctx.submission.id)
+}
+
+#[async_trait]
+impl ActionModule for MatrixModule {
+    fn id(&self) -> &str {
+        "matrix"
+    }
+
+    fn name(&self) -> &str {
+        "Matrix"
+    }
+
+    fn config_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "homeserver_url": { "type": "string", "description": "Homeserver base URL, e.g. https://matrix.org" },
+                "access_token": { "type": "string", "description": "Access token of the sending user (stored encrypted)" },
+                "room_id": { "type": "string", "description": "Room id, e.g. !abc123:matrix.org (the user must have joined)" },
+                "message": { "type": "string", "description": "Message text (supports template vars). Default: all fields and a link" },
+                "msgtype": { "type": "string", "enum": ["m.notice", "m.text"], "default": "m.notice" }
+            },
+            "required": ["homeserver_url", "access_token", "room_id"]
+        })
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<(), ActionError> {
+        notify::require_url(config, "homeserver_url")?;
+        for field in ["access_token", "room_id"] {
+            config
+                .get(field)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| ActionError::from(format!("{field} is required")))?;
+        }
+        if !config["room_id"].as_str().unwrap_or_default().starts_with('!') {
+            return Err(ActionError::from("room_id must be a room id starting with '!'"));
+        }
+        template::validate(config, &["message"])?;
+        match config["msgtype"].as_str() {
+            None | Some("m.notice") | Some("m.text") => Ok(()),
+            Some(other) => Err(ActionError::from(format!("Unsupported msgtype: {other}"))),
+        }
+    }
+
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &["access_token"]
+    }
+
+    async fn execute(&self, ctx: &ActionContext, config: &Value) -> Result<ActionResult, ActionError> {
+        let homeserver = config["homeserver_url"]
+            .as_str()
+            .ok_or_else(|| ActionError::from("homeserver_url is required"))?;
+        let token = config["access_token"]
+            .as_str()
+            .ok_or_else(|| ActionError::from("access_token is required"))?;
+        let room_id = config["room_id"]
+            .as_str()
+            .ok_or_else(|| ActionError::from("room_id is required"))?;
+
+        let mut url = reqwest::Url::parse(homeserver)
+            .map_err(|e| ActionError::from(format!("Invalid homeserver_url: {e}")))?;
+        url.path_segments_mut()
+            .map_err(|_| ActionError::from("Invalid homeserver_url"))?
+            .pop_if_empty()
+            .extend([
+                "_matrix",
+                "client",
+                "v3",
+                "rooms",
+                room_id,
+                "send",
+                "m.room.message",
+                &txn_id(ctx, room_id),
+            ]);
+
+        let text = match config["message"].as_str().filter(|s| !s.is_empty()) {
+            Some(tmpl) => template::render(tmpl, ctx, Escape::None)?,
+            None => notify::default_text(ctx, &self.base_url),
+        };
+        let body = json!({
+            "msgtype": config["msgtype"].as_str().unwrap_or("m.notice"),
+            "body": text,
+        });
+
+        let req = self.http.client.put(url.clone()).bearer_auth(token).json(&body);
+        self.http.send(url.as_str(), req).await
+    }
+}
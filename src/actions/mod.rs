@@ -1,6 +1,15 @@
+pub mod condition;
 pub mod context;
 pub mod template;
+pub mod discord;
 pub mod email;
+pub mod matrix;
+pub mod notify;
+pub mod secrets;
+pub mod signing;
+pub mod slack;
+pub mod teams;
+pub mod telegram;
 pub mod webhook;
 
 use std::collections::HashMap;
@@ -55,6 +64,14 @@ pub trait ActionModule: Send + Sync {
     fn name(&self) -> &str;
     fn config_schema(&self) -> serde_json::Value;
     fn validate_config(&self, config: &serde_json::Value) -> Result<(), ActionError>;
+    /// Whether actions of this type get a signing secret (see `signing`).
+    fn signs_requests(&self) -> bool {
+        false
+    }
+    /// Config keys holding credentials, encrypted at rest (see `secrets`).
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &[]
+    }
     async fn execute(
         &self,
         ctx: &ActionContext,
@@ -66,6 +83,12 @@ pub struct ModuleRegistry {
     modules: HashMap<String, Arc<dyn ActionModule>>,
 }
 
+impl Default for ModuleRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ModuleRegistry {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,120 @@
+//! Shared helpers for chat-style notification modules (Discord, Slack, ...).
+
+use ipnet::IpNet;
+use serde_json::{json, Value};
+
+use super::webhook::validate_url;
+use super::{ActionError, ActionResult, ActionStatus};
+use crate::config::SsrfMode;
+
+/// Outbound HTTP settings shared by notification modules.
+pub struct HttpTarget {
+    pub client: reqwest::Client,
+    pub ssrf_mode: SsrfMode,
+    pub allowed_cidrs: Vec<IpNet>,
+}
+
+impl HttpTarget {
+    pub fn new(ssrf_mode: SsrfMode, allowed_cidrs: Vec<IpNet>) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(30))
+                .build()
+                .expect("Failed to build reqwest client"),
+            ssrf_mode,
+            allowed_cidrs,
+        }
+    }
+
+    /// Send a request after the SSRF check and turn the response into an `ActionResult`.
+    pub async fn send(
+        &self,
+        url: &str,
+        req: reqwest::RequestBuilder,
+    ) -> Result<ActionResult, ActionError> {
+        validate_url(url, &self.ssrf_mode, &self.allowed_cidrs)?;
+
+        let resp = req
+            .send()
+            .await
+            // The URL may carry a secret (bot token, webhook key), keep it out of the log
+            .map_err(|e| ActionError::from(format!("Request failed: {}", e.without_url())))?;
+
+        let status_code = resp.status().as_u16();
+        let body: String = resp
+            .text()
+            .await
+            .unwrap_or_default()
+            .chars()
+            .take(1024)
+            .collect();
+
+        Ok(ActionResult {
+            status: if (200..300).contains(&status_code) {
+                ActionStatus::Success
+            } else {
+                ActionStatus::Failed
+            },
+            response: Some(json!({ "status_code": status_code, "body": body })),
+        })
+    }
+}
+
+/// Require a non-empty http(s) URL in `config[key]`.
+pub fn require_url(config: &Value, key: &str) -> Result<(), ActionError> {
+    let url = config
+        .get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ActionError::from(format!("{key} is required")))?;
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| ActionError::from(format!("{key} is not a valid URL: {e}")))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(ActionError::from(format!("{key} must be an http(s) URL")));
+    }
+    Ok(())
+}
+
+/// Submission data as (field, display value) pairs, sorted by field name.
+/// Strings are shown as-is, null as empty, everything else as JSON.
+pub fn field_pairs(data: &Value) -> Vec<(String, String)> {
+    let Some(obj) = data.as_object() else {
+        return Vec::new();
+    };
+    obj.iter()
+        .map(|(k, v)| {
+            let text = match v {
+                Value::String(s) => s.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            (k.clone(), text)
+        })
+        .collect()
+}
+
+/// Truncate to at most `max` characters, marking the cut with an ellipsis.
+pub fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut out: String = s.chars().take(max.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+/// Dashboard link to a single submission.
+pub fn submission_url(base_url: &str, submission_id: uuid::Uuid) -> String {
+    format!("{}/submissions/{submission_id}", base_url.trim_end_matches('/'))
+}
+
+/// Plain-text message used when a module has no `message` template:
+/// a title line, one `field: value` line per field, and the submission link.
+pub fn default_text(ctx: &super::context::ActionContext, base_url: &str) -> String {
+    let mut text = format!("New submission: {}\n", ctx.endpoint.name);
+    for (name, value) in field_pairs(&ctx.submission.data) {
+        text.push_str(&format!("\n{name}: {value}"));
+    }
+    text.push_str(&format!("\n\n{}", submission_url(base_url, ctx.submission.id)));
+    text
+}
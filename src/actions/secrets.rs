@@ -0,0 +1,61 @@
+//! Encryption at rest for secret action config values (bot tokens, webhook URLs).
+//!
+//! A module lists its secret keys in `ActionModule::secret_fields`. Before an
+//! action is saved each plaintext `key` is replaced with `key_enc`, the
+//! hex-encoded `crypto::encrypt` output; the worker opens them again before
+//! `execute`, so modules only ever see plaintext.
+
+use serde_json::Value;
+
+use crate::crypto;
+
+fn enc_key(field: &str) -> String {
+    format!("{field}_enc")
+}
+
+/// Replace each plaintext secret field with its encrypted `_enc` form.
+pub fn seal(mut config: Value, fields: &[&str], key: &str) -> Result<Value, String> {
+    let Some(obj) = config.as_object_mut() else {
+        return Ok(config);
+    };
+    for field in fields {
+        if let Some(Value::String(plain)) = obj.remove(*field) {
+            let enc = crypto::encrypt(&plain, key)?;
+            obj.insert(enc_key(field), Value::String(hex::encode(enc)));
+        }
+    }
+    Ok(config)
+}
+
+/// Decrypt each `_enc` field back to its plaintext key.
+pub fn open(config: &Value, fields: &[&str], key: &str) -> Result<Value, String> {
+    let mut config = config.clone();
+    let Some(obj) = config.as_object_mut() else {
+        return Ok(config);
+    };
+    for field in fields {
+        if let Some(Value::String(enc)) = obj.remove(&enc_key(field)) {
+            let bytes = hex::decode(enc).map_err(|e| format!("Invalid {field}: {e}"))?;
+            let plain = crypto::decrypt(&bytes, key)?;
+            obj.insert(field.to_string(), Value::String(plain));
+        }
+    }
+    Ok(config)
+}
+
+/// On update, keep the stored secret for any field the request left out,
+/// so clients don't have to resend secrets they can't read back.
+pub fn carry_over(config: &mut Value, existing: &Value, fields: &[&str]) {
+    let (Some(obj), Some(old)) = (config.as_object_mut(), existing.as_object()) else {
+        return;
+    };
+    for field in fields {
+        let enc = enc_key(field);
+        if !obj.contains_key(*field)
+            && !obj.contains_key(&enc)
+            && let Some(v) = old.get(&enc)
+        {
+            obj.insert(enc, v.clone());
+        }
+    }
+}
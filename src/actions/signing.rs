@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::crypto;
+use crate::models::Action;
+
+pub const SIGNATURE_HEADER: &str = "Webhooker-Signature";
+pub const TIMESTAMP_HEADER: &str = "Webhooker-Timestamp";
+
+/// Generate a new signing secret (`whsec_` + 32 random bytes, hex-encoded).
+pub fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::random();
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// HMAC-SHA256 over `{timestamp}.{body}`, keyed by the secret string, hex-encoded.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// The `Webhooker-Signature` value: one `v1=<hex>` entry per secret, comma-separated.
+/// During a rotation grace period both the new and old secrets are included.
+pub fn signature_header(secrets: &[String], timestamp: i64, body: &[u8]) -> String {
+    secrets
+        .iter()
+        .map(|s| format!("v1={}", sign(s, timestamp, body)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Decrypt the secrets an action should currently sign with: the active secret,
+/// then the previous one if its grace period hasn't ended.
+pub fn active_secrets(
+    action: &Action,
+    key: &str,
+    now: DateTime<Utc>,
+) -> Result<Vec<String>, String> {
+    let mut secrets = Vec::new();
+    if let Some(ref enc) = action.signing_secret_enc {
+        secrets.push(crypto::decrypt(enc, key)?);
+    }
+    if let (Some(enc), Some(expires)) = (
+        &action.previous_signing_secret_enc,
+        action.previous_secret_expires_at,
+    ) && expires > now
+    {
+        secrets.push(crypto::decrypt(enc, key)?);
+    }
+    Ok(secrets)
+}
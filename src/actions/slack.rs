@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use ipnet::IpNet;
+use serde_json::{json, Value};
+
+use super::context::ActionContext;
+use super::notify::{self, HttpTarget};
+use super::template::{self, Escape};
+use super::{ActionError, ActionModule, ActionResult};
+use crate::config::SsrfMode;
+
+/// Block Kit limits.
+const MAX_BLOCKS: usize = 50;
+const MAX_HEADER: usize = 150;
+const MAX_SECTION_TEXT: usize = 3000;
+
+/// Posts a Block Kit message to a Slack incoming webhook: a header, a section
+/// per submission field, and a button linking to the submission.
+pub struct SlackModule {
+    http: HttpTarget,
+    base_url: String,
+}
+
+impl SlackModule {
+    pub fn new(ssrf_mode: SsrfMode, allowed_cidrs: Vec<IpNet>, base_url: String) -> Self {
+        Self {
+            http: HttpTarget::new(ssrf_mode, allowed_cidrs),
+            base_url,
+        }
+    }
+
+    fn payload(&self, ctx: &ActionContext, config: &Value) -> Result<Value, ActionError> {
+        let title = match config["title"].as_str().filter(|s| !s.is_empty()) {
+            Some(t) => template::render(t, ctx, Escape::None)?,
+            None => format!("New submission: {}", ctx.endpoint.name),
+        };
+
+        let mut blocks = vec![json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": notify::truncate(&title, MAX_HEADER) },
+        })];
+
+        // Leave room for the header and the actions block
+        for (name, value) in notify::field_pairs(&ctx.submission.data)
+            .into_iter()
+            .take(MAX_BLOCKS - 2)
+        {
+            let text = format!("*{}*\n{}", escape(&name), escape(&value));
+            blocks.push(json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": notify::truncate(&text, MAX_SECTION_TEXT) },
+            }));
+        }
+
+        blocks.push(json!({
+            "type": "actions",
+            "elements": [{
+                "type": "button",
+                "text": { "type": "plain_text", "text": "View in Webhooker" },
+                "url": notify::submission_url(&self.base_url, ctx.submission.id),
+            }],
+        }));
+
+        Ok(json!({
+            // Fallback for notifications and clients without Block Kit, read as mrkdwn
+            "text": escape(&title),
+            "blocks": blocks,
+        }))
+    }
+}
+
+/// Escape the characters Slack treats as control sequences in mrkdwn.
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[async_trait]
+impl ActionModule for SlackModule {
+    fn id(&self) -> &str {
+        "slack"
+    }
+
+    fn name(&self) -> &str {
+        "Slack"
+    }
+
+    fn config_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "webhook_url": { "type": "string", "description": "Slack incoming webhook URL (stored encrypted)" },
+                "title": { "type": "string", "description": "Header text (supports template vars). Default: New submission: <endpoint>" }
+            },
+            "required": ["webhook_url"]
+        })
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<(), ActionError> {
+        notify::require_url(config, "webhook_url")?;
+        template::validate(config, &["title"])
+    }
+
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &["webhook_url"]
+    }
+
+    async fn execute(&self, ctx: &ActionContext, config: &Value) -> Result<ActionResult, ActionError> {
+        let url = config["webhook_url"]
+            .as_str()
+            .ok_or_else(|| ActionError::from("webhook_url is required"))?;
+        let req = self.http.client.post(url).json(&self.payload(ctx, config)?);
+        self.http.send(url, req).await
+    }
+}
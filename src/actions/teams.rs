@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use ipnet::IpNet;
+use serde_json::{json, Value};
+
+use super::context::ActionContext;
+use super::notify::{self, HttpTarget};
+use super::template::{self, Escape};
+use super::{ActionError, ActionModule, ActionResult};
+use crate::config::SsrfMode;
+
+/// Posts an Adaptive Card to a Microsoft Teams incoming webhook (or a
+/// Workflows "post to channel" URL), with the submission fields as a FactSet.
+pub struct TeamsModule {
+    http: HttpTarget,
+    base_url: String,
+}
+
+impl TeamsModule {
+    pub fn new(ssrf_mode: SsrfMode, allowed_cidrs: Vec<IpNet>, base_url: String) -> Self {
+        Self {
+            http: HttpTarget::new(ssrf_mode, allowed_cidrs),
+            base_url,
+        }
+    }
+
+    fn payload(&self, ctx: &ActionContext, config: &Value) -> Result<Value, ActionError> {
+        let title = match config["title"].as_str().filter(|s| !s.is_empty()) {
+            Some(t) => template::render(t, ctx, Escape::None)?,
+            None => format!("New submission: {}", ctx.endpoint.name),
+        };
+
+        let mut body = vec![json!({
+            "type": "TextBlock",
+            "text": title,
+            "weight": "Bolder",
+            "size": "Medium",
+            "wrap": true,
+        })];
+        if let Some(message) = config["message"].as_str().filter(|s| !s.is_empty()) {
+            body.push(json!({
+                "type": "TextBlock",
+                "text": template::render(message, ctx, Escape::None)?,
+                "wrap": true,
+            }));
+        }
+        let facts: Vec<Value> = notify::field_pairs(&ctx.submission.data)
+            .into_iter()
+            .map(|(name, value)| json!({ "title": name, "value": value }))
+            .collect();
+        body.push(json!({ "type": "FactSet", "facts": facts }));
+
+        Ok(json!({
+            "type": "message",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "contentUrl": null,
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "body": body,
+                    "actions": [{
+                        "type": "Action.OpenUrl",
+                        "title": "View in Webhooker",
+                        "url": notify::submission_url(&self.base_url, ctx.submission.id),
+                    }],
+                },
+            }],
+        }))
+    }
+}
+
+#[async_trait]
+impl ActionModule for TeamsModule {
+    fn id(&self) -> &str {
+        "teams"
+    }
+
+    fn name(&self) -> &str {
+        "Microsoft Teams"
+    }
+
+    fn config_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "webhook_url": { "type": "string", "description": "Teams incoming webhook or Workflows URL (stored encrypted)" },
+                "title": { "type": "string", "description": "Card title (supports template vars). Default: New submission: <endpoint>" },
+                "message": { "type": "string", "description": "Text below the title (supports template vars)" }
+            },
+            "required": ["webhook_url"]
+        })
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<(), ActionError> {
+        notify::require_url(config, "webhook_url")?;
+        template::validate(config, &["title", "message"])
+    }
+
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &["webhook_url"]
+    }
+
+    async fn execute(&self, ctx: &ActionContext, config: &Value) -> Result<ActionResult, ActionError> {
+        let url = config["webhook_url"]
+            .as_str()
+            .ok_or_else(|| ActionError::from("webhook_url is required"))?;
+        let req = self.http.client.post(url).json(&self.payload(ctx, config)?);
+        self.http.send(url, req).await
+    }
+}
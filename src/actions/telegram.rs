@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use ipnet::IpNet;
+use serde_json::{json, Value};
+
+use super::context::ActionContext;
+use super::notify::{self, HttpTarget};
+use super::template::{self, Escape};
+use super::{ActionError, ActionModule, ActionResult};
+use crate::config::SsrfMode;
+
+const DEFAULT_API_URL: &str = "https://api.telegram.org";
+/// Telegram's limit on message text.
+const MAX_TEXT: usize = 4096;
+
+/// Sends a message to a chat via the Telegram Bot API.
+pub struct TelegramModule {
+    http: HttpTarget,
+    base_url: String,
+}
+
+impl TelegramModule {
+    pub fn new(ssrf_mode: SsrfMode, allowed_cidrs: Vec<IpNet>, base_url: String) -> Self {
+        Self {
+            http: HttpTarget::new(ssrf_mode, allowed_cidrs),
+            base_url,
+        }
+    }
+}
+
+#[async_trait]
+impl ActionModule for TelegramModule {
+    fn id(&self) -> &str {
+        "telegram"
+    }
+
+    fn name(&self) -> &str {
+        "Telegram"
+    }
+
+    fn config_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "bot_token": { "type": "string", "description": "Bot token from @BotFather (stored encrypted)" },
+                "chat_id": { "type": "string", "description": "Chat, group or channel id (e.g. -1001234567890 or @channel)" },
+                "message": { "type": "string", "description": "Message text (supports template vars). Default: all fields and a link" },
+                "parse_mode": { "type": "string", "enum": ["", "HTML"], "default": "", "description": "HTML escapes template values" },
+                "api_url": { "type": "string", "description": "Bot API server", "default": DEFAULT_API_URL }
+            },
+            "required": ["bot_token", "chat_id"]
+        })
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<(), ActionError> {
+        config
+            .get("bot_token")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| ActionError::from("bot_token is required"))?;
+        match config.get("chat_id") {
+            Some(Value::String(s)) if !s.is_empty() => {}
+            Some(Value::Number(_)) => {}
+            _ => return Err(ActionError::from("chat_id is required")),
+        }
+        if config.get("api_url").is_some_and(|v| !v.is_null()) {
+            notify::require_url(config, "api_url")?;
+        }
+        template::validate(config, &["message"])?;
+        match config["parse_mode"].as_str() {
+            None | Some("") | Some("HTML") => Ok(()),
+            Some(other) => Err(ActionError::from(format!("Unsupported parse_mode: {other}"))),
+        }
+    }
+
+    fn secret_fields(&self) -> &'static [&'static str] {
+        &["bot_token"]
+    }
+
+    async fn execute(&self, ctx: &ActionContext, config: &Value) -> Result<ActionResult, ActionError> {
+        let token = config["bot_token"]
+            .as_str()
+            .ok_or_else(|| ActionError::from("bot_token is required"))?;
+        let html = config["parse_mode"].as_str() == Some("HTML");
+
+        let text = match config["message"].as_str().filter(|s| !s.is_empty()) {
+            Some(tmpl) if html => template::render(tmpl, ctx, Escape::Html)?,
+            Some(tmpl) => template::render(tmpl, ctx, Escape::None)?,
+            None => notify::default_text(ctx, &self.base_url),
+        };
+
+        let mut body = json!({
+            "chat_id": config["chat_id"],
+            "text": notify::truncate(&text, MAX_TEXT),
+        });
+        if html {
+            body["parse_mode"] = json!("HTML");
+        }
+
+        let api_url = config["api_url"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .unwrap_or(DEFAULT_API_URL);
+        let url = format!("{}/bot{token}/sendMessage", api_url.trim_end_matches('/'));
+        let req = self.http.client.post(&url).json(&body);
+        let mut result = self.http.send(&url, req).await?;
+        // The URL carries the token; make sure it never lands in the action log
+        if let Some(resp) = result.response.as_mut() {
+            resp["body"] = json!(resp["body"].as_str().unwrap_or_default().replace(token, "***"));
+        }
+        Ok(result)
+    }
+}
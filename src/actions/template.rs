@@ -1,61 +1,646 @@
-use regex::Regex;
-use std::sync::LazyLock;
+//! Action templates.
+//!
+//! ```text
+//! New lead from {{ data.name | default("someone") }}
+//! {% for field in data %}{{ field.key | upper }}: {{ field.value }}
+//! {% endfor %}{% if data.phone %}Call {{ data.phone }}{% else %}No phone{% endif %}
+//! ```
+//!
+//! `{{ expr | filter | filter(arg) }}` outputs a value. Expressions and `if`
+//! tests use the condition language (see `condition`), so paths like
+//! `data.address.city` and `data.items[0]` work and `if` can compare.
+//! `{% for x in list %}` iterates arrays; over an object each item is
+//! `{key, value}`, or use `{% for k, v in obj %}`. Inside loops, `loop.index`,
+//! `loop.first` and `loop.last` are available. `{%-` and `-%}` trim
+//! surrounding whitespace.
+//!
+//! Output is escaped for where it ends up (see `Escape`). In JSON templates
+//! only strings are escaped: numbers, booleans, objects and arrays are
+//! emitted as JSON, so `{"items": {{ data.items }}}` is valid, as is the
+//! `json` filter's output. `raw` disables escaping entirely.
 
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::{json, Value};
+
+use super::condition::Condition;
 use super::context::ActionContext;
+use super::ActionError;
 
-static TEMPLATE_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"\{\{(\w+(?:\.\w+)*)\}\}").unwrap());
+/// Names available at the top level of every template.
+const ROOTS: &[&str] = &[
+    "data",
+    "extras",
+    "metadata",
+    "raw",
+    "endpoint",
+    "project",
+    "tenant",
+    "submission",
+];
 
-/// Replace {{variable}} placeholders in a template string with values from the context.
-pub fn render(template: &str, ctx: &ActionContext) -> String {
-    TEMPLATE_RE
-        .replace_all(template, |caps: &regex::Captures| {
-            let path = &caps[1];
-            resolve(path, ctx).unwrap_or_default()
-        })
-        .to_string()
+/// Deepest nesting of `if` and `for` blocks.
+const MAX_DEPTH: usize = 32;
+
+/// Loop iterations allowed in one render, across all loops.
+const MAX_ITERATIONS: usize = 10_000;
+
+/// Largest rendered output, in bytes.
+const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// How interpolated values are escaped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Escape {
+    /// Plain text, or text that is serialized as a JSON value afterwards.
+    None,
+    /// HTML bodies (email, Telegram HTML).
+    Html,
+    /// A hand-written JSON body: strings are escaped for a string literal,
+    /// other values are emitted as JSON.
+    Json,
+    /// HTTP header and email header values: control characters become spaces.
+    Header,
+}
+
+/// Render a template against an action's submission. Unknown names render
+/// as empty here; `validate` rejects them when the action is saved.
+pub fn render(template: &str, ctx: &ActionContext, escape: Escape) -> Result<String, ActionError> {
+    let parsed = Template::parse_lenient(template)
+        .map_err(|e| ActionError::from(format!("Invalid template: {e}")))?;
+    parsed
+        .render(&context(ctx), escape)
+        .map_err(|e| ActionError::from(format!("Template render failed: {e}")))
+}
+
+/// Check that the given config keys, where present, hold valid templates.
+pub fn validate(config: &Value, fields: &[&str]) -> Result<(), ActionError> {
+    for field in fields {
+        if let Some(tmpl) = config.get(*field).and_then(|v| v.as_str()) {
+            Template::parse(tmpl)
+                .map_err(|e| ActionError::from(format!("Invalid template in {field}: {e}")))?;
+        }
+    }
+    Ok(())
+}
+
+/// The values a template can reference.
+fn context(ctx: &ActionContext) -> Value {
+    json!({
+        "data": ctx.submission.data,
+        "extras": ctx.submission.extras,
+        "metadata": ctx.submission.metadata,
+        "raw": ctx.submission.raw,
+        "endpoint": {
+            "id": ctx.endpoint.id,
+            "name": ctx.endpoint.name,
+            "slug": ctx.endpoint.slug,
+        },
+        "project": { "name": ctx.project.name, "slug": ctx.project.slug },
+        "tenant": { "name": ctx.tenant.name },
+        "submission": {
+            "id": ctx.submission.id,
+            "created_at": ctx.submission.created_at.to_rfc3339(),
+        },
+    })
 }
 
-/// Like `render()` but HTML-escapes interpolated values. Use for HTML email bodies.
-pub fn render_html(template: &str, ctx: &ActionContext) -> String {
-    TEMPLATE_RE
-        .replace_all(template, |caps: &regex::Captures| {
-            let path = &caps[1];
-            html_escape(&resolve(path, ctx).unwrap_or_default())
+/// A parsed template.
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+enum Node {
+    Text(String),
+    Output(Condition, Vec<Filter>),
+    If(Vec<(Condition, Vec<Node>)>, Vec<Node>),
+    For {
+        key: Option<String>,
+        value: String,
+        iter: Condition,
+        body: Vec<Node>,
+    },
+}
+
+enum Filter {
+    Upper,
+    Lower,
+    Trim,
+    Length,
+    Default(Value),
+    Json,
+    Date(String),
+    UrlEncode,
+    Raw,
+}
+
+impl Template {
+    /// Parse, rejecting names that aren't a root or an enclosing loop variable.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        Self::parse_with(source, true)
+    }
+
+    /// Parse, letting unknown names through to render as empty.
+    pub fn parse_lenient(source: &str) -> Result<Self, String> {
+        Self::parse_with(source, false)
+    }
+
+    fn parse_with(source: &str, strict: bool) -> Result<Self, String> {
+        let mut parser = Parser {
+            source,
+            pos: 0,
+            trim_next: false,
+            scope: ROOTS.iter().map(|r| r.to_string()).collect(),
+            strict,
+            depth: 0,
+        };
+        let (nodes, end) = parser.block(&[])?;
+        if let Some(tag) = end {
+            return Err(format!("Unexpected {{% {} %}} at position {}", tag.keyword, tag.pos));
+        }
+        Ok(Self { nodes })
+    }
+
+    /// Render against a context object keyed by root name. Fails once the
+    /// output or the loop iterations go over their limits.
+    pub fn render(&self, ctx: &Value, escape: Escape) -> Result<String, String> {
+        let mut ctx = ctx.clone();
+        let mut out = String::new();
+        let mut iterations = 0;
+        render_nodes(&self.nodes, &mut ctx, escape, &mut out, &mut iterations)?;
+        Ok(out)
+    }
+}
+
+// ── Parser ──────────────────────────────────────────────────────
+
+struct Parser<'a> {
+    source: &'a str,
+    /// Byte offset of the next unread character.
+    pos: usize,
+    /// Set by `-%}` / `-}}`: strip leading whitespace from the next text.
+    trim_next: bool,
+    /// Names that may start a path: the roots plus enclosing loop variables.
+    scope: Vec<String>,
+    /// Reject names outside `scope`; otherwise they render as empty.
+    strict: bool,
+    /// Current block nesting, bounded by `MAX_DEPTH`.
+    depth: usize,
+}
+
+/// A block tag that ended the current block (`else`, `endif`, ...).
+struct EndTag {
+    keyword: String,
+    rest: String,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn char_pos(&self, byte: usize) -> usize {
+        self.source[..byte].chars().count()
+    }
+
+    fn expr(&self, source: &str, pos: usize) -> Result<Condition, String> {
+        let parsed = if self.strict {
+            let scope: Vec<&str> = self.scope.iter().map(|s| s.as_str()).collect();
+            Condition::parse_scoped(source, &scope)
+        } else {
+            Condition::parse_lenient(source)
+        };
+        parsed.map_err(|e| format!("{e} (in tag at position {pos})"))
+    }
+
+    /// Parse nodes until EOF or one of `terminators`, which is returned.
+    fn block(&mut self, terminators: &[&str]) -> Result<(Vec<Node>, Option<EndTag>), String> {
+        let mut nodes = Vec::new();
+
+        loop {
+            let rest = &self.source[self.pos..];
+            let next = [rest.find("{{"), rest.find("{%")]
+                .into_iter()
+                .flatten()
+                .min();
+            let text_end = next.map_or(self.source.len(), |i| self.pos + i);
+
+            let mut text = &self.source[self.pos..text_end];
+            if std::mem::take(&mut self.trim_next) {
+                text = text.trim_start();
+            }
+            if next.is_none() {
+                push_text(&mut nodes, text);
+                return Ok((nodes, None));
+            }
+            let tag_start = text_end;
+
+            let is_output = self.source[tag_start..].starts_with("{{");
+            let close = if is_output { "}}" } else { "%}" };
+            let pos = self.char_pos(tag_start);
+            let inner_start = tag_start + 2;
+            let inner_len = self.source[inner_start..]
+                .find(close)
+                .ok_or_else(|| format!("Unclosed tag at position {pos}"))?;
+            let mut inner = &self.source[inner_start..inner_start + inner_len];
+            self.pos = inner_start + inner_len + 2;
+
+            if let Some(stripped) = inner.strip_prefix('-') {
+                inner = stripped;
+                text = text.trim_end();
+            }
+            if let Some(stripped) = inner.strip_suffix('-') {
+                inner = stripped;
+                self.trim_next = true;
+            }
+            push_text(&mut nodes, text);
+            let inner = inner.trim();
+
+            if is_output {
+                nodes.push(self.output(inner, pos)?);
+                continue;
+            }
+
+            let (keyword, rest) = inner.split_once(char::is_whitespace).unwrap_or((inner, ""));
+            let rest = rest.trim();
+            if matches!(keyword, "if" | "for") && self.depth >= MAX_DEPTH {
+                return Err(format!(
+                    "Blocks nested too deeply at position {pos}: at most {MAX_DEPTH} levels"
+                ));
+            }
+            match keyword {
+                "if" => {
+                    self.depth += 1;
+                    nodes.push(self.if_block(rest, pos)?);
+                    self.depth -= 1;
+                }
+                "for" => {
+                    self.depth += 1;
+                    nodes.push(self.for_block(rest, pos)?);
+                    self.depth -= 1;
+                }
+                _ if terminators.contains(&keyword) => {
+                    return Ok((
+                        nodes,
+                        Some(EndTag {
+                            keyword: keyword.to_string(),
+                            rest: rest.to_string(),
+                            pos,
+                        }),
+                    ));
+                }
+                "elif" | "else" | "endif" | "endfor" => {
+                    return Err(format!("Unexpected {{% {keyword} %}} at position {pos}"));
+                }
+                _ => return Err(format!("Unknown tag '{keyword}' at position {pos}")),
+            }
+        }
+    }
+
+    fn output(&self, inner: &str, pos: usize) -> Result<Node, String> {
+        let mut parts = split_filters(inner).into_iter();
+        let expr = parts.next().unwrap_or_default();
+        if expr.trim().is_empty() {
+            return Err(format!("Empty expression at position {pos}"));
+        }
+        let expr = self.expr(expr, pos)?;
+        let filters = parts
+            .map(|f| parse_filter(f.trim()).map_err(|e| format!("{e} (in tag at position {pos})")))
+            .collect::<Result<_, _>>()?;
+        Ok(Node::Output(expr, filters))
+    }
+
+    fn if_block(&mut self, test: &str, pos: usize) -> Result<Node, String> {
+        let mut branches = Vec::new();
+        let mut test = self.expr(test, pos)?;
+        loop {
+            let (body, end) = self.block(&["elif", "else", "endif"])?;
+            branches.push((test, body));
+            let end = end.ok_or_else(|| format!("Unclosed {{% if %}} at position {pos}"))?;
+            match end.keyword.as_str() {
+                "elif" => test = self.expr(&end.rest, end.pos)?,
+                "else" => {
+                    let (otherwise, end) = self.block(&["endif"])?;
+                    end.ok_or_else(|| format!("Unclosed {{% if %}} at position {pos}"))?;
+                    return Ok(Node::If(branches, otherwise));
+                }
+                _ => return Ok(Node::If(branches, Vec::new())),
+            }
+        }
+    }
+
+    fn for_block(&mut self, spec: &str, pos: usize) -> Result<Node, String> {
+        let (vars, iter) = spec
+            .split_once(" in ")
+            .ok_or_else(|| format!("Expected {{% for x in ... %}} at position {pos}"))?;
+        let mut names = vars.split(',').map(str::trim);
+        let (key, value) = match (names.next(), names.next(), names.next()) {
+            (Some(v), None, _) => (None, v),
+            (Some(k), Some(v), None) => (Some(k.to_string()), v),
+            _ => return Err(format!("Expected one or two loop variables at position {pos}")),
+        };
+        for name in key.as_deref().into_iter().chain([value]) {
+            let valid = name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if !valid || name == "loop" {
+                return Err(format!("Invalid loop variable '{name}' at position {pos}"));
+            }
+        }
+        let iter = self.expr(iter.trim(), pos)?;
+
+        let depth = self.scope.len();
+        self.scope.extend(key.iter().cloned());
+        self.scope.push(value.to_string());
+        self.scope.push("loop".to_string());
+        let result = self.block(&["endfor"]);
+        self.scope.truncate(depth);
+
+        let (body, end) = result?;
+        end.ok_or_else(|| format!("Unclosed {{% for %}} at position {pos}"))?;
+        Ok(Node::For {
+            key,
+            value: value.to_string(),
+            iter,
+            body,
         })
-        .to_string()
+    }
 }
 
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&#x27;")
+fn push_text(nodes: &mut Vec<Node>, text: &str) {
+    if !text.is_empty() {
+        nodes.push(Node::Text(text.to_string()));
+    }
+}
+
+/// Split `expr | f1 | f2(..)` on single pipes outside string literals.
+fn split_filters(inner: &str) -> Vec<&str> {
+    let bytes = inner.as_bytes();
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match (quote, bytes[i]) {
+            (Some(_), b'\\') => i += 1,
+            (Some(q), c) if c == q => quote = None,
+            (None, c @ (b'"' | b'\'')) => quote = Some(c),
+            (None, b'|') if bytes.get(i + 1) == Some(&b'|') => i += 1,
+            (None, b'|') => {
+                parts.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(&inner[start..]);
+    parts
+}
+
+fn parse_filter(spec: &str) -> Result<Filter, String> {
+    let (name, args) = match spec.split_once('(') {
+        Some((name, rest)) => {
+            let args = rest
+                .strip_suffix(')')
+                .ok_or_else(|| format!("Expected ')' after arguments to filter '{}'", name.trim()))?;
+            // Arguments are literals, parsed as a condition-language list
+            let list = Condition::parse_scoped(&format!("[{args}]"), &[])
+                .map_err(|e| format!("Invalid arguments to filter '{}': {e}", name.trim()))?;
+            (name.trim(), list.value(&Value::Null).as_array().cloned().unwrap_or_default())
+        }
+        None => (spec, Vec::new()),
+    };
+
+    let arity = |n: usize| {
+        if args.len() == n {
+            Ok(())
+        } else {
+            Err(format!("Filter '{name}' takes {n} argument(s), got {}", args.len()))
+        }
+    };
+    let filter = match name {
+        "upper" => Filter::Upper,
+        "lower" => Filter::Lower,
+        "trim" => Filter::Trim,
+        "length" => Filter::Length,
+        "json" => Filter::Json,
+        "urlencode" => Filter::UrlEncode,
+        "raw" => Filter::Raw,
+        "default" => {
+            arity(1)?;
+            Filter::Default(args[0].clone())
+        }
+        "date" => {
+            let format = match args.as_slice() {
+                [] => "%Y-%m-%d".to_string(),
+                [Value::String(f)] => f.clone(),
+                _ => return Err("Filter 'date' takes one format string".to_string()),
+            };
+            if StrftimeItems::new(&format).any(|item| matches!(item, Item::Error)) {
+                return Err(format!("Invalid date format '{format}'"));
+            }
+            return Ok(Filter::Date(format));
+        }
+        "" => return Err("Empty filter".to_string()),
+        other => return Err(format!("Unknown filter '{other}'")),
+    };
+    if !matches!(filter, Filter::Default(_)) {
+        arity(0)?;
+    }
+    Ok(filter)
+}
+
+// ── Rendering ───────────────────────────────────────────────────
+
+/// Render `nodes` into `out`, counting loop iterations in `iterations`.
+fn render_nodes(
+    nodes: &[Node],
+    ctx: &mut Value,
+    escape: Escape,
+    out: &mut String,
+    iterations: &mut usize,
+) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Output(expr, filters) => {
+                let (value, safe) = apply_filters(expr.value(ctx), filters, escape);
+                let text = to_text(&value);
+                // Already JSON text, as the previous engine inserted it
+                let json = escape == Escape::Json && !value.is_string();
+                if safe || json {
+                    out.push_str(&text);
+                } else {
+                    out.push_str(&escape_text(&text, escape));
+                }
+            }
+            Node::If(branches, otherwise) => {
+                let body = branches
+                    .iter()
+                    .find(|(test, _)| test.matches(ctx))
+                    .map_or(otherwise, |(_, body)| body);
+                render_nodes(body, ctx, escape, out, iterations)?;
+            }
+            Node::For {
+                key,
+                value,
+                iter,
+                body,
+            } => {
+                let iterable = iter.value(ctx);
+                let is_object = iterable.is_object();
+                let items: Vec<(Value, Value)> = match iterable {
+                    Value::Array(items) => items
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, v)| (json!(i), v))
+                        .collect(),
+                    Value::Object(map) => map.into_iter().map(|(k, v)| (json!(k), v)).collect(),
+                    _ => Vec::new(),
+                };
+                let len = items.len();
+
+                let mut names: Vec<&str> = key.iter().map(|k| k.as_str()).collect();
+                names.extend([value.as_str(), "loop"]);
+                let saved: Vec<Option<Value>> = names.iter().map(|n| ctx.get(*n).cloned()).collect();
+
+                for (i, (k, v)) in items.into_iter().enumerate() {
+                    *iterations += 1;
+                    if *iterations > MAX_ITERATIONS {
+                        return Err(format!("more than {MAX_ITERATIONS} loop iterations"));
+                    }
+                    let vars = ctx.as_object_mut().expect("template context is an object");
+                    match key {
+                        Some(key) => {
+                            vars.insert(key.clone(), k);
+                            vars.insert(value.clone(), v);
+                        }
+                        None if is_object => {
+                            vars.insert(value.clone(), json!({ "key": k, "value": v }));
+                        }
+                        None => {
+                            vars.insert(value.clone(), v);
+                        }
+                    }
+                    vars.insert(
+                        "loop".to_string(),
+                        json!({
+                            "index": i + 1,
+                            "index0": i,
+                            "first": i == 0,
+                            "last": i + 1 == len,
+                        }),
+                    );
+                    render_nodes(body, ctx, escape, out, iterations)?;
+                }
+
+                let vars = ctx.as_object_mut().expect("template context is an object");
+                for (name, prev) in names.into_iter().zip(saved) {
+                    match prev {
+                        Some(prev) => vars.insert(name.to_string(), prev),
+                        None => vars.remove(name),
+                    };
+                }
+            }
+        }
+        if out.len() > MAX_OUTPUT_BYTES {
+            return Err(format!("output is larger than {MAX_OUTPUT_BYTES} bytes"));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the filtered value and whether it should skip escaping.
+fn apply_filters(mut value: Value, filters: &[Filter], escape: Escape) -> (Value, bool) {
+    let mut safe = false;
+    for filter in filters {
+        value = match filter {
+            Filter::Upper => json!(to_text(&value).to_uppercase()),
+            Filter::Lower => json!(to_text(&value).to_lowercase()),
+            Filter::Trim => json!(to_text(&value).trim()),
+            Filter::Length => match &value {
+                Value::Array(a) => json!(a.len()),
+                Value::Object(o) => json!(o.len()),
+                Value::Null => json!(0),
+                other => json!(to_text(other).chars().count()),
+            },
+            Filter::Default(fallback) => match &value {
+                Value::Null => fallback.clone(),
+                Value::String(s) if s.is_empty() => fallback.clone(),
+                _ => value,
+            },
+            Filter::Json => {
+                // Already valid JSON, so it can go straight into a JSON body
+                safe = safe || escape == Escape::Json;
+                json!(value.to_string())
+            }
+            Filter::Date(format) => match parse_date(&value) {
+                Some(date) => json!(date.format_with_items(StrftimeItems::new(format)).to_string()),
+                None => value,
+            },
+            Filter::UrlEncode => json!(urlencode(&to_text(&value))),
+            Filter::Raw => {
+                safe = true;
+                value
+            }
+        };
+    }
+    (value, safe)
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
 }
 
-fn resolve(path: &str, ctx: &ActionContext) -> Option<String> {
-    let parts: Vec<&str> = path.splitn(2, '.').collect();
-    match parts.as_slice() {
-        ["data", field] => json_string_field(&ctx.submission.data, field),
-        ["extras", field] => json_string_field(&ctx.submission.extras, field),
-        ["metadata", field] => json_string_field(&ctx.submission.metadata, field),
-        ["endpoint", "name"] => Some(ctx.endpoint.name.clone()),
-        ["endpoint", "slug"] => Some(ctx.endpoint.slug.clone()),
-        ["endpoint", "id"] => Some(ctx.endpoint.id.to_string()),
-        ["project", "name"] => Some(ctx.project.name.clone()),
-        ["project", "slug"] => Some(ctx.project.slug.clone()),
-        ["tenant", "name"] => Some(ctx.tenant.name.clone()),
-        ["submission", "id"] => Some(ctx.submission.id.to_string()),
-        ["submission", "created_at"] => Some(ctx.submission.created_at.to_rfc3339()),
+/// RFC 3339 timestamps, plain dates, or Unix seconds.
+fn parse_date(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .map(|d| d.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    .map(|d| d.and_utc())
+            }),
+        Value::Number(n) => n.as_i64().and_then(|secs| DateTime::from_timestamp(secs, 0)),
         _ => None,
     }
 }
 
-fn json_string_field(value: &serde_json::Value, field: &str) -> Option<String> {
-    match value.get(field)? {
-        serde_json::Value::String(s) => Some(s.clone()),
-        serde_json::Value::Null => None,
-        other => Some(other.to_string()),
+fn urlencode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
     }
+    out
+}
+
+fn escape_text(s: &str, escape: Escape) -> String {
+    match escape {
+        Escape::None => s.to_string(),
+        Escape::Html => html_escape(s),
+        Escape::Json => {
+            let quoted = Value::String(s.to_string()).to_string();
+            quoted[1..quoted.len() - 1].to_string()
+        }
+        Escape::Header => s
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect(),
+    }
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
 }
@@ -5,7 +5,8 @@ use ipnet::IpNet;
 use serde_json::json;
 
 use super::context::ActionContext;
-use super::template;
+use super::signing;
+use super::template::{self, Escape};
 use super::{ActionError, ActionModule, ActionResult, ActionStatus};
 use crate::config::SsrfMode;
 
@@ -45,7 +46,7 @@ impl ActionModule for WebhookModule {
                 "url": { "type": "string", "description": "Webhook URL" },
                 "method": { "type": "string", "enum": ["POST", "PUT"], "default": "POST" },
                 "headers": { "type": "object", "description": "Custom headers" },
-                "body_template": { "type": "string", "description": "Custom body template (JSON). If empty, sends full submission data." }
+                "body_template": { "type": "string", "description": "Custom body template (JSON). Values are escaped for JSON strings; use {{data | json}} to embed objects. If empty, sends full submission data." }
             },
             "required": ["url"]
         })
@@ -57,9 +58,23 @@ impl ActionModule for WebhookModule {
             .and_then(|v| v.as_str())
             .filter(|s| !s.is_empty())
             .ok_or_else(|| ActionError::from("url is required"))?;
+        template::validate(config, &["url", "body_template"])?;
+        if let Some(headers) = config.get("headers").and_then(|v| v.as_object()) {
+            for (name, value) in headers {
+                if let Some(tmpl) = value.as_str() {
+                    template::Template::parse(tmpl).map_err(|e| {
+                        ActionError::from(format!("Invalid template in header '{name}': {e}"))
+                    })?;
+                }
+            }
+        }
         Ok(())
     }
 
+    fn signs_requests(&self) -> bool {
+        true
+    }
+
     async fn execute(
         &self,
         ctx: &ActionContext,
@@ -68,7 +83,7 @@ impl ActionModule for WebhookModule {
         let url = config["url"]
             .as_str()
             .ok_or_else(|| ActionError::from("url is required"))?;
-        let url = template::render(url, ctx);
+        let url = template::render(url, ctx, Escape::None)?;
 
         validate_url(&url, &self.ssrf_mode, &self.allowed_cidrs)?;
 
@@ -85,8 +100,10 @@ impl ActionModule for WebhookModule {
                     "submitted_at": &ctx.submission.created_at,
                 })
             } else {
-                let rendered = template::render(tmpl, ctx);
-                serde_json::from_str(&rendered).unwrap_or(json!(rendered))
+                let rendered = template::render(tmpl, ctx, Escape::Json)?;
+                serde_json::from_str(&rendered).map_err(|e| {
+                    ActionError::from(format!("body_template didn't render valid JSON: {e}"))
+                })?
             }
         } else {
             json!({
@@ -108,8 +125,13 @@ impl ActionModule for WebhookModule {
 
         if let Some(headers) = config.get("headers").and_then(|v| v.as_object()) {
             for (k, v) in headers {
+                if k.eq_ignore_ascii_case(signing::SIGNATURE_HEADER)
+                    || k.eq_ignore_ascii_case(signing::TIMESTAMP_HEADER)
+                {
+                    continue;
+                }
                 if let Some(val) = v.as_str() {
-                    let rendered = template::render(val, ctx);
+                    let rendered = template::render(val, ctx, Escape::Header)?;
                     if rendered.contains('\r') || rendered.contains('\n') {
                         return Err(ActionError::from(format!(
                             "Header value for '{k}' contains invalid characters"
@@ -120,8 +142,21 @@ impl ActionModule for WebhookModule {
             }
         }
 
+        let payload = serde_json::to_vec(&body)
+            .map_err(|e| ActionError::from(format!("Failed to serialize body: {e}")))?;
+
+        if !ctx.signing_secrets.is_empty() {
+            let timestamp = chrono::Utc::now().timestamp();
+            req = req
+                .header(signing::TIMESTAMP_HEADER, timestamp.to_string())
+                .header(
+                    signing::SIGNATURE_HEADER,
+                    signing::signature_header(&ctx.signing_secrets, timestamp, &payload),
+                );
+        }
+
         let resp = req
-            .json(&body)
+            .body(payload)
             .send()
             .await
             .map_err(|e| ActionError::from(format!("Webhook request failed: {e}")))?;
@@ -152,7 +187,7 @@ impl ActionModule for WebhookModule {
 }
 
 /// Validate a webhook URL to prevent SSRF attacks.
-fn validate_url(url: &str, mode: &SsrfMode, allowed_cidrs: &[IpNet]) -> Result<(), ActionError> {
+pub(crate) fn validate_url(url: &str, mode: &SsrfMode, allowed_cidrs: &[IpNet]) -> Result<(), ActionError> {
     let parsed = reqwest::Url::parse(url)
         .map_err(|e| ActionError::from(format!("Invalid webhook URL: {e}")))?;
 
@@ -1,7 +1,11 @@
 use std::net::IpAddr;
+use std::sync::Arc;
 
 use ipnet::IpNet;
 
+use crate::quota::Quotas;
+use crate::submission::access::GeoIp;
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database_url: String,
@@ -18,6 +22,15 @@ pub struct Config {
     pub worker_count: usize,
     pub log_level: String,
     pub smtp: Option<SmtpConfig>,
+    pub storage: StorageConfig,
+    /// Replaces every CAPTCHA provider's verify URL, e.g. for a local mock.
+    pub captcha_verify_url: Option<String>,
+    pub rate_limit_store: RateLimitBackend,
+    /// Quotas for tenants that don't have their own.
+    pub default_quotas: Quotas,
+    /// MaxMind-format database for endpoint country rules, opened from
+    /// `WEBHOOKER_GEOIP_DB` while loading the config.
+    pub geoip: Option<Arc<GeoIp>>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,12 +42,36 @@ pub struct SmtpConfig {
     pub from: String,
 }
 
+#[derive(Debug, Clone)]
+pub enum StorageConfig {
+    Local { path: String },
+    S3(S3Config),
+}
+
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum RegistrationMode {
     Open,
     Closed,
 }
 
+/// Where rate limit counters are kept (`WEBHOOKER_RATE_LIMIT_STORE`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateLimitBackend {
+    /// Per process; each replica limits on its own.
+    Memory,
+    /// The `rate_limits` table, shared by all replicas.
+    Postgres,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SsrfMode {
     Strict,
@@ -116,6 +153,53 @@ impl Config {
             _ => None,
         };
 
+        let storage = match env_or("WEBHOOKER_STORAGE", "local").as_str() {
+            "s3" => StorageConfig::S3(S3Config {
+                endpoint: env_required("WEBHOOKER_S3_ENDPOINT")?,
+                bucket: env_required("WEBHOOKER_S3_BUCKET")?,
+                region: env_or("WEBHOOKER_S3_REGION", "us-east-1"),
+                access_key: env_required("WEBHOOKER_S3_ACCESS_KEY")?,
+                secret_key: env_required("WEBHOOKER_S3_SECRET_KEY")?,
+            }),
+            "local" => StorageConfig::Local {
+                path: env_or("WEBHOOKER_STORAGE_PATH", "./data/uploads"),
+            },
+            other => {
+                return Err(format!("Invalid WEBHOOKER_STORAGE '{other}': must be local or s3"))
+            }
+        };
+
+        let captcha_verify_url = std::env::var("WEBHOOKER_CAPTCHA_VERIFY_URL")
+            .ok()
+            .filter(|s| !s.trim().is_empty());
+
+        let rate_limit_store = match env_or("WEBHOOKER_RATE_LIMIT_STORE", "memory").as_str() {
+            "memory" => RateLimitBackend::Memory,
+            "postgres" => RateLimitBackend::Postgres,
+            other => {
+                return Err(format!(
+                    "Invalid WEBHOOKER_RATE_LIMIT_STORE '{other}': must be memory or postgres"
+                ))
+            }
+        };
+
+        let geoip = std::env::var("WEBHOOKER_GEOIP_DB")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .map(|path| {
+                GeoIp::open(&path)
+                    .map(Arc::new)
+                    .map_err(|e| format!("Invalid WEBHOOKER_GEOIP_DB '{path}': {e}"))
+            })
+            .transpose()?;
+
+        let default_quotas = Quotas {
+            daily_submissions: env_quota("WEBHOOKER_QUOTA_DAILY_SUBMISSIONS")?,
+            monthly_submissions: env_quota("WEBHOOKER_QUOTA_MONTHLY_SUBMISSIONS")?,
+            stored_submissions: env_quota("WEBHOOKER_QUOTA_STORED_SUBMISSIONS")?,
+            storage_bytes: env_quota("WEBHOOKER_QUOTA_STORAGE_BYTES")?,
+        };
+
         Ok(Config {
             database_url,
             jwt_secret,
@@ -131,6 +215,11 @@ impl Config {
             worker_count,
             log_level,
             smtp,
+            storage,
+            captcha_verify_url,
+            rate_limit_store,
+            default_quotas,
+            geoip,
         })
     }
 }
@@ -142,3 +231,17 @@ fn env_required(key: &str) -> Result<String, String> {
 fn env_or(key: &str, default: &str) -> String {
     std::env::var(key).unwrap_or_else(|_| default.to_string())
 }
+
+/// An optional positive limit; unset or empty means unlimited.
+fn env_quota(key: &str) -> Result<Option<i64>, String> {
+    match std::env::var(key).ok().filter(|s| !s.trim().is_empty()) {
+        None => Ok(None),
+        Some(v) => v
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .filter(|n| *n > 0)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid {key}: must be a positive integer")),
+    }
+}
@@ -1,6 +1,7 @@
 use aes_gcm::aead::{Aead, KeyInit, OsRng};
 use aes_gcm::{Aes256Gcm, AeadCore, Nonce};
 use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
 use sha2::Sha256;
 
 const HKDF_SALT: &[u8] = b"webhooker-v1";
@@ -14,6 +15,15 @@ fn derive_key(key: &str) -> [u8; 32] {
     okm
 }
 
+/// Derive a purpose-specific key, HMAC-SHA256(key, purpose), so the instance key
+/// itself is never used directly as an HMAC key for stored hashes.
+pub fn subkey(key: &str, purpose: &str) -> [u8; 32] {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(purpose.as_bytes());
+    mac.finalize().into_bytes().into()
+}
+
 /// Encrypt plaintext using AES-256-GCM. Returns nonce (12 bytes) prepended to ciphertext.
 pub fn encrypt(plaintext: &str, key: &str) -> Result<Vec<u8>, String> {
     let key_bytes = derive_key(key);
@@ -3,8 +3,8 @@ use uuid::Uuid;
 
 use crate::models::ActionLog;
 
-pub async fn create(
-    pool: &PgPool,
+pub async fn create<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
     action_id: Uuid,
     submission_id: Uuid,
     status: &str,
@@ -18,7 +18,7 @@ pub async fn create(
     .bind(submission_id)
     .bind(status)
     .bind(response)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
@@ -3,19 +3,24 @@ use uuid::Uuid;
 
 use crate::models::action_queue::ActionQueueItem;
 
-pub async fn enqueue(
-    pool: &PgPool,
-    submission_id: Uuid,
-    action_id: Uuid,
-) -> Result<ActionQueueItem, sqlx::Error> {
-    sqlx::query_as::<_, ActionQueueItem>(
+/// Enqueue `(submission_id, action_id)` pairs with a single multi-row insert.
+pub async fn enqueue_many<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    items: &[(Uuid, Uuid)],
+) -> Result<u64, sqlx::Error> {
+    if items.is_empty() {
+        return Ok(0);
+    }
+    let (submission_ids, action_ids): (Vec<Uuid>, Vec<Uuid>) = items.iter().copied().unzip();
+    let result = sqlx::query(
         "INSERT INTO action_queue (submission_id, action_id)
-         VALUES ($1, $2) RETURNING *",
+         SELECT * FROM UNNEST($1::uuid[], $2::uuid[])",
     )
-    .bind(submission_id)
-    .bind(action_id)
-    .fetch_one(pool)
-    .await
+    .bind(&submission_ids)
+    .bind(&action_ids)
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
 }
 
 /// Atomically claim the next ready item using SELECT FOR UPDATE SKIP LOCKED.
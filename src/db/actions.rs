@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -33,15 +34,19 @@ pub async fn create(
     action_type: &str,
     config: &serde_json::Value,
     position: i32,
+    condition: Option<&str>,
+    signing_secret_enc: Option<&[u8]>,
 ) -> Result<Action, sqlx::Error> {
     sqlx::query_as::<_, Action>(
-        "INSERT INTO actions (endpoint_id, action_type, config, position)
-         VALUES ($1, $2, $3, $4) RETURNING *",
+        "INSERT INTO actions (endpoint_id, action_type, config, position, condition, signing_secret_enc)
+         VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
     )
     .bind(endpoint_id)
     .bind(action_type)
     .bind(config)
     .bind(position)
+    .bind(condition)
+    .bind(signing_secret_enc)
     .fetch_one(pool)
     .await
 }
@@ -70,6 +75,7 @@ pub async fn find_by_id_scoped(
     .await
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn update(
     pool: &PgPool,
     id: Uuid,
@@ -78,9 +84,10 @@ pub async fn update(
     config: &serde_json::Value,
     position: i32,
     enabled: bool,
+    condition: Option<&str>,
 ) -> Result<Action, sqlx::Error> {
     sqlx::query_as::<_, Action>(
-        "UPDATE actions SET action_type = $3, config = $4, position = $5, enabled = $6
+        "UPDATE actions SET action_type = $3, config = $4, position = $5, enabled = $6, condition = $7
          WHERE id = $1 AND endpoint_id IN (
             SELECT e.id FROM endpoints e JOIN projects p ON e.project_id = p.id WHERE p.tenant_id = $2
          ) RETURNING *",
@@ -91,6 +98,32 @@ pub async fn update(
     .bind(config)
     .bind(position)
     .bind(enabled)
+    .bind(condition)
+    .fetch_one(pool)
+    .await
+}
+
+/// Replace the signing secret, keeping the old one valid until `previous_expires_at`.
+pub async fn rotate_secret(
+    pool: &PgPool,
+    id: Uuid,
+    tenant_id: Uuid,
+    signing_secret_enc: &[u8],
+    previous_expires_at: DateTime<Utc>,
+) -> Result<Action, sqlx::Error> {
+    sqlx::query_as::<_, Action>(
+        "UPDATE actions
+         SET previous_signing_secret_enc = signing_secret_enc,
+             previous_secret_expires_at = CASE WHEN signing_secret_enc IS NULL THEN NULL ELSE $4 END,
+             signing_secret_enc = $3
+         WHERE id = $1 AND endpoint_id IN (
+            SELECT e.id FROM endpoints e JOIN projects p ON e.project_id = p.id WHERE p.tenant_id = $2
+         ) RETURNING *",
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .bind(signing_secret_enc)
+    .bind(previous_expires_at)
     .fetch_one(pool)
     .await
 }
@@ -1,7 +1,9 @@
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::models::Endpoint;
+use crate::retention;
 
 pub async fn list_by_project(
     pool: &PgPool,
@@ -126,3 +128,76 @@ pub async fn delete(pool: &PgPool, id: Uuid, tenant_id: Uuid) -> Result<(), sqlx
     .await?;
     Ok(())
 }
+
+/// An endpoint with a `retention_days` setting, as seen by the purge job.
+#[derive(Debug)]
+pub struct RetentionTarget {
+    pub endpoint_id: Uuid,
+    pub tenant_id: Uuid,
+    pub retention_days: i64,
+}
+
+/// All endpoints with a valid `retention_days` setting.
+/// Values outside `1..=MAX_RETENTION_DAYS` (e.g. saved before validation existed) are
+/// skipped rather than failing the whole pass.
+pub async fn list_with_retention(pool: &PgPool) -> Result<Vec<RetentionTarget>, sqlx::Error> {
+    let rows: Vec<(Uuid, Uuid, serde_json::Value)> = sqlx::query_as(
+        "SELECT e.id, p.tenant_id, e.settings->'retention_days'
+         FROM endpoints e
+         JOIN projects p ON e.project_id = p.id
+         WHERE jsonb_typeof(e.settings->'retention_days') = 'number'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(endpoint_id, tenant_id, days)| {
+            let retention_days = retention::parse_days(&days)?;
+            Some(RetentionTarget {
+                endpoint_id,
+                tenant_id,
+                retention_days,
+            })
+        })
+        .collect())
+}
+
+pub async fn record_purge(
+    pool: &PgPool,
+    id: Uuid,
+    purged_at: DateTime<Utc>,
+    count: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE endpoints SET last_purged_at = $2, last_purge_count = $3 WHERE id = $1")
+        .bind(id)
+        .bind(purged_at)
+        .bind(count)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Count a request turned away by the endpoint's or tenant's access rules.
+pub async fn record_access_denial(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE endpoints SET access_denials = access_denials + 1,
+         last_access_denial_at = now() WHERE id = $1",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Count a rejected inbound signature.
+pub async fn record_signature_failure(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE endpoints SET signature_failures = signature_failures + 1,
+         last_signature_failure_at = now() WHERE id = $1",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Claim `key` for a new submission, taking over an expired claim.
+/// Returns the submission already holding it, or `None` if the caller now
+/// owns the key and must `attach` its submission in the same transaction.
+///
+/// A concurrent claim blocks on the primary key until the other transaction
+/// finishes, so only one of them stores a submission.
+pub async fn claim(
+    conn: &mut PgConnection,
+    endpoint_id: Uuid,
+    key: &str,
+    window_secs: i64,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let claimed = sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO idempotency_keys (endpoint_id, idempotency_key, expires_at)
+         VALUES ($1, $2, now() + make_interval(secs => $3))
+         ON CONFLICT (endpoint_id, idempotency_key) DO UPDATE
+         SET submission_id = NULL, created_at = now(), expires_at = EXCLUDED.expires_at
+         WHERE idempotency_keys.expires_at <= now()
+         RETURNING endpoint_id",
+    )
+    .bind(endpoint_id)
+    .bind(key)
+    .bind(window_secs as f64)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if claimed.is_some() {
+        return Ok(None);
+    }
+
+    let existing = sqlx::query_scalar::<_, Option<Uuid>>(
+        "SELECT submission_id FROM idempotency_keys
+         WHERE endpoint_id = $1 AND idempotency_key = $2",
+    )
+    .bind(endpoint_id)
+    .bind(key)
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(existing.flatten())
+}
+
+pub async fn attach(
+    conn: &mut PgConnection,
+    endpoint_id: Uuid,
+    key: &str,
+    submission_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE idempotency_keys SET submission_id = $3
+         WHERE endpoint_id = $1 AND idempotency_key = $2",
+    )
+    .bind(endpoint_id)
+    .bind(key)
+    .bind(submission_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Delete up to `limit` keys whose window has passed.
+pub async fn delete_expired(pool: &PgPool, limit: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM idempotency_keys WHERE (endpoint_id, idempotency_key) IN (
+            SELECT endpoint_id, idempotency_key FROM idempotency_keys
+            WHERE expires_at <= now()
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )",
+    )
+    .bind(limit)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -10,3 +10,8 @@ pub mod actions;
 pub mod action_log;
 pub mod action_queue;
 pub mod audit;
+pub mod submission_files;
+pub mod submission_requests;
+pub mod idempotency_keys;
+pub mod rate_limits;
+pub mod quotas;
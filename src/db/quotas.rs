@@ -0,0 +1,164 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::{Tenant, TenantUsage};
+use crate::quota::{Quota, Quotas};
+
+const USAGE_COLUMNS: &str = "t.*,
+    COALESCE((SELECT d.submissions FROM tenant_daily_usage d
+              WHERE d.tenant_id = t.id AND d.day = (now() AT TIME ZONE 'UTC')::date), 0)
+        AS submissions_today,
+    COALESCE((SELECT SUM(d.submissions) FROM tenant_daily_usage d
+              WHERE d.tenant_id = t.id
+                AND d.day >= date_trunc('month', now() AT TIME ZONE 'UTC')::date), 0)::bigint
+        AS submissions_this_month";
+
+/// Quotas and usage of the tenant owning a project.
+pub async fn usage_for_project(
+    pool: &PgPool,
+    project_id: Uuid,
+) -> Result<Option<TenantUsage>, sqlx::Error> {
+    sqlx::query_as::<_, TenantUsage>(&format!(
+        "SELECT {USAGE_COLUMNS} FROM tenants t
+         JOIN projects p ON p.tenant_id = t.id
+         WHERE p.id = $1"
+    ))
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn usage_for_tenant(pool: &PgPool, tenant_id: Uuid) -> Result<Option<TenantUsage>, sqlx::Error> {
+    sqlx::query_as::<_, TenantUsage>(&format!("SELECT {USAGE_COLUMNS} FROM tenants t WHERE t.id = $1"))
+        .bind(tenant_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn list_usage(pool: &PgPool) -> Result<Vec<TenantUsage>, sqlx::Error> {
+    sqlx::query_as::<_, TenantUsage>(&format!(
+        "SELECT {USAGE_COLUMNS} FROM tenants t ORDER BY t.created_at DESC"
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+/// Count newly stored submissions and their size against the tenant, and
+/// return its usage including them. The tenant row is updated first, so it
+/// stays locked until the transaction ends and concurrent submissions for
+/// the same tenant count one after the other.
+pub async fn record(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    submissions: i64,
+    bytes: i64,
+) -> Result<TenantUsage, sqlx::Error> {
+    sqlx::query(
+        "UPDATE tenants SET stored_submissions = stored_submissions + $2, stored_bytes = stored_bytes + $3
+         WHERE id = $1",
+    )
+    .bind(tenant_id)
+    .bind(submissions)
+    .bind(bytes)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(
+        "INSERT INTO tenant_daily_usage (tenant_id, day, submissions)
+         VALUES ($1, (now() AT TIME ZONE 'UTC')::date, $2)
+         ON CONFLICT (tenant_id, day)
+         DO UPDATE SET submissions = tenant_daily_usage.submissions + EXCLUDED.submissions",
+    )
+    .bind(tenant_id)
+    .bind(submissions)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_as::<_, TenantUsage>(&format!(
+        "SELECT {USAGE_COLUMNS} FROM tenants t WHERE t.id = $1"
+    ))
+    .bind(tenant_id)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+/// Recount every tenant's stored submissions and bytes from the sizes kept
+/// on each submission (`submissions.size_bytes`, set when it was stored).
+/// Returns the tenants updated.
+pub async fn recount(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "WITH counts AS (
+            SELECT p.tenant_id, COUNT(*) AS submissions, SUM(s.size_bytes) AS bytes
+            FROM submissions s
+            JOIN endpoints e ON e.id = s.endpoint_id
+            JOIN projects p ON p.id = e.project_id
+            GROUP BY p.tenant_id
+        )
+        UPDATE tenants t
+        SET stored_submissions = COALESCE(c.submissions, 0),
+            stored_bytes = COALESCE(c.bytes, 0)::bigint,
+            usage_counted_at = now()
+        FROM tenants t2 LEFT JOIN counts c ON c.tenant_id = t2.id
+        WHERE t.id = t2.id",
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Forget warnings for stored totals that are back under 80%, so crossing
+/// again warns again. `defaults` are the instance quotas.
+pub async fn clear_recovered_warnings(pool: &PgPool, defaults: &Quotas) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM tenant_quota_warnings w USING tenants t
+         WHERE w.tenant_id = t.id AND w.period = '' AND (
+            (w.quota = $1 AND (COALESCE(t.quota_stored_submissions, $3) IS NULL
+                OR t.stored_submissions * 5 < COALESCE(t.quota_stored_submissions, $3) * 4))
+            OR (w.quota = $2 AND (COALESCE(t.quota_storage_bytes, $4) IS NULL
+                OR t.stored_bytes * 5 < COALESCE(t.quota_storage_bytes, $4) * 4))
+         )",
+    )
+    .bind(Quota::StoredSubmissions.as_str())
+    .bind(Quota::StorageBytes.as_str())
+    .bind(defaults.stored_submissions)
+    .bind(defaults.storage_bytes)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Record that the warning at `percent` for `quota` in `period` is being
+/// sent. False if it already was, by this or another replica.
+pub async fn claim_warning(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    quota: Quota,
+    period: &str,
+    percent: i16,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO tenant_quota_warnings (tenant_id, quota, period, percent)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(tenant_id)
+    .bind(quota.as_str())
+    .bind(period)
+    .bind(percent)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn update(pool: &PgPool, tenant_id: Uuid, quotas: &Quotas) -> Result<Tenant, sqlx::Error> {
+    sqlx::query_as::<_, Tenant>(
+        "UPDATE tenants SET quota_daily_submissions = $2, quota_monthly_submissions = $3,
+            quota_stored_submissions = $4, quota_storage_bytes = $5, updated_at = now()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(tenant_id)
+    .bind(quotas.daily_submissions)
+    .bind(quotas.monthly_submissions)
+    .bind(quotas.stored_submissions)
+    .bind(quotas.storage_bytes)
+    .fetch_one(pool)
+    .await
+}
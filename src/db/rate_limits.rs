@@ -0,0 +1,64 @@
+use sqlx::PgPool;
+
+/// Take one token from `key`'s bucket, refilled at `per_sec` up to
+/// `capacity` since it was last touched. Returns the tokens left, or `None`
+/// if the bucket had less than one (nothing is taken then).
+///
+/// Concurrent takes serialize on the row, so replicas never overspend.
+pub async fn take(
+    pool: &PgPool,
+    key: &str,
+    capacity: f64,
+    per_sec: f64,
+) -> Result<Option<f64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "INSERT INTO rate_limits AS b (key, tokens, updated_at, full_at)
+         VALUES ($1, $2 - 1, now(), now() + make_interval(secs => 1 / $3))
+         ON CONFLICT (key) DO UPDATE
+         SET tokens = LEAST($2, b.tokens + EXTRACT(EPOCH FROM now() - b.updated_at)::float8 * $3) - 1,
+             updated_at = now(),
+             full_at = now() + make_interval(secs =>
+                 ($2 + 1 - LEAST($2, b.tokens + EXTRACT(EPOCH FROM now() - b.updated_at)::float8 * $3)) / $3)
+         WHERE LEAST($2, b.tokens + EXTRACT(EPOCH FROM now() - b.updated_at)::float8 * $3) >= 1
+         RETURNING tokens",
+    )
+    .bind(key)
+    .bind(capacity)
+    .bind(per_sec)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Tokens currently in `key`'s bucket; `None` if it has no row (full).
+pub async fn available(
+    pool: &PgPool,
+    key: &str,
+    capacity: f64,
+    per_sec: f64,
+) -> Result<Option<f64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT LEAST($2, tokens + EXTRACT(EPOCH FROM now() - updated_at)::float8 * $3)
+         FROM rate_limits WHERE key = $1",
+    )
+    .bind(key)
+    .bind(capacity)
+    .bind(per_sec)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Delete up to `limit` buckets that have refilled.
+pub async fn delete_full(pool: &PgPool, limit: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM rate_limits WHERE key IN (
+            SELECT key FROM rate_limits
+            WHERE full_at <= now()
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )",
+    )
+    .bind(limit)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,79 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::SubmissionFile;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn create<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    id: Uuid,
+    submission_id: Uuid,
+    field_name: &str,
+    filename: &str,
+    content_type: &str,
+    size_bytes: i64,
+    storage_key: &str,
+) -> Result<SubmissionFile, sqlx::Error> {
+    sqlx::query_as::<_, SubmissionFile>(
+        "INSERT INTO submission_files (id, submission_id, field_name, filename, content_type, size_bytes, storage_key)
+         VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+    )
+    .bind(id)
+    .bind(submission_id)
+    .bind(field_name)
+    .bind(filename)
+    .bind(content_type)
+    .bind(size_bytes)
+    .bind(storage_key)
+    .fetch_one(executor)
+    .await
+}
+
+pub async fn find_by_id_scoped(
+    pool: &PgPool,
+    id: Uuid,
+    tenant_id: Uuid,
+) -> Result<Option<SubmissionFile>, sqlx::Error> {
+    sqlx::query_as::<_, SubmissionFile>(
+        "SELECT f.* FROM submission_files f
+         JOIN submissions s ON f.submission_id = s.id
+         JOIN endpoints e ON s.endpoint_id = e.id
+         JOIN projects p ON e.project_id = p.id
+         WHERE f.id = $1 AND p.tenant_id = $2",
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn list_by_submissions(
+    pool: &PgPool,
+    submission_ids: &[Uuid],
+) -> Result<Vec<SubmissionFile>, sqlx::Error> {
+    sqlx::query_as::<_, SubmissionFile>(
+        "SELECT * FROM submission_files WHERE submission_id = ANY($1) ORDER BY created_at ASC",
+    )
+    .bind(submission_ids)
+    .fetch_all(pool)
+    .await
+}
+
+/// Files whose submission has been deleted and whose blobs still need removing.
+pub async fn list_orphaned(pool: &PgPool, limit: i64) -> Result<Vec<SubmissionFile>, sqlx::Error> {
+    sqlx::query_as::<_, SubmissionFile>(
+        "SELECT * FROM submission_files WHERE submission_id IS NULL
+         ORDER BY created_at ASC LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM submission_files WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::SubmissionRequest;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn create<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    submission_id: Uuid,
+    method: &str,
+    content_type: Option<&str>,
+    headers: &serde_json::Value,
+    body: &[u8],
+    body_size: i64,
+    truncated: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO submission_requests
+         (submission_id, method, content_type, headers, body, body_size, truncated)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(submission_id)
+    .bind(method)
+    .bind(content_type)
+    .bind(headers)
+    .bind(body)
+    .bind(body_size)
+    .bind(truncated)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+pub async fn find_by_submission_scoped(
+    pool: &PgPool,
+    submission_id: Uuid,
+    tenant_id: Uuid,
+) -> Result<Option<SubmissionRequest>, sqlx::Error> {
+    sqlx::query_as::<_, SubmissionRequest>(
+        "SELECT r.* FROM submission_requests r
+         JOIN submissions s ON r.submission_id = s.id
+         JOIN endpoints e ON s.endpoint_id = e.id
+         JOIN projects p ON e.project_id = p.id
+         WHERE r.submission_id = $1 AND p.tenant_id = $2",
+    )
+    .bind(submission_id)
+    .bind(tenant_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Which of the given submissions have a captured request.
+pub async fn list_captured(
+    pool: &PgPool,
+    submission_ids: &[Uuid],
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>(
+        "SELECT submission_id FROM submission_requests WHERE submission_id = ANY($1)",
+    )
+    .bind(submission_ids)
+    .fetch_all(pool)
+    .await
+}
@@ -1,26 +1,62 @@
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::models::Submission;
 
-pub async fn create(
-    pool: &PgPool,
+#[allow(clippy::too_many_arguments)]
+pub async fn create<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
     endpoint_id: Uuid,
     data: &serde_json::Value,
     extras: &serde_json::Value,
     raw: &serde_json::Value,
     metadata: &serde_json::Value,
+    validation_warnings: Option<&serde_json::Value>,
+    content_hash: Option<&str>,
+    duplicate: bool,
+    quarantined: bool,
+    size_bytes: i64,
 ) -> Result<Submission, sqlx::Error> {
     sqlx::query_as::<_, Submission>(
-        "INSERT INTO submissions (endpoint_id, data, extras, raw, metadata)
-         VALUES ($1, $2, $3, $4, $5) RETURNING *",
+        "INSERT INTO submissions
+         (endpoint_id, data, extras, raw, metadata, validation_warnings, content_hash, duplicate,
+          quarantined, size_bytes)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *",
     )
     .bind(endpoint_id)
     .bind(data)
     .bind(extras)
     .bind(raw)
     .bind(metadata)
-    .fetch_one(pool)
+    .bind(validation_warnings)
+    .bind(content_hash)
+    .bind(duplicate)
+    .bind(quarantined)
+    .bind(size_bytes)
+    .fetch_one(executor)
+    .await
+}
+
+/// Whether the endpoint stored a submission with this content hash in the
+/// last `window_mins` minutes.
+pub async fn has_recent_hash<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    endpoint_id: Uuid,
+    content_hash: &str,
+    window_mins: i64,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT EXISTS (
+            SELECT 1 FROM submissions
+            WHERE endpoint_id = $1 AND content_hash = $2
+              AND created_at > now() - make_interval(mins => $3)
+        )",
+    )
+    .bind(endpoint_id)
+    .bind(content_hash)
+    .bind(window_mins as i32)
+    .fetch_one(executor)
     .await
 }
 
@@ -97,69 +133,57 @@ pub struct ListParams {
     pub sort_by: SortColumn,
     pub sort_order: SortOrder,
     pub search: Option<String>,
+    /// Only duplicates (`true`) or only originals (`false`).
+    pub duplicate: Option<bool>,
+    /// Only quarantined (`true`) or only accepted (`false`).
+    pub quarantined: Option<bool>,
 }
 
+/// Shared by `list` and `count`: `$1` endpoint, `$2` search pattern, `$3` duplicate,
+/// `$4` quarantined.
+const LIST_FILTER: &str = "endpoint_id = $1
+    AND ($2::text IS NULL OR data::text ILIKE $2 OR extras::text ILIKE $2)
+    AND ($3::boolean IS NULL OR duplicate = $3)
+    AND ($4::boolean IS NULL OR quarantined = $4)";
+
 pub async fn list(pool: &PgPool, params: &ListParams) -> Result<Vec<Submission>, sqlx::Error> {
     let sort_col = params.sort_by.as_sql();
     let order = params.sort_order.as_sql();
 
-    if let Some(search) = &params.search {
-        let search_pattern = format!("%{search}%");
-        sqlx::query_as::<_, Submission>(&format!(
-            "SELECT * FROM submissions
-             WHERE endpoint_id = $1 AND (data::text ILIKE $4 OR extras::text ILIKE $4)
-             ORDER BY {sort_col} {order} LIMIT $2 OFFSET $3"
-        ))
-        .bind(params.endpoint_id)
-        .bind(params.limit)
-        .bind(params.offset)
-        .bind(search_pattern)
-        .fetch_all(pool)
-        .await
-    } else {
-        sqlx::query_as::<_, Submission>(&format!(
-            "SELECT * FROM submissions
-             WHERE endpoint_id = $1
-             ORDER BY {sort_col} {order} LIMIT $2 OFFSET $3"
-        ))
-        .bind(params.endpoint_id)
-        .bind(params.limit)
-        .bind(params.offset)
-        .fetch_all(pool)
-        .await
-    }
+    sqlx::query_as::<_, Submission>(&format!(
+        "SELECT * FROM submissions
+         WHERE {LIST_FILTER}
+         ORDER BY {sort_col} {order} LIMIT $5 OFFSET $6"
+    ))
+    .bind(params.endpoint_id)
+    .bind(params.search.as_ref().map(|s| format!("%{s}%")))
+    .bind(params.duplicate)
+    .bind(params.quarantined)
+    .bind(params.limit)
+    .bind(params.offset)
+    .fetch_all(pool)
+    .await
 }
 
-pub async fn count(
-    pool: &PgPool,
-    endpoint_id: Uuid,
-    search: Option<&str>,
-) -> Result<i64, sqlx::Error> {
-    let row: (i64,) = if let Some(search) = search {
-        let search_pattern = format!("%{search}%");
-        sqlx::query_as(
-            "SELECT COUNT(*) FROM submissions
-             WHERE endpoint_id = $1 AND (data::text ILIKE $2 OR extras::text ILIKE $2)",
-        )
-        .bind(endpoint_id)
-        .bind(search_pattern)
+/// Total matching `params`' filters, ignoring paging.
+pub async fn count(pool: &PgPool, params: &ListParams) -> Result<i64, sqlx::Error> {
+    let row: (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM submissions WHERE {LIST_FILTER}"))
+        .bind(params.endpoint_id)
+        .bind(params.search.as_ref().map(|s| format!("%{s}%")))
+        .bind(params.duplicate)
+        .bind(params.quarantined)
         .fetch_one(pool)
-        .await?
-    } else {
-        sqlx::query_as("SELECT COUNT(*) FROM submissions WHERE endpoint_id = $1")
-            .bind(endpoint_id)
-            .fetch_one(pool)
-            .await?
-    };
+        .await?;
     Ok(row.0)
 }
 
 pub async fn delete(pool: &PgPool, id: Uuid, tenant_id: Uuid) -> Result<(), sqlx::Error> {
-    sqlx::query(
+    sqlx::query(&releasing_usage(
         "DELETE FROM submissions WHERE id = $1 AND endpoint_id IN (
             SELECT e.id FROM endpoints e JOIN projects p ON e.project_id = p.id WHERE p.tenant_id = $2
         )",
-    )
+        "$2",
+    ))
     .bind(id)
     .bind(tenant_id)
     .execute(pool)
@@ -172,16 +196,17 @@ pub async fn bulk_delete(
     endpoint_id: Uuid,
     tenant_id: Uuid,
 ) -> Result<u64, sqlx::Error> {
-    let result = sqlx::query(
+    let deleted: i64 = sqlx::query_scalar(&releasing_usage(
         "DELETE FROM submissions WHERE endpoint_id = $1 AND endpoint_id IN (
             SELECT e.id FROM endpoints e JOIN projects p ON e.project_id = p.id WHERE p.tenant_id = $2
         )",
-    )
+        "$2",
+    ))
     .bind(endpoint_id)
     .bind(tenant_id)
-    .execute(pool)
+    .fetch_one(pool)
     .await?;
-    Ok(result.rows_affected())
+    Ok(deleted as u64)
 }
 
 pub async fn list_for_export(
@@ -201,3 +226,64 @@ pub async fn list_for_export(
     .fetch_all(pool)
     .await
 }
+
+/// Count submissions on an endpoint created before `cutoff`.
+pub async fn count_older_than(
+    pool: &PgPool,
+    endpoint_id: Uuid,
+    cutoff: DateTime<Utc>,
+) -> Result<i64, sqlx::Error> {
+    let row: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM submissions WHERE endpoint_id = $1 AND created_at < $2",
+    )
+    .bind(endpoint_id)
+    .bind(cutoff)
+    .fetch_one(pool)
+    .await?;
+    Ok(row.0)
+}
+
+/// Delete up to `limit` submissions created before `cutoff`, oldest first.
+/// `action_log` and `action_queue` rows go with them via ON DELETE CASCADE.
+pub async fn delete_older_than(
+    pool: &PgPool,
+    endpoint_id: Uuid,
+    cutoff: DateTime<Utc>,
+    limit: i64,
+) -> Result<u64, sqlx::Error> {
+    let deleted: i64 = sqlx::query_scalar(&releasing_usage(
+        "DELETE FROM submissions WHERE id IN (
+            SELECT id FROM submissions
+            WHERE endpoint_id = $1 AND created_at < $2
+            ORDER BY created_at ASC
+            LIMIT $3
+            FOR UPDATE SKIP LOCKED
+        )",
+        "(SELECT p.tenant_id FROM endpoints e JOIN projects p ON e.project_id = p.id
+          WHERE e.id = $1)",
+    ))
+    .bind(endpoint_id)
+    .bind(cutoff)
+    .bind(limit)
+    .fetch_one(pool)
+    .await?;
+    Ok(deleted as u64)
+}
+
+/// Wrap a `DELETE FROM submissions` so the same statement takes what it
+/// removed off the tenant's stored totals (`tenant` is its ID as SQL), and
+/// returns the number deleted.
+fn releasing_usage(delete: &str, tenant: &str) -> String {
+    format!(
+        "WITH deleted AS ({delete} RETURNING size_bytes),
+         released AS (
+            UPDATE tenants t
+            SET stored_submissions = GREATEST(t.stored_submissions - d.submissions, 0),
+                stored_bytes = GREATEST(t.stored_bytes - d.bytes, 0)
+            FROM (SELECT COUNT(*) AS submissions, COALESCE(SUM(size_bytes), 0)::bigint AS bytes
+                  FROM deleted) d
+            WHERE t.id = {tenant} AND d.submissions > 0
+         )
+         SELECT COUNT(*) FROM deleted"
+    )
+}
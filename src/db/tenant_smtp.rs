@@ -15,6 +15,7 @@ pub async fn find_by_tenant(
     .await
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn upsert(
     pool: &PgPool,
     tenant_id: Uuid,
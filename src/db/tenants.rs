@@ -53,6 +53,66 @@ pub async fn update(
     .await
 }
 
+pub async fn update_ip_blocklist(
+    pool: &PgPool,
+    id: Uuid,
+    entries: &[String],
+) -> Result<Tenant, sqlx::Error> {
+    sqlx::query_as::<_, Tenant>(
+        "UPDATE tenants SET ip_blocklist = $2, updated_at = now() WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(entries)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn update_ip_denylist(
+    pool: &PgPool,
+    id: Uuid,
+    entries: &[String],
+) -> Result<Tenant, sqlx::Error> {
+    sqlx::query_as::<_, Tenant>(
+        "UPDATE tenants SET ip_denylist = $2, updated_at = now() WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(entries)
+    .fetch_one(pool)
+    .await
+}
+
+/// The IP denylist of the tenant owning a project.
+pub async fn ip_denylist_for_project(
+    pool: &PgPool,
+    project_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, Vec<String>>(
+        "SELECT t.ip_denylist FROM tenants t
+         JOIN projects p ON p.tenant_id = t.id
+         WHERE p.id = $1",
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await
+    .map(Option::unwrap_or_default)
+}
+
+/// The IP blocklist of the tenant owning a project.
+pub async fn ip_blocklist_for_project(
+    pool: &PgPool,
+    project_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, Vec<String>>(
+        "SELECT t.ip_blocklist FROM tenants t
+         JOIN projects p ON p.tenant_id = t.id
+         WHERE p.id = $1",
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await
+    .map(Option::unwrap_or_default)
+}
+
 pub async fn delete(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
     sqlx::query("DELETE FROM tenants WHERE id = $1")
         .bind(id)
@@ -5,6 +5,7 @@ use lettre::transport::smtp::authentication::Credentials;
 use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
 
 use crate::config::SmtpConfig;
+use crate::quota::Quota;
 
 pub struct SystemMailer {
     transport: AsyncSmtpTransport<Tokio1Executor>,
@@ -58,6 +59,24 @@ impl SystemMailer {
         .await
     }
 
+    pub async fn send_quota_warning(
+        &self,
+        to_email: &str,
+        tenant_name: &str,
+        quota: Quota,
+        percent: i16,
+        limit: i64,
+        base_url: &str,
+    ) -> Result<(), String> {
+        let html = templates::render_quota_warning(tenant_name, quota, percent, limit, base_url);
+        let subject = if percent >= 100 {
+            format!("{tenant_name} has reached its {} quota - Webhooker", quota.label())
+        } else {
+            format!("{tenant_name} is at {percent}% of its {} quota - Webhooker", quota.label())
+        };
+        self.send(to_email, &subject, &html).await
+    }
+
     async fn send(&self, to: &str, subject: &str, html_body: &str) -> Result<(), String> {
         let message = Message::builder()
             .from(
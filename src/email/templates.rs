@@ -1,3 +1,5 @@
+use crate::quota::Quota;
+
 pub fn render_welcome(name: &str, base_url: &str) -> String {
     format!(
         r#"<!DOCTYPE html>
@@ -43,3 +45,41 @@ pub fn render_member_added(name: &str, tenant_name: &str, base_url: &str) -> Str
 </html>"#
     )
 }
+
+pub fn render_quota_warning(
+    tenant_name: &str,
+    quota: Quota,
+    percent: i16,
+    limit: i64,
+    base_url: &str,
+) -> String {
+    let amount = match quota {
+        Quota::StorageBytes => format!("{limit} bytes of storage"),
+        _ => format!("{limit} {}", quota.label()),
+    };
+    let consequence = match quota {
+        Quota::DailySubmissions | Quota::MonthlySubmissions => {
+            "New submissions are rejected with 429 until the period resets."
+        }
+        Quota::StoredSubmissions | Quota::StorageBytes => {
+            "New submissions are rejected with 507 until stored data is deleted."
+        }
+    };
+    let status = if percent >= 100 {
+        format!("<p><strong>{tenant_name}</strong> has reached its quota of {amount}. {consequence}</p>")
+    } else {
+        format!("<p><strong>{tenant_name}</strong> has used {percent}% of its quota of {amount}. {consequence}</p>")
+    };
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"></head>
+<body style="font-family: sans-serif; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <h2>Quota warning</h2>
+    {status}
+    <p style="color: #666; font-size: 14px;">Contact your Webhooker administrator to raise the quota, or delete submissions you no longer need.</p>
+    <p><a href="{base_url}" style="display: inline-block; padding: 10px 20px; background: #0070f3; color: white; text-decoration: none; border-radius: 4px;">Open Webhooker</a></p>
+</body>
+</html>"#
+    )
+}
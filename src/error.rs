@@ -2,6 +2,8 @@ use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use serde_json::json;
 
+use crate::submission::fields::FieldErrors;
+
 #[derive(Debug)]
 pub enum AppError {
     NotFound(String),
@@ -10,6 +12,12 @@ pub enum AppError {
     BadRequest(String),
     Conflict(String),
     RateLimited(String),
+    PayloadTooLarge(String),
+    /// A storage quota is used up.
+    InsufficientStorage(String),
+    UnsupportedMediaType(String),
+    /// Submission failed validation; per-field messages.
+    Validation(FieldErrors),
     Internal(String),
     Database(sqlx::Error),
 }
@@ -23,21 +31,34 @@ impl std::fmt::Display for AppError {
             AppError::BadRequest(msg) => write!(f, "Bad Request: {msg}"),
             AppError::Conflict(msg) => write!(f, "Conflict: {msg}"),
             AppError::RateLimited(msg) => write!(f, "Rate Limited: {msg}"),
+            AppError::PayloadTooLarge(msg) => write!(f, "Payload Too Large: {msg}"),
+            AppError::InsufficientStorage(msg) => write!(f, "Insufficient Storage: {msg}"),
+            AppError::UnsupportedMediaType(msg) => write!(f, "Unsupported Media Type: {msg}"),
+            AppError::Validation(errors) => write!(f, "Validation Failed: {} field(s)", errors.len()),
             AppError::Internal(msg) => write!(f, "Internal Error: {msg}"),
             AppError::Database(err) => write!(f, "Database Error: {err}"),
         }
     }
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, message) = match &self {
+impl AppError {
+    /// The status and JSON body this error responds with.
+    pub fn status_and_body(&self) -> (StatusCode, serde_json::Value) {
+        let (status, message) = match self {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.clone()),
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone()),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
             AppError::RateLimited(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg.clone()),
+            AppError::InsufficientStorage(msg) => (StatusCode::INSUFFICIENT_STORAGE, msg.clone()),
+            AppError::UnsupportedMediaType(msg) => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, msg.clone())
+            }
+            AppError::Validation(_) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "Validation failed".to_string())
+            }
             AppError::Internal(msg) => {
                 tracing::error!("Internal error: {msg}");
                 (
@@ -54,7 +75,17 @@ impl IntoResponse for AppError {
             }
         };
 
-        let body = json!({ "error": message });
+        let mut body = json!({ "error": message });
+        if let AppError::Validation(errors) = self {
+            body["errors"] = json!(errors);
+        }
+        (status, body)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, body) = self.status_and_body();
         (status, axum::Json(body)).into_response()
     }
 }
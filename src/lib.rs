@@ -12,7 +12,10 @@ pub mod email;
 pub mod submission;
 pub mod crypto;
 pub mod rate_limit;
+pub mod quota;
 pub mod worker;
+pub mod retention;
+pub mod storage;
 
 use std::sync::Arc;
 
@@ -26,12 +29,19 @@ use tower_http::set_header::SetResponseHeaderLayer;
 use crate::middleware::auth_redirect::redirect_unauthorized;
 
 use crate::actions::webhook::WebhookModule;
+use crate::actions::discord::DiscordModule;
 use crate::actions::email::EmailModule;
+use crate::actions::matrix::MatrixModule;
+use crate::actions::slack::SlackModule;
+use crate::actions::teams::TeamsModule;
+use crate::actions::telegram::TelegramModule;
 use crate::actions::ModuleRegistry;
 use crate::config::Config;
 use crate::email::SystemMailer;
-use crate::rate_limit::{LoginRateLimiter, SubmissionRateLimiter};
+use crate::config::RateLimitBackend;
+use crate::rate_limit::{LoginRateLimiter, MemoryStore, PgStore, RateLimitStore, SubmissionRateLimiter};
 use crate::state::{AppState, SharedState};
+use crate::submission::captcha::CaptchaVerifier;
 
 pub fn build_app(pool: PgPool, config: Config) -> (Router, SharedState) {
     let max_body_size = config.max_body_size;
@@ -42,6 +52,31 @@ pub fn build_app(pool: PgPool, config: Config) -> (Router, SharedState) {
         config.allowed_webhook_cidrs.clone(),
     )));
     modules.register(Arc::new(EmailModule::new(pool.clone(), config.encryption_key.clone())));
+    modules.register(Arc::new(DiscordModule::new(
+        config.webhook_ssrf_mode.clone(),
+        config.allowed_webhook_cidrs.clone(),
+        config.base_url.clone(),
+    )));
+    modules.register(Arc::new(SlackModule::new(
+        config.webhook_ssrf_mode.clone(),
+        config.allowed_webhook_cidrs.clone(),
+        config.base_url.clone(),
+    )));
+    modules.register(Arc::new(TelegramModule::new(
+        config.webhook_ssrf_mode.clone(),
+        config.allowed_webhook_cidrs.clone(),
+        config.base_url.clone(),
+    )));
+    modules.register(Arc::new(TeamsModule::new(
+        config.webhook_ssrf_mode.clone(),
+        config.allowed_webhook_cidrs.clone(),
+        config.base_url.clone(),
+    )));
+    modules.register(Arc::new(MatrixModule::new(
+        config.webhook_ssrf_mode.clone(),
+        config.allowed_webhook_cidrs.clone(),
+        config.base_url.clone(),
+    )));
 
     let system_mailer = config.smtp.as_ref().and_then(|smtp| {
         match SystemMailer::new(smtp) {
@@ -56,13 +91,28 @@ pub fn build_app(pool: PgPool, config: Config) -> (Router, SharedState) {
         }
     });
 
+    let blob_store = storage::from_config(&config.storage);
+    let captcha = CaptchaVerifier::new(config.captcha_verify_url.clone());
+    let geoip = config.geoip.clone();
+    if geoip.is_some() {
+        tracing::info!("GeoIP database loaded");
+    }
+    let rate_limit_store: Arc<dyn RateLimitStore> = match config.rate_limit_store {
+        RateLimitBackend::Memory => Arc::new(MemoryStore::new()),
+        RateLimitBackend::Postgres => Arc::new(PgStore::new(pool.clone())),
+    };
+
     let state: SharedState = Arc::new(AppState {
         pool,
         config,
         modules,
         system_mailer,
-        submission_limiter: SubmissionRateLimiter::new(),
-        login_limiter: LoginRateLimiter::new(),
+        submission_limiter: SubmissionRateLimiter::new(rate_limit_store.clone()),
+        login_limiter: LoginRateLimiter::new(rate_limit_store.clone()),
+        rate_limit_store,
+        blob_store,
+        captcha,
+        geoip,
     });
 
     let router = Router::new()
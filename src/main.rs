@@ -82,17 +82,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut cleanup_shutdown = shutdown_rx.clone();
     tokio::spawn(async move {
         let interval = std::time::Duration::from_secs(5 * 60);
-        let max_age = std::time::Duration::from_secs(30 * 60);
         loop {
             tokio::select! {
                 _ = tokio::time::sleep(interval) => {}
                 _ = cleanup_shutdown.changed() => break,
             }
-            cleanup_state.submission_limiter.cleanup(max_age);
-            cleanup_state.login_limiter.cleanup(max_age);
+            cleanup_state.rate_limit_store.cleanup().await;
         }
     });
 
+    tokio::spawn(webhooker::retention::run(
+        state.clone(),
+        shutdown_rx.clone(),
+    ));
+
     let worker_pool = webhooker::worker::run_pool(state, shutdown_rx, worker_count);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -10,5 +10,12 @@ pub struct Action {
     pub config: serde_json::Value,
     pub position: i32,
     pub enabled: bool,
+    /// Filter expression; the action only runs when it evaluates true.
+    pub condition: Option<String>,
+    #[serde(skip_serializing)]
+    pub signing_secret_enc: Option<Vec<u8>>,
+    #[serde(skip_serializing)]
+    pub previous_signing_secret_enc: Option<Vec<u8>>,
+    pub previous_secret_expires_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
@@ -10,6 +10,12 @@ pub struct Endpoint {
     pub slug: String,
     pub fields: Option<serde_json::Value>,
     pub settings: Option<serde_json::Value>,
+    pub last_purged_at: Option<DateTime<Utc>>,
+    pub last_purge_count: i64,
+    pub signature_failures: i64,
+    pub last_signature_failure_at: Option<DateTime<Utc>>,
+    pub access_denials: i64,
+    pub last_access_denial_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
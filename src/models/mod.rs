@@ -10,8 +10,10 @@ pub mod action;
 pub mod action_log;
 pub mod audit_event;
 pub mod action_queue;
+pub mod submission_file;
+pub mod submission_request;
 
-pub use tenant::Tenant;
+pub use tenant::{Tenant, TenantUsage};
 pub use user::User;
 pub use refresh_token::RefreshToken;
 pub use password_reset_token::PasswordResetToken;
@@ -23,3 +25,5 @@ pub use action::Action;
 pub use action_log::ActionLog;
 pub use audit_event::AuditEvent;
 pub use action_queue::ActionQueueItem;
+pub use submission_file::SubmissionFile;
+pub use submission_request::SubmissionRequest;
@@ -10,5 +10,13 @@ pub struct Submission {
     pub extras: serde_json::Value,
     pub raw: serde_json::Value,
     pub metadata: serde_json::Value,
+    /// Field validation problems, kept when the endpoint validates in warn mode.
+    pub validation_warnings: Option<serde_json::Value>,
+    /// Set when duplicate detection is on (`settings.duplicates`).
+    pub content_hash: Option<String>,
+    /// Matched a recent submission's content hash.
+    pub duplicate: bool,
+    /// Scored as likely spam; stored, but actions didn't run.
+    pub quarantined: bool,
     pub created_at: DateTime<Utc>,
 }
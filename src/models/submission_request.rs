@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct SubmissionRequest {
+    pub submission_id: Uuid,
+    pub method: String,
+    pub content_type: Option<String>,
+    pub headers: serde_json::Value,
+    #[serde(skip_serializing)]
+    pub body: Vec<u8>,
+    pub body_size: i64,
+    pub truncated: bool,
+    pub created_at: DateTime<Utc>,
+}
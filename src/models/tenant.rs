@@ -7,6 +7,29 @@ pub struct Tenant {
     pub id: Uuid,
     pub name: String,
     pub slug: String,
+    /// IPs and CIDR ranges scored as spam on every endpoint (see `submission::spam`).
+    pub ip_blocklist: Vec<String>,
+    /// IPs and CIDR ranges turned away from every endpoint (see `submission::access`).
+    pub ip_denylist: Vec<String>,
+    /// Quotas set for this tenant; `None` uses the instance default (see `quota`).
+    pub quota_daily_submissions: Option<i64>,
+    pub quota_monthly_submissions: Option<i64>,
+    pub quota_stored_submissions: Option<i64>,
+    pub quota_storage_bytes: Option<i64>,
+    /// Usage as of the last recount, plus what was stored since.
+    pub stored_submissions: i64,
+    pub stored_bytes: i64,
+    pub usage_counted_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+/// A tenant with its submission counts for the current UTC day and month.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize)]
+pub struct TenantUsage {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub tenant: Tenant,
+    pub submissions_today: i64,
+    pub submissions_this_month: i64,
+}
@@ -0,0 +1,252 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db;
+use crate::error::AppError;
+use crate::models::TenantUsage;
+use crate::state::SharedState;
+
+/// Warnings are raised as usage reaches these percentages of a quota.
+const WARN_AT: [i16; 2] = [80, 100];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quota {
+    DailySubmissions,
+    MonthlySubmissions,
+    StoredSubmissions,
+    StorageBytes,
+}
+
+impl Quota {
+    pub const ALL: [Quota; 4] = [
+        Quota::DailySubmissions,
+        Quota::MonthlySubmissions,
+        Quota::StoredSubmissions,
+        Quota::StorageBytes,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Quota::DailySubmissions => "daily_submissions",
+            Quota::MonthlySubmissions => "monthly_submissions",
+            Quota::StoredSubmissions => "stored_submissions",
+            Quota::StorageBytes => "storage_bytes",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Quota::DailySubmissions => "submissions today",
+            Quota::MonthlySubmissions => "submissions this month",
+            Quota::StoredSubmissions => "stored submissions",
+            Quota::StorageBytes => "storage",
+        }
+    }
+
+    /// What a warning is sent once per: the UTC day or month for submission
+    /// counts; stored totals warn again only after dropping back under 80%.
+    fn period(self) -> String {
+        let now = Utc::now();
+        match self {
+            Quota::DailySubmissions => now.format("%Y-%m-%d").to_string(),
+            Quota::MonthlySubmissions => now.format("%Y-%m").to_string(),
+            Quota::StoredSubmissions | Quota::StorageBytes => String::new(),
+        }
+    }
+
+    /// Counts reset with time, so they're rate limits (429); stored totals
+    /// only go down when data is deleted (507).
+    fn exceeded(self) -> AppError {
+        match self {
+            Quota::DailySubmissions => {
+                AppError::RateLimited("Daily submission quota reached".to_string())
+            }
+            Quota::MonthlySubmissions => {
+                AppError::RateLimited("Monthly submission quota reached".to_string())
+            }
+            Quota::StoredSubmissions => {
+                AppError::InsufficientStorage("Stored submission quota reached".to_string())
+            }
+            Quota::StorageBytes => {
+                AppError::InsufficientStorage("Storage quota reached".to_string())
+            }
+        }
+    }
+}
+
+/// Limits per tenant; `None` is unlimited. Instance defaults come from
+/// `WEBHOOKER_QUOTA_*`, and a tenant's own values override them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Quotas {
+    pub daily_submissions: Option<i64>,
+    pub monthly_submissions: Option<i64>,
+    pub stored_submissions: Option<i64>,
+    pub storage_bytes: Option<i64>,
+}
+
+impl Quotas {
+    pub fn get(&self, quota: Quota) -> Option<i64> {
+        match quota {
+            Quota::DailySubmissions => self.daily_submissions,
+            Quota::MonthlySubmissions => self.monthly_submissions,
+            Quota::StoredSubmissions => self.stored_submissions,
+            Quota::StorageBytes => self.storage_bytes,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match Quota::ALL.iter().find(|q| self.get(**q).is_some_and(|n| n < 1)) {
+            Some(q) => Err(format!("{} must be at least 1", q.as_str())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A tenant's effective quotas and usage when a request came in.
+pub struct TenantQuota {
+    pub tenant_id: Uuid,
+    pub tenant_name: String,
+    pub limits: Quotas,
+    pub usage: Quotas,
+}
+
+impl TenantQuota {
+    pub fn new(row: TenantUsage, defaults: &Quotas) -> Self {
+        let t = row.tenant;
+        Self {
+            limits: Quotas {
+                daily_submissions: t.quota_daily_submissions.or(defaults.daily_submissions),
+                monthly_submissions: t.quota_monthly_submissions.or(defaults.monthly_submissions),
+                stored_submissions: t.quota_stored_submissions.or(defaults.stored_submissions),
+                storage_bytes: t.quota_storage_bytes.or(defaults.storage_bytes),
+            },
+            usage: Quotas {
+                daily_submissions: Some(row.submissions_today),
+                monthly_submissions: Some(row.submissions_this_month),
+                stored_submissions: Some(t.stored_submissions),
+                storage_bytes: Some(t.stored_bytes),
+            },
+            tenant_id: t.id,
+            tenant_name: t.name,
+        }
+    }
+
+    fn used(&self, quota: Quota) -> i64 {
+        self.usage.get(quota).unwrap_or(0)
+    }
+
+    /// Whether `submissions` more fit, before any work is done. Storage is
+    /// checked as used so far, since a submission's size isn't known until
+    /// it's processed. Usage may change before commit, so `check_recorded`
+    /// has the final say.
+    pub fn check(&self, submissions: i64) -> Result<(), AppError> {
+        for quota in Quota::ALL {
+            let Some(limit) = self.limits.get(quota) else {
+                continue;
+            };
+            let over = match quota {
+                Quota::StorageBytes => self.used(quota) >= limit,
+                _ => self.used(quota) + submissions > limit,
+            };
+            if over {
+                return Err(quota.exceeded());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether usage that already counts one more stored submission of
+    /// `bytes` (as returned by `db::quotas::record`, under the tenant's row
+    /// lock) is within the limits, by the same rules as `check`.
+    pub fn check_recorded(&self, bytes: i64) -> Result<(), AppError> {
+        for quota in Quota::ALL {
+            let Some(limit) = self.limits.get(quota) else {
+                continue;
+            };
+            let over = match quota {
+                Quota::StorageBytes => self.used(quota) - bytes >= limit,
+                _ => self.used(quota) > limit,
+            };
+            if over {
+                return Err(quota.exceeded());
+            }
+        }
+        Ok(())
+    }
+
+    /// Warning thresholds crossed by adding `submissions` and `bytes`.
+    fn crossed(&self, submissions: i64, bytes: i64) -> Vec<(Quota, i16)> {
+        let mut crossed = Vec::new();
+        for quota in Quota::ALL {
+            let Some(limit) = self.limits.get(quota) else {
+                continue;
+            };
+            let before = self.used(quota);
+            let after = before + if quota == Quota::StorageBytes { bytes } else { submissions };
+            // Only the highest, so a jump from 70% to 100% sends one email
+            let reached = WARN_AT
+                .iter()
+                .rev()
+                .find(|&&pct| before * 100 < limit * pct as i64 && after * 100 >= limit * pct as i64);
+            if let Some(&pct) = reached {
+                crossed.push((quota, pct));
+            }
+        }
+        crossed
+    }
+}
+
+/// The quotas and usage of the tenant owning `project_id`.
+pub async fn load(state: &SharedState, project_id: Uuid) -> Result<TenantQuota, AppError> {
+    let row = db::quotas::usage_for_project(&state.pool, project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Endpoint not found".to_string()))?;
+    Ok(TenantQuota::new(row, &state.config.default_quotas))
+}
+
+/// Raise warnings for thresholds crossed by what was just stored, emailing
+/// the tenant's owners if system SMTP is set up. Runs in the background;
+/// each warning is raised once per period.
+pub fn send_warnings(state: &SharedState, quota: &TenantQuota, submissions: i64, bytes: i64) {
+    let crossed = quota.crossed(submissions, bytes);
+    if crossed.is_empty() {
+        return;
+    }
+    let state = state.clone();
+    let tenant_id = quota.tenant_id;
+    let tenant_name = quota.tenant_name.clone();
+    let limits = quota.limits;
+    tokio::spawn(async move {
+        let owners = match db::users::list_by_tenant(&state.pool, tenant_id).await {
+            Ok(users) => users.into_iter().filter(|u| u.role == "owner").collect::<Vec<_>>(),
+            Err(e) => {
+                tracing::error!("Failed to load owners for quota warning: {e}");
+                return;
+            }
+        };
+        for (quota, pct) in crossed {
+            match db::quotas::claim_warning(&state.pool, tenant_id, quota, &quota.period(), pct).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::error!("Failed to record quota warning: {e}");
+                    continue;
+                }
+            }
+            tracing::info!("Tenant {tenant_id} reached {pct}% of its {} quota", quota.as_str());
+            let Some(mailer) = &state.system_mailer else {
+                continue;
+            };
+            let limit = limits.get(quota).unwrap_or_default();
+            for owner in &owners {
+                if let Err(e) = mailer
+                    .send_quota_warning(&owner.email, &tenant_name, quota, pct, limit, &state.config.base_url)
+                    .await
+                {
+                    tracing::error!("Failed to send quota warning to {}: {e}", owner.email);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,71 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+
+use super::{Limit, RateLimitStore};
+
+/// Buckets in this process only: each replica enforces its own limits, and a
+/// restart forgets them.
+#[derive(Default)]
+pub struct MemoryStore {
+    buckets: DashMap<String, Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    full_at: Instant,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Bucket {
+    fn tokens_at(&self, now: Instant, limit: Limit) -> f64 {
+        let refill = now.duration_since(self.updated).as_secs_f64() * limit.per_sec();
+        (self.tokens + refill).min(limit.capacity())
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn acquire(&self, key: &str, limit: Limit) -> Result<(), u64> {
+        let now = Instant::now();
+        let mut bucket = self.buckets.entry(key.to_string()).or_insert_with(|| Bucket {
+            tokens: limit.capacity(),
+            updated: now,
+            full_at: now,
+        });
+
+        let tokens = bucket.tokens_at(now, limit);
+        if tokens < 1.0 {
+            return Err(limit.retry_after(tokens));
+        }
+        bucket.tokens = tokens - 1.0;
+        bucket.updated = now;
+        bucket.full_at = now + Duration::from_secs_f64((limit.capacity() - bucket.tokens) / limit.per_sec());
+        Ok(())
+    }
+
+    async fn peek(&self, key: &str, limit: Limit) -> Result<(), u64> {
+        let Some(bucket) = self.buckets.get(key) else {
+            return Ok(());
+        };
+        let tokens = bucket.tokens_at(Instant::now(), limit);
+        if tokens < 1.0 {
+            return Err(limit.retry_after(tokens));
+        }
+        Ok(())
+    }
+
+    async fn cleanup(&self) -> u64 {
+        let now = Instant::now();
+        let before = self.buckets.len();
+        self.buckets.retain(|_, bucket| bucket.full_at > now);
+        before.saturating_sub(self.buckets.len()) as u64
+    }
+}
@@ -0,0 +1,106 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+mod memory;
+mod postgres;
+
+pub use memory::MemoryStore;
+pub use postgres::PgStore;
+
+/// Failed logins allowed per email before attempts are refused.
+const LOGIN_MAX_FAILURES: u32 = 5;
+const LOGIN_WINDOW_SECS: u64 = 15 * 60;
+
+/// A token bucket holding `max` tokens, refilled at `max` per `window_secs`:
+/// bursts of up to `max`, and `max` per window sustained, with no reset at a
+/// window boundary.
+#[derive(Debug, Clone, Copy)]
+pub struct Limit {
+    pub max: u32,
+    pub window_secs: u64,
+}
+
+impl Limit {
+    pub fn capacity(&self) -> f64 {
+        self.max as f64
+    }
+
+    /// Tokens added per second.
+    pub fn per_sec(&self) -> f64 {
+        self.max as f64 / self.window_secs.max(1) as f64
+    }
+
+    /// Seconds until a bucket holding `tokens` has one to spend.
+    pub fn retry_after(&self, tokens: f64) -> u64 {
+        ((1.0 - tokens) / self.per_sec()).ceil().max(1.0) as u64
+    }
+}
+
+/// Where the buckets live. `MemoryStore` is per process; `PgStore` is shared
+/// by every replica on the database.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Take a token from `key`'s bucket. Err with retry-after seconds if empty.
+    async fn acquire(&self, key: &str, limit: Limit) -> Result<(), u64>;
+
+    /// Whether `key`'s bucket has a token, without taking it.
+    async fn peek(&self, key: &str, limit: Limit) -> Result<(), u64>;
+
+    /// Forget buckets that have refilled (a full bucket is the same as none).
+    /// Returns how many were removed.
+    async fn cleanup(&self) -> u64;
+}
+
+/// Per-IP-per-endpoint submission rate limiter.
+pub struct SubmissionRateLimiter {
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl SubmissionRateLimiter {
+    pub fn new(store: Arc<dyn RateLimitStore>) -> Self {
+        Self { store }
+    }
+
+    /// Check if request is allowed. Returns Ok(()) or Err with retry-after seconds.
+    pub async fn check(&self, endpoint_id: Uuid, ip: IpAddr, limit: u32, window_secs: u64) -> Result<(), u64> {
+        if limit == 0 {
+            return Err(window_secs);
+        }
+        let key = format!("submit:{endpoint_id}:{ip}");
+        self.store.acquire(&key, Limit { max: limit, window_secs }).await
+    }
+}
+
+/// Per-email login brute force limiter.
+pub struct LoginRateLimiter {
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl LoginRateLimiter {
+    pub fn new(store: Arc<dyn RateLimitStore>) -> Self {
+        Self { store }
+    }
+
+    /// Check if login attempt is allowed: 5 failures, then one more every 3 minutes.
+    /// Does NOT count the attempt — call `record_failure()` on invalid password.
+    pub async fn check(&self, email: &str) -> Result<(), u64> {
+        self.store.peek(&login_key(email), login_limit()).await
+    }
+
+    /// Record a failed login attempt for the given email.
+    pub async fn record_failure(&self, email: &str) {
+        // An empty bucket stays empty
+        let _ = self.store.acquire(&login_key(email), login_limit()).await;
+    }
+}
+
+fn login_key(email: &str) -> String {
+    format!("login:{}", email.to_lowercase())
+}
+
+fn login_limit() -> Limit {
+    Limit { max: LOGIN_MAX_FAILURES, window_secs: LOGIN_WINDOW_SECS }
+}
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use super::{Limit, RateLimitStore};
+use crate::db;
+
+/// Rows deleted per cleanup statement.
+const CLEANUP_BATCH: i64 = 1000;
+
+/// Buckets in the `rate_limits` table, shared by every replica and kept
+/// across restarts. If the database can't be reached the request is let
+/// through; it would fail on its next query anyway.
+pub struct PgStore {
+    pool: PgPool,
+}
+
+impl PgStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Wait for a bucket that was just found empty.
+    async fn retry_after(&self, key: &str, limit: Limit) -> u64 {
+        let tokens = db::rate_limits::available(&self.pool, key, limit.capacity(), limit.per_sec())
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or(0.0);
+        limit.retry_after(tokens)
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for PgStore {
+    async fn acquire(&self, key: &str, limit: Limit) -> Result<(), u64> {
+        match db::rate_limits::take(&self.pool, key, limit.capacity(), limit.per_sec()).await {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err(self.retry_after(key, limit).await),
+            Err(e) => {
+                tracing::error!("Rate limit check failed for {key}: {e}");
+                Ok(())
+            }
+        }
+    }
+
+    async fn peek(&self, key: &str, limit: Limit) -> Result<(), u64> {
+        match db::rate_limits::available(&self.pool, key, limit.capacity(), limit.per_sec()).await {
+            Ok(Some(tokens)) if tokens < 1.0 => Err(limit.retry_after(tokens)),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::error!("Rate limit check failed for {key}: {e}");
+                Ok(())
+            }
+        }
+    }
+
+    async fn cleanup(&self) -> u64 {
+        let mut removed = 0;
+        loop {
+            match db::rate_limits::delete_full(&self.pool, CLEANUP_BATCH).await {
+                Ok(deleted) => {
+                    removed += deleted;
+                    if deleted < CLEANUP_BATCH as u64 {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Rate limit cleanup failed: {e}");
+                    break;
+                }
+            }
+        }
+        removed
+    }
+}
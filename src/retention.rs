@@ -0,0 +1,181 @@
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tokio::sync::watch;
+
+use crate::db;
+use crate::middleware::audit;
+use crate::quota::Quotas;
+use crate::state::SharedState;
+use crate::storage::BlobStore;
+
+/// How often the purge job runs.
+pub const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Max submissions deleted per statement, so a large backlog doesn't hold long locks.
+const BATCH_SIZE: i64 = 1000;
+
+/// Upper bound for `retention_days` (100 years).
+pub const MAX_RETENTION_DAYS: i64 = 36500;
+
+/// Parse a `retention_days` value, accepting integers in `1..=MAX_RETENTION_DAYS`.
+pub fn parse_days(value: &Value) -> Option<i64> {
+    value
+        .as_i64()
+        .filter(|days| (1..=MAX_RETENTION_DAYS).contains(days))
+}
+
+/// Validate `settings.retention_days`: absent/null, or a whole number of days.
+pub fn validate(settings: &Value) -> Result<(), String> {
+    let days = &settings["retention_days"];
+    if days.is_null() || parse_days(days).is_some() {
+        return Ok(());
+    }
+    Err(format!(
+        "retention_days must be an integer from 1 to {MAX_RETENTION_DAYS}"
+    ))
+}
+
+/// The oldest `created_at` kept under a retention of `days`, or `None` if out of range.
+pub fn cutoff(now: DateTime<Utc>, days: i64) -> Option<DateTime<Utc>> {
+    now.checked_sub_signed(Duration::try_days(days)?)
+}
+
+/// Periodically purge submissions older than each endpoint's `retention_days`,
+/// then remove blobs for files whose submission is gone and expired idempotency keys,
+/// and recount each tenant's stored usage.
+/// Runs once at startup, then every `PURGE_INTERVAL` until shutdown is signaled.
+pub async fn run(state: SharedState, mut shutdown: watch::Receiver<bool>) {
+    loop {
+        match purge_expired(&state.pool).await {
+            Ok(0) => {}
+            Ok(total) => tracing::info!("Retention purge removed {total} submissions"),
+            Err(e) => tracing::error!("Retention purge failed: {e}"),
+        }
+
+        match remove_orphaned_files(&state.pool, state.blob_store.as_ref()).await {
+            Ok(0) => {}
+            Ok(total) => tracing::info!("Removed {total} orphaned files"),
+            Err(e) => tracing::error!("Orphaned file cleanup failed: {e}"),
+        }
+
+        match remove_expired_idempotency_keys(&state.pool).await {
+            Ok(0) => {}
+            Ok(total) => tracing::info!("Removed {total} expired idempotency keys"),
+            Err(e) => tracing::error!("Idempotency key cleanup failed: {e}"),
+        }
+
+        if let Err(e) = recount_usage(&state.pool, &state.config.default_quotas).await {
+            tracing::error!("Tenant usage recount failed: {e}");
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(PURGE_INTERVAL) => {}
+            _ = shutdown.changed() => break,
+        }
+    }
+}
+
+/// Run one purge pass over all endpoints with retention configured.
+/// Returns the total number of submissions deleted.
+pub async fn purge_expired(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let targets = db::endpoints::list_with_retention(pool).await?;
+    let mut total = 0;
+
+    for target in targets {
+        let now = Utc::now();
+        let Some(cutoff) = cutoff(now, target.retention_days) else {
+            continue;
+        };
+
+        let mut purged: u64 = 0;
+        loop {
+            let deleted =
+                db::submissions::delete_older_than(pool, target.endpoint_id, cutoff, BATCH_SIZE)
+                    .await?;
+            purged += deleted;
+            if deleted < BATCH_SIZE as u64 {
+                break;
+            }
+        }
+
+        db::endpoints::record_purge(pool, target.endpoint_id, now, purged as i64).await?;
+
+        if purged > 0 {
+            audit::log_event(
+                pool,
+                target.tenant_id,
+                None,
+                "submissions.purged",
+                "endpoint",
+                Some(target.endpoint_id),
+                Some(json!({
+                    "count": purged,
+                    "retention_days": target.retention_days,
+                    "cutoff": cutoff,
+                })),
+            )
+            .await;
+        }
+
+        total += purged;
+    }
+
+    Ok(total)
+}
+
+/// Delete blobs and rows for files whose submission has been deleted.
+/// A row is only removed once its blob is gone, so failures are retried next run.
+pub async fn remove_orphaned_files(
+    pool: &PgPool,
+    store: &dyn BlobStore,
+) -> Result<u64, sqlx::Error> {
+    let mut removed = 0;
+
+    loop {
+        let files = db::submission_files::list_orphaned(pool, BATCH_SIZE).await?;
+        let batch_len = files.len();
+        let mut batch_removed = 0;
+
+        for file in files {
+            match store.delete(&file.storage_key).await {
+                Ok(()) => {
+                    db::submission_files::delete(pool, file.id).await?;
+                    batch_removed += 1;
+                }
+                Err(e) => tracing::warn!("Failed to delete blob {}: {e}", file.storage_key),
+            }
+        }
+
+        removed += batch_removed;
+        // Stop on a short batch, or if nothing could be removed (avoid spinning)
+        if batch_len < BATCH_SIZE as usize || batch_removed == 0 {
+            break;
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Delete idempotency keys whose window has passed. Expired keys are already
+/// ignored on ingest; this only keeps the table small.
+pub async fn remove_expired_idempotency_keys(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let mut removed = 0;
+    loop {
+        let deleted = db::idempotency_keys::delete_expired(pool, BATCH_SIZE).await?;
+        removed += deleted;
+        if deleted < BATCH_SIZE as u64 {
+            break;
+        }
+    }
+    Ok(removed)
+}
+
+/// Recount stored submissions and bytes per tenant, correcting totals that
+/// cascaded deletes (endpoints, projects) left behind, and re-arm warnings
+/// for totals back under 80%.
+pub async fn recount_usage(pool: &PgPool, default_quotas: &Quotas) -> Result<(), sqlx::Error> {
+    db::quotas::recount(pool).await?;
+    db::quotas::clear_recovered_warnings(pool, default_quotas).await?;
+    Ok(())
+}
@@ -1,9 +1,13 @@
 use axum::extract::{Path, Query, State};
 use axum::Json;
-use serde::Deserialize;
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::actions::condition::Condition;
+use crate::actions::{secrets, signing};
 use crate::auth::extractor::AuthUser;
+use crate::crypto;
 use crate::db;
 use crate::error::AppError;
 use crate::middleware::audit;
@@ -15,6 +19,7 @@ pub struct CreateAction {
     pub action_type: String,
     pub config: serde_json::Value,
     pub position: Option<i32>,
+    pub condition: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -23,6 +28,38 @@ pub struct UpdateAction {
     pub config: serde_json::Value,
     pub position: i32,
     pub enabled: bool,
+    pub condition: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RotateSecret {
+    /// How long the old secret keeps being sent alongside the new one.
+    pub grace_period_hours: Option<i64>,
+}
+
+/// Default overlap after a rotation, in hours.
+const DEFAULT_GRACE_PERIOD_HOURS: i64 = 24;
+const MAX_GRACE_PERIOD_HOURS: i64 = 24 * 30;
+
+/// An action plus its plaintext signing secret. Only returned when the secret
+/// is generated (on create and rotate); it can't be retrieved afterwards.
+#[derive(Serialize)]
+pub struct ActionWithSecret {
+    #[serde(flatten)]
+    pub action: Action,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+}
+
+/// Blank conditions mean "always run"; anything else must parse.
+fn validate_condition(condition: Option<String>) -> Result<Option<String>, AppError> {
+    let Some(condition) = condition.map(|c| c.trim().to_string()).filter(|c| !c.is_empty())
+    else {
+        return Ok(None);
+    };
+    Condition::parse(&condition)
+        .map_err(|e| AppError::BadRequest(format!("Invalid condition: {e}")))?;
+    Ok(Some(condition))
 }
 
 #[derive(Deserialize)]
@@ -50,30 +87,42 @@ pub async fn create(
     State(state): State<SharedState>,
     Path(endpoint_id): Path<Uuid>,
     Json(req): Json<CreateAction>,
-) -> Result<Json<Action>, AppError> {
+) -> Result<Json<ActionWithSecret>, AppError> {
     // Verify endpoint belongs to tenant
     db::endpoints::find_by_id_scoped(&state.pool, endpoint_id, auth.tenant_id())
         .await?
         .ok_or_else(|| AppError::NotFound("Endpoint not found".to_string()))?;
 
     // Validate config against module
-    if let Some(module) = state.modules.get(&req.action_type) {
-        module
-            .validate_config(&req.config)
-            .map_err(|e| AppError::BadRequest(e.message))?;
-    } else {
-        return Err(AppError::BadRequest(format!(
-            "Unknown action type: {}",
-            req.action_type
-        )));
-    }
+    let module = state.modules.get(&req.action_type).ok_or_else(|| {
+        AppError::BadRequest(format!("Unknown action type: {}", req.action_type))
+    })?;
+    module
+        .validate_config(&req.config)
+        .map_err(|e| AppError::BadRequest(e.message))?;
+    let condition = validate_condition(req.condition)?;
+    let config = secrets::seal(
+        req.config,
+        module.secret_fields(),
+        &state.config.encryption_key,
+    )
+    .map_err(AppError::Internal)?;
+
+    let signing_secret = module.signs_requests().then(signing::generate_secret);
+    let signing_secret_enc = signing_secret
+        .as_deref()
+        .map(|s| crypto::encrypt(s, &state.config.encryption_key))
+        .transpose()
+        .map_err(AppError::Internal)?;
 
     let action = db::actions::create(
         &state.pool,
         endpoint_id,
         &req.action_type,
-        &req.config,
+        &config,
         req.position.unwrap_or(0),
+        condition.as_deref(),
+        signing_secret_enc.as_deref(),
     )
     .await?;
 
@@ -88,7 +137,75 @@ pub async fn create(
     )
     .await;
 
-    Ok(Json(action))
+    Ok(Json(ActionWithSecret {
+        action,
+        signing_secret,
+    }))
+}
+
+pub async fn rotate_secret(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<RotateSecret>,
+) -> Result<Json<ActionWithSecret>, AppError> {
+    let action = db::actions::find_by_id_scoped(&state.pool, id, auth.tenant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Action not found".to_string()))?;
+
+    let signs = state
+        .modules
+        .get(&action.action_type)
+        .is_some_and(|m| m.signs_requests());
+    if !signs {
+        return Err(AppError::BadRequest(format!(
+            "Action type '{}' does not sign requests",
+            action.action_type
+        )));
+    }
+
+    let grace_hours = req
+        .grace_period_hours
+        .unwrap_or(DEFAULT_GRACE_PERIOD_HOURS);
+    if !(0..=MAX_GRACE_PERIOD_HOURS).contains(&grace_hours) {
+        return Err(AppError::BadRequest(format!(
+            "grace_period_hours must be between 0 and {MAX_GRACE_PERIOD_HOURS}"
+        )));
+    }
+
+    let secret = signing::generate_secret();
+    let secret_enc =
+        crypto::encrypt(&secret, &state.config.encryption_key).map_err(AppError::Internal)?;
+    let previous_expires_at = Utc::now() + Duration::hours(grace_hours);
+
+    let action = db::actions::rotate_secret(
+        &state.pool,
+        id,
+        auth.tenant_id(),
+        &secret_enc,
+        previous_expires_at,
+    )
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::RowNotFound => AppError::NotFound("Action not found".to_string()),
+        _ => AppError::Database(e),
+    })?;
+
+    audit::log_event(
+        &state.pool,
+        auth.tenant_id(),
+        Some(auth.user_id),
+        "action.secret_rotated",
+        "action",
+        Some(action.id),
+        Some(serde_json::json!({ "grace_period_hours": grace_hours })),
+    )
+    .await;
+
+    Ok(Json(ActionWithSecret {
+        action,
+        signing_secret: Some(secret),
+    }))
 }
 
 pub async fn update(
@@ -97,11 +214,25 @@ pub async fn update(
     Path(id): Path<Uuid>,
     Json(req): Json<UpdateAction>,
 ) -> Result<Json<Action>, AppError> {
-    // Validate config
+    let existing = db::actions::find_by_id_scoped(&state.pool, id, auth.tenant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Action not found".to_string()))?;
+
+    let condition = validate_condition(req.condition)?;
+
+    // Validate config against plaintext secrets, including ones carried over
+    let mut config = req.config;
+    let key = &state.config.encryption_key;
     if let Some(module) = state.modules.get(&req.action_type) {
+        let fields = module.secret_fields();
+        if existing.action_type == req.action_type {
+            secrets::carry_over(&mut config, &existing.config, fields);
+        }
+        let plain = secrets::open(&config, fields, key).map_err(AppError::Internal)?;
         module
-            .validate_config(&req.config)
+            .validate_config(&plain)
             .map_err(|e| AppError::BadRequest(e.message))?;
+        config = secrets::seal(plain, fields, key).map_err(AppError::Internal)?;
     }
 
     let action = db::actions::update(
@@ -109,9 +240,10 @@ pub async fn update(
         id,
         auth.tenant_id(),
         &req.action_type,
-        &req.config,
+        &config,
         req.position,
         req.enabled,
+        condition.as_deref(),
     )
     .await
     .map_err(|e| match e {
@@ -166,7 +298,7 @@ pub async fn log(
         .ok_or_else(|| AppError::NotFound("Action not found".to_string()))?;
 
     let page = params.page.unwrap_or(1).max(1);
-    let per_page = params.per_page.unwrap_or(20).min(100).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100);
     let offset = (page - 1) * per_page;
 
     let logs = db::action_log::list_by_action(&state.pool, id, per_page, offset).await?;
@@ -9,6 +9,7 @@ use crate::db;
 use crate::error::AppError;
 use crate::middleware::audit;
 use crate::models::{Tenant, User};
+use crate::quota::Quotas;
 use crate::state::SharedState;
 
 #[derive(Deserialize)]
@@ -72,7 +73,7 @@ pub async fn get_tenant(
 ) -> Result<Json<serde_json::Value>, AppError> {
     auth.require_system_admin()?;
 
-    let tenant = db::tenants::find_by_id(&state.pool, id)
+    let tenant = db::quotas::usage_for_tenant(&state.pool, id)
         .await?
         .ok_or_else(|| AppError::NotFound("Tenant not found".to_string()))?;
 
@@ -84,6 +85,35 @@ pub async fn get_tenant(
     })))
 }
 
+/// Set a tenant's quotas; `null` falls back to the instance default.
+pub async fn update_tenant_quotas(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<Quotas>,
+) -> Result<Json<Tenant>, AppError> {
+    auth.require_system_admin()?;
+    req.validate().map_err(AppError::BadRequest)?;
+
+    db::tenants::find_by_id(&state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Tenant not found".to_string()))?;
+    let tenant = db::quotas::update(&state.pool, id, &req).await?;
+
+    audit::log_event(
+        &state.pool,
+        auth.tenant_id(),
+        Some(auth.user_id),
+        "tenant.quotas_updated",
+        "tenant",
+        Some(id),
+        Some(serde_json::json!(req)),
+    )
+    .await;
+
+    Ok(Json(tenant))
+}
+
 pub async fn delete_tenant(
     auth: AuthUser,
     State(state): State<SharedState>,
@@ -140,7 +170,7 @@ pub async fn create_user(
         .await?
         .ok_or_else(|| AppError::NotFound("Tenant not found".to_string()))?;
 
-    let pw_hash = password::hash(&req.password).map_err(|e| AppError::Internal(e))?;
+    let pw_hash = password::hash(&req.password).map_err(AppError::Internal)?;
 
     let user = db::users::create(
         &state.pool,
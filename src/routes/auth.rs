@@ -143,8 +143,8 @@ pub async fn register(
 
     // Generate tokens
     let claims = Claims::new(user.id, tenant.id, "owner".to_string(), true);
-    let access_token = encode_token(&claims, &state.config.jwt_secret)
-        .map_err(|e| AppError::Internal(e))?;
+    let access_token =
+        encode_token(&claims, &state.config.jwt_secret).map_err(AppError::Internal)?;
 
     let refresh = generate_refresh_token();
     let refresh_hash = hash_token(&refresh);
@@ -179,7 +179,7 @@ pub async fn login(
     Json(req): Json<LoginRequest>,
 ) -> Result<(CookieJar, Json<AuthResponse>), AppError> {
     // Rate limit check
-    if let Err(_) = state.login_limiter.check(&req.email) {
+    if state.login_limiter.check(&req.email).await.is_err() {
         return Err(AppError::RateLimited(
             "Too many login attempts. Please try again later.".to_string(),
         ));
@@ -189,11 +189,10 @@ pub async fn login(
         .await?
         .ok_or_else(|| AppError::Unauthorized("Invalid credentials".to_string()))?;
 
-    let valid = password::verify(&req.password, &user.password_hash)
-        .map_err(|e| AppError::Internal(e))?;
+    let valid = password::verify(&req.password, &user.password_hash).map_err(AppError::Internal)?;
 
     if !valid {
-        state.login_limiter.record_failure(&req.email);
+        state.login_limiter.record_failure(&req.email).await;
         return Err(AppError::Unauthorized("Invalid credentials".to_string()));
     }
 
@@ -203,8 +202,8 @@ pub async fn login(
         user.role.clone(),
         user.is_system_admin,
     );
-    let access_token = encode_token(&claims, &state.config.jwt_secret)
-        .map_err(|e| AppError::Internal(e))?;
+    let access_token =
+        encode_token(&claims, &state.config.jwt_secret).map_err(AppError::Internal)?;
 
     let refresh = generate_refresh_token();
     let refresh_hash = hash_token(&refresh);
@@ -276,8 +275,8 @@ pub async fn refresh(
         user.role.clone(),
         user.is_system_admin,
     );
-    let access_token = encode_token(&claims, &state.config.jwt_secret)
-        .map_err(|e| AppError::Internal(e))?;
+    let access_token =
+        encode_token(&claims, &state.config.jwt_secret).map_err(AppError::Internal)?;
 
     let new_refresh = generate_refresh_token();
     let new_refresh_hash = hash_token(&new_refresh);
@@ -329,13 +328,14 @@ pub async fn forgot_password(
             let token = generate_refresh_token();
             let token_hash = hash_token(&token);
 
-            if let Ok(_) = db::password_reset_tokens::create(
+            if db::password_reset_tokens::create(
                 &pool,
                 user.id,
                 &token_hash,
                 Utc::now() + Duration::hours(1),
             )
             .await
+            .is_ok()
             {
                 if let Some(mailer) = mailer {
                     let reset_url = format!("{base_url}/auth/reset-password?token={token}");
@@ -372,8 +372,7 @@ pub async fn reset_password(
     db::password_reset_tokens::mark_used(&state.pool, reset_token.id).await?;
 
     // Update password
-    let pw_hash =
-        password::hash(&req.password).map_err(|e| AppError::Internal(e))?;
+    let pw_hash = password::hash(&req.password).map_err(AppError::Internal)?;
     db::users::update_password(&state.pool, reset_token.user_id, &pw_hash).await?;
 
     // Nuke all refresh tokens
@@ -405,8 +404,8 @@ pub async fn change_password(
         .await?
         .ok_or_else(|| AppError::Unauthorized("User not found".to_string()))?;
 
-    let valid = password::verify(&req.current_password, &user.password_hash)
-        .map_err(|e| AppError::Internal(e))?;
+    let valid =
+        password::verify(&req.current_password, &user.password_hash).map_err(AppError::Internal)?;
 
     if !valid {
         return Err(AppError::Unauthorized(
@@ -414,7 +413,7 @@ pub async fn change_password(
         ));
     }
 
-    let pw_hash = password::hash(&req.new_password).map_err(|e| AppError::Internal(e))?;
+    let pw_hash = password::hash(&req.new_password).map_err(AppError::Internal)?;
     db::users::update_password(&state.pool, user.id, &pw_hash).await?;
 
     // Nuke all existing refresh tokens
@@ -427,8 +426,8 @@ pub async fn change_password(
         user.role.clone(),
         user.is_system_admin,
     );
-    let access_token = encode_token(&claims, &state.config.jwt_secret)
-        .map_err(|e| AppError::Internal(e))?;
+    let access_token =
+        encode_token(&claims, &state.config.jwt_secret).map_err(AppError::Internal)?;
 
     let refresh = generate_refresh_token();
     let refresh_hash = hash_token(&refresh);
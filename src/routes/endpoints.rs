@@ -8,7 +8,16 @@ use crate::db;
 use crate::error::AppError;
 use crate::middleware::audit;
 use crate::models::Endpoint;
+use crate::retention;
 use crate::state::SharedState;
+use crate::submission::fields::{self, ValidationMode};
+use crate::submission::parser::{BodyKind, FormKeys, GetIngest};
+use crate::submission::duplicates::Duplicates;
+use crate::submission::idempotency::Idempotency;
+use crate::submission::spam::SpamSettings;
+use crate::submission::access::{self, AccessRules};
+use crate::submission::cors::CorsPolicy;
+use crate::submission::{captcha, capture, redaction, signature};
 
 #[derive(Deserialize)]
 pub struct CreateEndpoint {
@@ -44,7 +53,7 @@ pub async fn create(
     auth: AuthUser,
     State(state): State<SharedState>,
     Path(project_id): Path<Uuid>,
-    Json(req): Json<CreateEndpoint>,
+    Json(mut req): Json<CreateEndpoint>,
 ) -> Result<Json<Endpoint>, AppError> {
     // Verify project belongs to tenant
     db::projects::find_by_id(&state.pool, project_id, auth.tenant_id())
@@ -53,6 +62,8 @@ pub async fn create(
 
     let slug = req.slug.unwrap_or_else(|| slugify(&req.name));
     validate_slug(&slug)?;
+    fields::parse_definitions(req.fields.as_ref()).map_err(AppError::BadRequest)?;
+    prepare_settings(req.settings.as_mut(), &state)?;
 
     let endpoint = db::endpoints::create(
         &state.pool,
@@ -99,10 +110,12 @@ pub async fn update(
     auth: AuthUser,
     State(state): State<SharedState>,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateEndpoint>,
+    Json(mut req): Json<UpdateEndpoint>,
 ) -> Result<Json<Endpoint>, AppError> {
     let slug = req.slug.unwrap_or_else(|| slugify(&req.name));
     validate_slug(&slug)?;
+    fields::parse_definitions(req.fields.as_ref()).map_err(AppError::BadRequest)?;
+    prepare_settings(req.settings.as_mut(), &state)?;
 
     let endpoint = db::endpoints::update(
         &state.pool,
@@ -165,6 +178,39 @@ fn slugify(name: &str) -> String {
         .join("-")
 }
 
+/// Validate settings before saving and encrypt any plaintext secrets in them.
+fn prepare_settings(
+    settings: Option<&mut serde_json::Value>,
+    state: &SharedState,
+) -> Result<(), AppError> {
+    let Some(settings) = settings else {
+        return Ok(());
+    };
+    let encryption_key = state.config.encryption_key.as_str();
+    access::normalize(settings).map_err(AppError::BadRequest)?;
+    if state.geoip.is_none()
+        && AccessRules::from_settings(settings).is_ok_and(|rules| rules.has_country_rules())
+    {
+        return Err(AppError::BadRequest(
+            "Country rules need a GeoIP database (WEBHOOKER_GEOIP_DB)".to_string(),
+        ));
+    }
+    CorsPolicy::from_settings(settings).map_err(AppError::BadRequest)?;
+    redaction::parse_rules(settings).map_err(AppError::BadRequest)?;
+    capture::validate(settings).map_err(AppError::BadRequest)?;
+    retention::validate(settings).map_err(AppError::BadRequest)?;
+    ValidationMode::from_settings(settings).map_err(AppError::BadRequest)?;
+    FormKeys::from_settings(settings).map_err(AppError::BadRequest)?;
+    GetIngest::validate(settings).map_err(AppError::BadRequest)?;
+    BodyKind::allowed(settings).map_err(AppError::BadRequest)?;
+    Idempotency::from_settings(settings).map_err(AppError::BadRequest)?;
+    Duplicates::from_settings(settings).map_err(AppError::BadRequest)?;
+    SpamSettings::from_settings(settings, encryption_key).map_err(AppError::BadRequest)?;
+    signature::seal_secret(settings, encryption_key).map_err(AppError::BadRequest)?;
+    captcha::seal_secret(settings, encryption_key).map_err(AppError::BadRequest)?;
+    Ok(())
+}
+
 fn validate_slug(slug: &str) -> Result<(), AppError> {
     if slug.is_empty() || slug.len() > 100 {
         return Err(AppError::BadRequest(
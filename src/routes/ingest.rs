@@ -1,113 +1,280 @@
 use std::net::IpAddr;
 
 use axum::body::Bytes;
-use axum::extract::{ConnectInfo, Path, State};
-use axum::http::{HeaderMap, StatusCode};
+use axum::extract::{ConnectInfo, Path, RawQuery, State};
+use axum::http::header::VARY;
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
 use axum::response::{IntoResponse, Redirect, Response};
 use axum::Json;
+use chrono::Utc;
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::db;
+use crate::error::AppError;
+use crate::models::Endpoint;
 use crate::state::SharedState;
-use crate::submission::{parser, pipeline};
-
-/// Extract the CORS allowed origin from endpoint settings, defaulting to "*".
-fn get_cors_origin(settings: &Option<serde_json::Value>) -> String {
-    settings
-        .as_ref()
-        .and_then(|s| {
-            s.get("cors_origins")
-                .and_then(|v| v.as_array())
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                })
-        })
-        .unwrap_or_else(|| "*".to_string())
+use crate::submission::access::{self, AccessRules};
+use crate::submission::signature::{self, VerifyError};
+use crate::submission::capture::{CaptureSettings, CapturedRequest};
+use crate::submission::cors::{Cors, CorsPolicy};
+use crate::submission::fields::FieldErrors;
+use crate::submission::parser::{BodyKind, FormKeys, GetIngest};
+use crate::submission::pipeline::PipelineResult;
+use crate::submission::spam::timing;
+use crate::submission::{metadata, parser, pipeline};
+
+/// Items per batch (or NDJSON body) unless `settings.batch_max_items` says otherwise.
+const DEFAULT_BATCH_MAX_ITEMS: u64 = 100;
+const BATCH_MAX_ITEMS_LIMIT: u64 = parser::MAX_NDJSON_LINES as u64;
+
+/// Request headers a browser may send cross-origin: the body type, the
+/// idempotency key, and each signature scheme's headers (`X-Signature` being
+/// the HMAC scheme's default; a custom name isn't listed).
+const ALLOW_HEADERS: &str = "Content-Type, Idempotency-Key, X-Signature, X-Hub-Signature-256, \
+    Stripe-Signature, X-Slack-Signature, X-Slack-Request-Timestamp";
+
+/// Match the request's origin against the endpoint's CORS allowlist.
+fn get_cors(endpoint: &Endpoint, headers: &HeaderMap) -> Cors {
+    let settings = endpoint.settings.as_ref().unwrap_or(&serde_json::Value::Null);
+    // Invalid settings are rejected on save
+    CorsPolicy::from_settings(settings).unwrap_or_default().check(headers)
 }
 
 /// Wrap a response with CORS headers.
-fn with_cors(response: Response, origin: &str) -> Response {
+fn with_cors(response: Response, cors: &Cors) -> Response {
     let mut response = response;
     let headers = response.headers_mut();
-    headers.insert("Access-Control-Allow-Origin", origin.parse().unwrap_or_else(|_| "*".parse().unwrap()));
-    headers.insert("Access-Control-Allow-Methods", "POST, OPTIONS".parse().unwrap());
-    headers.insert("Access-Control-Allow-Headers", "Content-Type".parse().unwrap());
+    if let Some(origin) = cors.allow_origin.as_deref().and_then(|o| o.parse().ok()) {
+        headers.insert("Access-Control-Allow-Origin", origin);
+    }
+    if cors.vary {
+        headers.append(VARY, HeaderValue::from_static("Origin"));
+    }
+    headers.insert("Access-Control-Allow-Methods", HeaderValue::from_static("GET, POST, OPTIONS"));
+    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static(ALLOW_HEADERS));
     response
 }
 
+/// Refuse origins outside the allowlist before anything is stored.
+fn check_origin(cors: &Cors) -> Result<(), AppError> {
+    if cors.allowed {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden("Origin not allowed".to_string()))
+    }
+}
+
+/// `url` with an `errors` query param holding the per-field errors as JSON.
+/// Only http(s) URLs are used; anything else falls back to a 422 response.
+fn validation_redirect(url: &str, errors: &FieldErrors) -> Option<String> {
+    let mut url = reqwest::Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let kept: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != "errors")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(kept)
+        .append_pair("errors", &json!(errors).to_string());
+    Some(url.to_string())
+}
+
+async fn load_endpoint(state: &SharedState, endpoint_id: Uuid) -> Result<Endpoint, Response> {
+    db::endpoints::find_by_id(&state.pool, endpoint_id)
+        .await
+        .map_err(|_| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Internal error"}))).into_response()
+        })?
+        .ok_or_else(|| {
+            (StatusCode::NOT_FOUND, Json(json!({"error": "Endpoint not found"}))).into_response()
+        })
+}
+
+/// Turn away clients outside the endpoint's or tenant's access rules, before
+/// anything else is looked at.
+async fn check_access(
+    state: &SharedState,
+    endpoint: &Endpoint,
+    headers: &HeaderMap,
+    peer_ip: IpAddr,
+) -> Result<(), Response> {
+    let internal = || {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Internal error"}))).into_response()
+    };
+    let denylist = db::tenants::ip_denylist_for_project(&state.pool, endpoint.project_id)
+        .await
+        .map_err(|_| internal())?;
+    let settings = endpoint.settings.clone().unwrap_or(json!({}));
+    // Invalid settings are rejected on save
+    let rules = AccessRules::from_settings(&settings).unwrap_or_default();
+    let ip = metadata::extract_ip(headers, Some(peer_ip), &state.config.trusted_proxies);
+
+    match rules.check(ip, &access::parse_denylist(&denylist), state.geoip.as_deref()) {
+        Ok(()) => Ok(()),
+        Err(reason) => {
+            tracing::debug!("Access denied for endpoint {}: {reason}", endpoint.id);
+            if let Err(e) = db::endpoints::record_access_denial(&state.pool, endpoint.id).await {
+                tracing::error!("Failed to record access denial: {e}");
+            }
+            Err(AppError::Forbidden("Access denied".to_string()).into_response())
+        }
+    }
+}
+
+/// Verify against the raw bytes, before anything is parsed or stored.
+async fn check_signature(
+    state: &SharedState,
+    endpoint: &Endpoint,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Result<(), Response> {
+    let Some(ref settings) = endpoint.settings else {
+        return Ok(());
+    };
+    match signature::verify(
+        settings,
+        headers,
+        body,
+        &state.config.encryption_key,
+        chrono::Utc::now(),
+    ) {
+        Ok(()) => Ok(()),
+        Err(VerifyError::Invalid(reason)) => {
+            tracing::debug!("Signature check failed for endpoint {}: {reason}", endpoint.id);
+            if let Err(e) = db::endpoints::record_signature_failure(&state.pool, endpoint.id).await {
+                tracing::error!("Failed to record signature failure: {e}");
+            }
+            Err((StatusCode::UNAUTHORIZED, Json(json!({"error": "Invalid signature"}))).into_response())
+        }
+        Err(VerifyError::Config(e)) => {
+            tracing::error!("Signature settings unusable for endpoint {}: {e}", endpoint.id);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Internal error"}))).into_response())
+        }
+    }
+}
+
 pub async fn ingest(
     State(state): State<SharedState>,
     Path(endpoint_id): Path<Uuid>,
     ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
+    method: Method,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<Response, Response> {
-    let endpoint = db::endpoints::find_by_id(&state.pool, endpoint_id)
+    let endpoint = load_endpoint(&state, endpoint_id).await?;
+    let cors = get_cors(&endpoint, &headers);
+    check_access(&state, &endpoint, &headers, addr.ip())
         .await
-        .map_err(|_| {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Internal error"}))).into_response()
-        })?
-        .ok_or_else(|| {
-            (StatusCode::NOT_FOUND, Json(json!({"error": "Endpoint not found"}))).into_response()
-        })?;
-
-    let cors_origin = get_cors_origin(&endpoint.settings);
+        .map_err(|e| with_cors(e, &cors))?;
+    check_origin(&cors).map_err(|e| with_cors(e.into_response(), &cors))?;
+    check_signature(&state, &endpoint, &headers, &body)
+        .await
+        .map_err(|e| with_cors(e, &cors))?;
 
     // Parse body
     let content_type = headers
         .get("content-type")
         .and_then(|v| v.to_str().ok());
 
-    let raw_data = if content_type.is_some_and(|ct| ct.contains("multipart/form-data")) {
-        parser::parse_multipart(&headers, body)
-            .await
-            .map_err(|e| {
-                with_cors(
-                    (StatusCode::BAD_REQUEST, Json(json!({"error": e}))).into_response(),
-                    &cors_origin,
-                )
-            })?
-    } else {
-        parser::parse_body(content_type, &body).map_err(|e| {
-            with_cors(
-                (StatusCode::BAD_REQUEST, Json(json!({"error": e}))).into_response(),
-                &cors_origin,
-            )
-        })?
+    let bad_request = |e: String| {
+        with_cors(
+            (StatusCode::BAD_REQUEST, Json(json!({"error": e}))).into_response(),
+            &cors,
+        )
     };
+    let settings = endpoint.settings.clone().unwrap_or(json!({}));
+    let allowed = BodyKind::allowed(&settings).unwrap_or(BodyKind::ALL.to_vec());
+    let kind = BodyKind::from_content_type(content_type)
+        .filter(|k| allowed.contains(k))
+        .ok_or_else(|| {
+            let e = AppError::UnsupportedMediaType(format!(
+                "Content type not accepted: {}",
+                content_type.unwrap_or("none")
+            ));
+            with_cors(e.into_response(), &cors)
+        })?;
+    let keys = FormKeys::from_settings(&settings).unwrap_or(FormKeys::Nested);
 
     let peer_ip: Option<IpAddr> = Some(addr.ip());
+    let captured = capture_request(&settings, &method, &headers, &body);
 
-    let result = pipeline::run(&state, &endpoint, &headers, peer_ip, raw_data)
-        .await
-        .map_err(|e| {
-            let status = if e.contains("Rate limited") {
-                StatusCode::TOO_MANY_REQUESTS
-            } else {
-                StatusCode::INTERNAL_SERVER_ERROR
-            };
-            with_cors(
-                (status, Json(json!({"error": e}))).into_response(),
-                &cors_origin,
+    let (raw_data, files) = match kind {
+        BodyKind::Multipart => parser::parse_multipart(&headers, body.clone(), keys)
+            .await
+            .map_err(bad_request)?,
+        BodyKind::Ndjson => {
+            let lines = parser::parse_ndjson(&body).map_err(bad_request)?;
+            return ingest_many(
+                &state,
+                &endpoint,
+                &headers,
+                peer_ip,
+                captured.as_ref(),
+                lines,
+                "line",
             )
-        })?;
+            .await
+            .map(|r| with_cors(r, &cors))
+            .map_err(|e| with_cors(e.into_response(), &cors));
+        }
+        kind => (
+            parser::parse_body(kind, &body, keys).map_err(bad_request)?,
+            Vec::new(),
+        ),
+    };
 
-    // If redirect configured and it's a form submission, redirect
-    if let Some(ref url) = result.redirect_url {
-        if content_type.is_some_and(|ct| ct.contains("form")) {
-            return Ok(with_cors(Redirect::to(url).into_response(), &cors_origin));
+    let is_form = matches!(kind, BodyKind::Form | BodyKind::Multipart);
+    let result = match pipeline::run(
+        &state,
+        &endpoint,
+        &headers,
+        peer_ip,
+        raw_data,
+        files,
+        captured.as_ref(),
+    )
+    .await
+    {
+        Ok(result) => result,
+        // Browsers posting a form get sent back to it with the errors attached
+        Err(AppError::Validation(errors)) if is_form => {
+            let back = endpoint
+                .settings
+                .as_ref()
+                .and_then(|s| s["error_redirect_url"].as_str())
+                .filter(|s| !s.is_empty())
+                .or_else(|| headers.get("referer").and_then(|v| v.to_str().ok()))
+                .and_then(|url| validation_redirect(url, &errors));
+            let response = match back {
+                Some(url) => Redirect::to(&url).into_response(),
+                None => AppError::Validation(errors).into_response(),
+            };
+            return Err(with_cors(response, &cors));
         }
+        Err(e) => return Err(with_cors(e.into_response(), &cors)),
+    };
+
+    // If redirect configured and it's a form submission, redirect
+    if let Some(ref url) = result.redirect_url
+        && is_form
+    {
+        return Ok(with_cors(Redirect::to(url).into_response(), &cors));
+    }
+
+    if result.duplicate {
+        return Ok(with_cors(duplicate_response(&result), &cors));
     }
 
     if result.spam {
         // Silent 200 for spam
         return Ok(with_cors(
             (StatusCode::OK, Json(json!({"status": "ok"}))).into_response(),
-            &cors_origin,
+            &cors,
         ));
     }
 
@@ -120,33 +287,275 @@ pub async fn ingest(
             })),
         )
             .into_response(),
-        &cors_origin,
+        &cors,
+    ))
+}
+
+/// A repeated idempotency key: the original submission, nothing new stored.
+fn duplicate_response(result: &PipelineResult) -> Response {
+    (
+        StatusCode::OK,
+        Json(json!({
+            "status": "duplicate",
+            "submission_id": result.submission_id,
+        })),
+    )
+        .into_response()
+}
+
+/// The verbatim request, if the endpoint captures them.
+fn capture_request(
+    settings: &serde_json::Value,
+    method: &Method,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Option<CapturedRequest> {
+    let capture = CaptureSettings::from_settings(settings);
+    capture.enabled.then(|| capture.capture(method, headers, body))
+}
+
+/// 1×1 transparent GIF returned in pixel mode.
+const PIXEL_GIF: &[u8] = &[
+    0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0x21, 0xf9, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x02, 0x44, 0x01, 0x00, 0x3b,
+];
+
+/// `GET /v1/e/{endpoint_id}?k=v`: the query parameters become the submission,
+/// for senders that can't POST. Opt-in via `settings.get_ingest`.
+pub async fn ingest_get(
+    State(state): State<SharedState>,
+    Path(endpoint_id): Path<Uuid>,
+    ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Result<Response, Response> {
+    let endpoint = load_endpoint(&state, endpoint_id).await?;
+    let cors = get_cors(&endpoint, &headers);
+    check_access(&state, &endpoint, &headers, addr.ip())
+        .await
+        .map_err(|e| with_cors(e, &cors))?;
+    check_origin(&cors).map_err(|e| with_cors(e.into_response(), &cors))?;
+
+    let settings = endpoint.settings.clone().unwrap_or(json!({}));
+    let get = GetIngest::from_settings(&settings);
+    if !get.enabled {
+        return Err(with_cors(
+            (
+                StatusCode::METHOD_NOT_ALLOWED,
+                Json(json!({"error": "GET submissions are not enabled for this endpoint"})),
+            )
+                .into_response(),
+            &cors,
+        ));
+    }
+    // A signature wouldn't cover the query string, so a signed endpoint only
+    // takes POSTs (settings saved before that was refused on save)
+    if !settings["signature"].is_null() {
+        return Err(with_cors(
+            (
+                StatusCode::METHOD_NOT_ALLOWED,
+                Json(json!({"error": "This endpoint verifies signatures and only accepts POST"})),
+            )
+                .into_response(),
+            &cors,
+        ));
+    }
+
+    let keys = FormKeys::from_settings(&settings).unwrap_or(FormKeys::Nested);
+    let query = query.unwrap_or_default();
+    let raw_data = parser::parse_body(BodyKind::Form, query.as_bytes(), keys).map_err(|e| {
+        with_cors(
+            (StatusCode::BAD_REQUEST, Json(json!({"error": e}))).into_response(),
+            &cors,
+        )
+    })?;
+
+    let result = pipeline::run(
+        &state,
+        &endpoint,
+        &headers,
+        Some(addr.ip()),
+        raw_data,
+        Vec::new(),
+        None,
+    )
+    .await
+    .map_err(|e| with_cors(e.into_response(), &cors))?;
+
+    let response = if get.pixel {
+        (
+            StatusCode::OK,
+            [
+                ("Content-Type", "image/gif"),
+                ("Cache-Control", "no-store, no-cache, must-revalidate"),
+            ],
+            PIXEL_GIF,
+        )
+            .into_response()
+    } else if let Some(url) = &result.redirect_url {
+        Redirect::to(url).into_response()
+    } else if result.duplicate {
+        duplicate_response(&result)
+    } else if result.spam {
+        (StatusCode::OK, Json(json!({"status": "ok"}))).into_response()
+    } else {
+        (
+            StatusCode::CREATED,
+            Json(json!({
+                "status": "created",
+                "submission_id": result.submission_id,
+            })),
+        )
+            .into_response()
+    };
+    Ok(with_cors(response, &cors))
+}
+
+/// Bulk ingest: a JSON array, each element stored as its own submission.
+pub async fn ingest_batch(
+    State(state): State<SharedState>,
+    Path(endpoint_id): Path<Uuid>,
+    ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
+    method: Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, Response> {
+    let endpoint = load_endpoint(&state, endpoint_id).await?;
+    let cors = get_cors(&endpoint, &headers);
+    check_access(&state, &endpoint, &headers, addr.ip())
+        .await
+        .map_err(|e| with_cors(e, &cors))?;
+    check_origin(&cors).map_err(|e| with_cors(e.into_response(), &cors))?;
+    check_signature(&state, &endpoint, &headers, &body)
+        .await
+        .map_err(|e| with_cors(e, &cors))?;
+
+    let settings = endpoint.settings.clone().unwrap_or(json!({}));
+    let allowed = BodyKind::allowed(&settings).unwrap_or(BodyKind::ALL.to_vec());
+    let content_type = headers.get("content-type").and_then(|v| v.to_str().ok());
+
+    let result = async {
+        if BodyKind::from_content_type(content_type) != Some(BodyKind::Json)
+            || !allowed.contains(&BodyKind::Json)
+        {
+            return Err(AppError::UnsupportedMediaType("Batches must be JSON".to_string()));
+        }
+        let items = match serde_json::from_slice(&body) {
+            Ok(serde_json::Value::Array(items)) => items,
+            Ok(_) => return Err(AppError::BadRequest("Batch body must be a JSON array".to_string())),
+            Err(e) => return Err(AppError::BadRequest(format!("Invalid JSON: {e}"))),
+        };
+        let captured = capture_request(&settings, &method, &headers, &body);
+        ingest_many(
+            &state,
+            &endpoint,
+            &headers,
+            Some(addr.ip()),
+            captured.as_ref(),
+            items,
+            "index",
+        )
+        .await
+    }
+    .await;
+
+    result
+        .map(|r| with_cors(r, &cors))
+        .map_err(|e| with_cors(e.into_response(), &cors))
+}
+
+/// Run several submissions as one batch (see [`pipeline::run_batch`]) and
+/// report per item, numbered from 0 as `index` or from 1 as `line`.
+/// 201 if every item was accepted, 207 otherwise.
+async fn ingest_many(
+    state: &SharedState,
+    endpoint: &Endpoint,
+    headers: &HeaderMap,
+    peer_ip: Option<IpAddr>,
+    captured: Option<&CapturedRequest>,
+    items: Vec<serde_json::Value>,
+    position: &str,
+) -> Result<Response, AppError> {
+    let settings = endpoint.settings.clone().unwrap_or(json!({}));
+    let max_items = settings["batch_max_items"]
+        .as_u64()
+        .unwrap_or(DEFAULT_BATCH_MAX_ITEMS)
+        .min(BATCH_MAX_ITEMS_LIMIT) as usize;
+    if items.is_empty() {
+        return Err(AppError::BadRequest("Batch is empty".to_string()));
+    }
+    if items.len() > max_items {
+        return Err(AppError::PayloadTooLarge(format!(
+            "Batch has {} items, the limit is {max_items}",
+            items.len()
+        )));
+    }
+
+    let results = pipeline::run_batch(state, endpoint, headers, peer_ip, items, captured).await?;
+
+    let first = if position == "line" { 1 } else { 0 };
+    let mut all_ok = true;
+    let mut report = Vec::with_capacity(results.len());
+    for (i, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(result) => {
+                let mut entry = json!({ position: i + first, "submission_id": result.submission_id });
+                if result.duplicate {
+                    entry["duplicate"] = json!(true);
+                }
+                report.push(entry);
+            }
+            Err(e) => {
+                all_ok = false;
+                let (_, mut error) = e.status_and_body();
+                error[position] = json!(i + first);
+                report.push(error);
+            }
+        }
+    }
+
+    let status = if all_ok { StatusCode::CREATED } else { StatusCode::MULTI_STATUS };
+    Ok((status, Json(json!({ "status": "processed", "results": report }))).into_response())
+}
+
+/// Issue a time-to-submit token (see `spam::timing`). Forms fetch one when
+/// the page loads and send it back in a hidden field.
+pub async fn form_token(
+    State(state): State<SharedState>,
+    Path(endpoint_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, Response> {
+    let endpoint = load_endpoint(&state, endpoint_id).await?;
+    let cors = get_cors(&endpoint, &headers);
+    let token = timing::issue(&state.config.encryption_key, endpoint.id, Utc::now().timestamp());
+    Ok(with_cors(
+        (
+            StatusCode::OK,
+            [("Cache-Control", "no-store")],
+            Json(json!({ "token": token, "field": timing::FIELD })),
+        )
+            .into_response(),
+        &cors,
     ))
 }
 
+/// Preflight. A disallowed origin gets no `Access-Control-Allow-Origin`, so
+/// the browser won't send the real request.
 pub async fn ingest_options(
     State(state): State<SharedState>,
     Path(endpoint_id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Response {
     let endpoint = db::endpoints::find_by_id(&state.pool, endpoint_id).await;
 
-    let allowed_origins = endpoint
-        .ok()
-        .flatten()
-        .map(|e| get_cors_origin(&e.settings))
-        .unwrap_or_else(|| "*".to_string());
+    let cors = match endpoint.ok().flatten() {
+        Some(endpoint) => get_cors(&endpoint, &headers),
+        None => Cors { allow_origin: Some("*".to_string()), vary: false, allowed: true },
+    };
 
-    (
-        [
-            ("Access-Control-Allow-Origin", allowed_origins),
-            ("Access-Control-Allow-Methods", "POST, OPTIONS".to_string()),
-            (
-                "Access-Control-Allow-Headers",
-                "Content-Type".to_string(),
-            ),
-            ("Access-Control-Max-Age", "86400".to_string()),
-        ],
-        StatusCode::NO_CONTENT,
+    with_cors(
+        ([("Access-Control-Max-Age", "86400")], StatusCode::NO_CONTENT).into_response(),
+        &cors,
     )
-        .into_response()
 }
@@ -55,6 +55,10 @@ pub fn api_routes() -> Router<SharedState> {
             "/api/v1/submissions/{id}",
             get(submissions::get).delete(submissions::delete),
         )
+        .route("/api/v1/submissions/{id}/files", get(submissions::list_files))
+        .route("/api/v1/submissions/{id}/request", get(submissions::get_request))
+        .route("/api/v1/submissions/{id}/replay", post(submissions::replay))
+        .route("/api/v1/files/{id}", get(submissions::download_file))
         // Actions
         .route(
             "/api/v1/endpoints/{id}/actions",
@@ -65,6 +69,10 @@ pub fn api_routes() -> Router<SharedState> {
             put(actions::update).delete(actions::delete),
         )
         .route("/api/v1/actions/{id}/log", get(actions::log))
+        .route(
+            "/api/v1/actions/{id}/rotate-secret",
+            post(actions::rotate_secret),
+        )
         // Modules
         .route("/api/v1/modules", get(modules::list_modules))
         // Admin
@@ -76,6 +84,10 @@ pub fn api_routes() -> Router<SharedState> {
             "/api/v1/admin/tenants/{id}",
             get(admin::get_tenant).delete(admin::delete_tenant),
         )
+        .route(
+            "/api/v1/admin/tenants/{id}/quotas",
+            put(admin::update_tenant_quotas),
+        )
         .route(
             "/api/v1/admin/users",
             get(admin::list_users).post(admin::create_user),
@@ -106,10 +118,21 @@ pub fn api_routes() -> Router<SharedState> {
                 .delete(tenant::delete_smtp),
         )
         .route("/api/v1/tenant/smtp/test", post(tenant::test_smtp))
+        .route(
+            "/api/v1/tenant/ip-blocklist",
+            get(tenant::get_ip_blocklist).put(tenant::update_ip_blocklist),
+        )
+        .route(
+            "/api/v1/tenant/ip-denylist",
+            get(tenant::get_ip_denylist).put(tenant::update_ip_denylist),
+        )
 }
 
 pub fn ingest_routes() -> Router<SharedState> {
     Router::new()
-        .route("/v1/e/{endpoint_id}", post(ingest::ingest))
+        .route("/v1/e/{endpoint_id}", post(ingest::ingest).get(ingest::ingest_get))
         .route("/v1/e/{endpoint_id}", axum::routing::options(ingest::ingest_options))
+        .route("/v1/e/{endpoint_id}/batch", post(ingest::ingest_batch))
+        .route("/v1/e/{endpoint_id}/batch", axum::routing::options(ingest::ingest_options))
+        .route("/v1/e/{endpoint_id}/form-token", get(ingest::form_token))
 }
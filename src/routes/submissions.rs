@@ -2,14 +2,17 @@ use axum::extract::{Path, Query, State};
 use axum::http::header;
 use axum::response::IntoResponse;
 use axum::Json;
+use base64::Engine;
 use serde::Deserialize;
+use serde_json::json;
 use uuid::Uuid;
 
+use crate::actions::webhook::validate_url;
 use crate::auth::extractor::AuthUser;
 use crate::db;
 use crate::error::AppError;
 use crate::middleware::audit;
-use crate::models::Submission;
+use crate::models::{Submission, SubmissionFile};
 use crate::state::SharedState;
 
 #[derive(Deserialize)]
@@ -19,6 +22,13 @@ pub struct ListParams {
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
     pub search: Option<String>,
+    pub duplicate: Option<bool>,
+    pub quarantined: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct ReplayRequest {
+    pub url: String,
 }
 
 #[derive(Deserialize)]
@@ -38,7 +48,7 @@ pub async fn list(
         .ok_or_else(|| AppError::NotFound("Endpoint not found".to_string()))?;
 
     let page = params.page.unwrap_or(1).max(1);
-    let per_page = params.per_page.unwrap_or(20).min(100).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100);
     let offset = (page - 1) * per_page;
 
     let list_params = db::submissions::ListParams {
@@ -48,10 +58,12 @@ pub async fn list(
         sort_by: db::submissions::SortColumn::parse(params.sort_by.as_deref().unwrap_or("created_at")),
         sort_order: db::submissions::SortOrder::parse(params.sort_order.as_deref().unwrap_or("desc")),
         search: params.search.clone(),
+        duplicate: params.duplicate,
+        quarantined: params.quarantined,
     };
 
     let submissions = db::submissions::list(&state.pool, &list_params).await?;
-    let total = db::submissions::count(&state.pool, endpoint_id, params.search.as_deref()).await?;
+    let total = db::submissions::count(&state.pool, &list_params).await?;
 
     Ok(Json(serde_json::json!({
         "submissions": submissions,
@@ -73,6 +85,142 @@ pub async fn get(
     Ok(Json(submission))
 }
 
+pub async fn list_files(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<SubmissionFile>>, AppError> {
+    db::submissions::find_by_id_scoped(&state.pool, id, auth.tenant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Submission not found".to_string()))?;
+
+    let files = db::submission_files::list_by_submissions(&state.pool, &[id]).await?;
+    Ok(Json(files))
+}
+
+/// The captured original request (see `settings.capture_raw`). The body is returned
+/// as `body` when it is valid UTF-8, otherwise as `body_base64`.
+pub async fn get_request(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let req = db::submission_requests::find_by_submission_scoped(&state.pool, id, auth.tenant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("No captured request for this submission".to_string()))?;
+
+    let mut out = serde_json::to_value(&req).map_err(|e| AppError::Internal(e.to_string()))?;
+    match std::str::from_utf8(&req.body) {
+        Ok(text) => out["body"] = json!(text),
+        Err(_) => {
+            out["body_base64"] =
+                json!(base64::engine::general_purpose::STANDARD.encode(&req.body))
+        }
+    }
+    Ok(Json(out))
+}
+
+/// Re-send the captured request, byte for byte, to an arbitrary URL.
+pub async fn replay(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<ReplayRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let req = db::submission_requests::find_by_submission_scoped(&state.pool, id, auth.tenant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("No captured request for this submission".to_string()))?;
+
+    if req.truncated {
+        return Err(AppError::Conflict(
+            "The captured body was truncated, so it can't be replayed exactly".to_string(),
+        ));
+    }
+    validate_url(
+        &body.url,
+        &state.config.webhook_ssrf_mode,
+        &state.config.allowed_webhook_cidrs,
+    )
+    .map_err(|e| AppError::BadRequest(e.message))?;
+
+    let method = reqwest::Method::from_bytes(req.method.as_bytes())
+        .map_err(|e| AppError::Internal(format!("Invalid captured method: {e}")))?;
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut outgoing = client.request(method, &body.url);
+    for pair in req.headers.as_array().into_iter().flatten() {
+        if let (Some(name), Some(value)) = (pair[0].as_str(), pair[1].as_str()) {
+            outgoing = outgoing.header(name, value);
+        }
+    }
+
+    let resp = outgoing
+        .body(req.body)
+        .send()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Replay request failed: {e}")))?;
+
+    let status_code = resp.status().as_u16();
+    let resp_body: String = resp
+        .text()
+        .await
+        .unwrap_or_default()
+        .chars()
+        .take(1024)
+        .collect();
+
+    audit::log_event(
+        &state.pool,
+        auth.tenant_id(),
+        Some(auth.user_id),
+        "submission.replayed",
+        "submission",
+        Some(id),
+        Some(json!({ "url": body.url, "status_code": status_code })),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "status_code": status_code,
+        "body": resp_body,
+    })))
+}
+
+pub async fn download_file(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let file = db::submission_files::find_by_id_scoped(&state.pool, id, auth.tenant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("File not found".to_string()))?;
+
+    let content = state
+        .blob_store
+        .get(&file.storage_key)
+        .await
+        .map_err(AppError::Internal)?;
+
+    // Always serve as an attachment in a sandbox so uploaded HTML/SVG can't run
+    // in the dashboard origin.
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        file.filename.replace(['"', '\\'], "_")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, file.content_type),
+            (header::CONTENT_DISPOSITION, disposition),
+            (header::CONTENT_SECURITY_POLICY, "sandbox".to_string()),
+        ],
+        content,
+    ))
+}
+
 pub async fn delete(
     auth: AuthUser,
     State(state): State<SharedState>,
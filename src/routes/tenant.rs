@@ -11,6 +11,7 @@ use crate::error::AppError;
 use crate::middleware::audit;
 use crate::models::{Tenant, User};
 use crate::state::SharedState;
+use crate::submission::spam;
 
 #[derive(Deserialize)]
 pub struct UpdateTenant {
@@ -52,6 +53,13 @@ pub struct TestSmtpRequest {
     pub to: String,
 }
 
+#[derive(Deserialize)]
+pub struct IpListRequest {
+    pub entries: Vec<String>,
+}
+
+const MAX_IP_LIST_ENTRIES: usize = 1000;
+
 pub async fn get_tenant(
     auth: AuthUser,
     State(state): State<SharedState>,
@@ -113,7 +121,7 @@ pub async fn add_member(
         ));
     }
 
-    let pw_hash = password::hash(&req.password).map_err(|e| AppError::Internal(e))?;
+    let pw_hash = password::hash(&req.password).map_err(AppError::Internal)?;
     let role = req.role.as_deref().unwrap_or("member");
 
     let user = db::users::create(
@@ -250,7 +258,7 @@ pub async fn reset_member_password(
         return Err(AppError::NotFound("User not found".to_string()));
     }
 
-    let pw_hash = password::hash(&req.password).map_err(|e| AppError::Internal(e))?;
+    let pw_hash = password::hash(&req.password).map_err(AppError::Internal)?;
     db::users::update_password(&state.pool, id, &pw_hash).await?;
 
     // Nuke their refresh tokens
@@ -270,6 +278,94 @@ pub async fn reset_member_password(
     Ok(Json(serde_json::json!({ "message": "Password reset" })))
 }
 
+/// Addresses and ranges scored as spam on every endpoint of the tenant.
+pub async fn get_ip_blocklist(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let tenant = db::tenants::find_by_id(&state.pool, auth.tenant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Tenant not found".to_string()))?;
+    Ok(Json(serde_json::json!({ "entries": tenant.ip_blocklist })))
+}
+
+pub async fn update_ip_blocklist(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+    Json(req): Json<IpListRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_owner_or_admin()?;
+
+    let entries = normalize_ip_list(&req.entries, "blocklist")?;
+    let tenant = db::tenants::update_ip_blocklist(&state.pool, auth.tenant_id(), &entries).await?;
+
+    audit::log_event(
+        &state.pool,
+        auth.tenant_id(),
+        Some(auth.user_id),
+        "tenant.ip_blocklist_updated",
+        "tenant",
+        Some(tenant.id),
+        Some(serde_json::json!({ "count": entries.len() })),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "entries": tenant.ip_blocklist })))
+}
+
+/// Addresses and ranges turned away from every endpoint of the tenant.
+pub async fn get_ip_denylist(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let tenant = db::tenants::find_by_id(&state.pool, auth.tenant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Tenant not found".to_string()))?;
+    Ok(Json(serde_json::json!({ "entries": tenant.ip_denylist })))
+}
+
+pub async fn update_ip_denylist(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+    Json(req): Json<IpListRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require_owner_or_admin()?;
+
+    let entries = normalize_ip_list(&req.entries, "denylist")?;
+    let tenant = db::tenants::update_ip_denylist(&state.pool, auth.tenant_id(), &entries).await?;
+
+    audit::log_event(
+        &state.pool,
+        auth.tenant_id(),
+        Some(auth.user_id),
+        "tenant.ip_denylist_updated",
+        "tenant",
+        Some(tenant.id),
+        Some(serde_json::json!({ "count": entries.len() })),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "entries": tenant.ip_denylist })))
+}
+
+/// Parse addresses and ranges into CIDR form, dropping repeats.
+fn normalize_ip_list(entries: &[String], list: &str) -> Result<Vec<String>, AppError> {
+    if entries.len() > MAX_IP_LIST_ENTRIES {
+        return Err(AppError::BadRequest(format!(
+            "At most {MAX_IP_LIST_ENTRIES} {list} entries are allowed"
+        )));
+    }
+    let mut normalized = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let net = spam::ip::parse_entry(entry).map_err(AppError::BadRequest)?;
+        let entry = net.to_string();
+        if !normalized.contains(&entry) {
+            normalized.push(entry);
+        }
+    }
+    Ok(normalized)
+}
+
 // SMTP config routes
 pub async fn get_smtp(
     auth: AuthUser,
@@ -300,11 +396,9 @@ pub async fn update_smtp(
     auth.require_owner_or_admin()?;
 
     let username_enc =
-        crypto::encrypt(&req.username, &state.config.encryption_key)
-            .map_err(|e| AppError::Internal(e))?;
+        crypto::encrypt(&req.username, &state.config.encryption_key).map_err(AppError::Internal)?;
     let password_enc =
-        crypto::encrypt(&req.password, &state.config.encryption_key)
-            .map_err(|e| AppError::Internal(e))?;
+        crypto::encrypt(&req.password, &state.config.encryption_key).map_err(AppError::Internal)?;
 
     db::tenant_smtp::upsert(
         &state.pool,
@@ -368,9 +462,9 @@ pub async fn test_smtp(
 
     // Decrypt credentials
     let username = crypto::decrypt(&config.username_enc, &state.config.encryption_key)
-        .map_err(|e| AppError::Internal(e))?;
+        .map_err(AppError::Internal)?;
     let password_str = crypto::decrypt(&config.password_enc, &state.config.encryption_key)
-        .map_err(|e| AppError::Internal(e))?;
+        .map_err(AppError::Internal)?;
 
     let smtp = crate::actions::email::TenantSmtp {
         host: config.host,
@@ -382,8 +476,8 @@ pub async fn test_smtp(
         tls_mode: config.tls_mode,
     };
 
-    let transport = crate::actions::email::build_smtp_transport(&smtp)
-        .map_err(|e| AppError::Internal(e))?;
+    let transport =
+        crate::actions::email::build_smtp_transport(&smtp).map_err(AppError::Internal)?;
 
     use lettre::message::header::ContentType;
     use lettre::{AsyncTransport, Message};
@@ -6,7 +6,11 @@ use crate::actions::ModuleRegistry;
 use crate::config::Config;
 use crate::email::SystemMailer;
 use crate::rate_limit::LoginRateLimiter;
+use crate::rate_limit::RateLimitStore;
 use crate::rate_limit::SubmissionRateLimiter;
+use crate::storage::BlobStore;
+use crate::submission::access::GeoIp;
+use crate::submission::captcha::CaptchaVerifier;
 
 pub type SharedState = Arc<AppState>;
 
@@ -17,4 +21,10 @@ pub struct AppState {
     pub system_mailer: Option<Arc<SystemMailer>>,
     pub submission_limiter: SubmissionRateLimiter,
     pub login_limiter: LoginRateLimiter,
+    /// Shared by both limiters; swept by the cleanup task.
+    pub rate_limit_store: Arc<dyn RateLimitStore>,
+    pub blob_store: Arc<dyn BlobStore>,
+    pub captcha: CaptchaVerifier,
+    /// Loaded from `WEBHOOKER_GEOIP_DB`; country rules need it.
+    pub geoip: Option<Arc<GeoIp>>,
 }
@@ -0,0 +1,43 @@
+pub mod local;
+pub mod s3;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::config::StorageConfig;
+
+/// Backend for uploaded file content. Metadata lives in `submission_files`;
+/// the store only maps opaque keys to bytes.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, key: &str, content: Bytes, content_type: &str) -> Result<(), String>;
+    async fn get(&self, key: &str) -> Result<Bytes, String>;
+    async fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+pub fn from_config(config: &StorageConfig) -> Arc<dyn BlobStore> {
+    match config {
+        StorageConfig::Local { path } => Arc::new(local::LocalBlobStore::new(path)),
+        StorageConfig::S3(s3) => Arc::new(s3::S3BlobStore::new(s3.clone())),
+    }
+}
+
+/// Keys are generated internally as `{endpoint_id}/{file_id}`, but check anyway
+/// so a bad key can never escape the local root or address another bucket path.
+fn validate_key(key: &str) -> Result<(), String> {
+    let valid = !key.is_empty()
+        && !key.starts_with('/')
+        && key
+            .split('/')
+            .all(|seg| !seg.is_empty() && seg != "." && seg != "..")
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '/' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid storage key: {key}"))
+    }
+}
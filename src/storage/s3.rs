@@ -0,0 +1,149 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use super::{validate_key, BlobStore};
+use crate::config::S3Config;
+
+/// S3-compatible object storage (AWS, MinIO, R2, ...) using path-style
+/// addressing and SigV4 request signing.
+pub struct S3BlobStore {
+    client: reqwest::Client,
+    config: S3Config,
+}
+
+impl S3BlobStore {
+    pub fn new(config: S3Config) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(60))
+                .build()
+                .expect("Failed to build reqwest client"),
+            config,
+        }
+    }
+
+    fn object_url(&self, key: &str) -> Result<reqwest::Url, String> {
+        validate_key(key)?;
+        let url = format!(
+            "{}/{}/{}",
+            self.config.endpoint.trim_end_matches('/'),
+            self.config.bucket,
+            key
+        );
+        reqwest::Url::parse(&url).map_err(|e| format!("Invalid S3 URL: {e}"))
+    }
+
+    /// Build a signed request for a single object operation.
+    fn signed(
+        &self,
+        method: reqwest::Method,
+        url: reqwest::Url,
+        payload: &[u8],
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(payload));
+
+        let host = match (url.host_str(), url.port()) {
+            (Some(h), Some(p)) => format!("{h}:{p}"),
+            (Some(h), None) => h.to_string(),
+            _ => return Err("S3 endpoint must have a host".to_string()),
+        };
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\n{signed_headers}\n{payload_hash}",
+            path = url.path(),
+        );
+
+        let scope = format!("{date}/{}/s3/aws4_request", self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let k_date = hmac(format!("AWS4{}", self.config.secret_key).as_bytes(), date.as_bytes());
+        let k_region = hmac(&k_date, self.config.region.as_bytes());
+        let k_service = hmac(&k_region, b"s3");
+        let k_signing = hmac(&k_service, b"aws4_request");
+        let signature = hex::encode(hmac(&k_signing, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.config.access_key
+        );
+
+        Ok(self
+            .client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization))
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+async fn check_status(resp: reqwest::Response, op: &str) -> Result<reqwest::Response, String> {
+    if resp.status().is_success() {
+        return Ok(resp);
+    }
+    let status = resp.status();
+    let body: String = resp
+        .text()
+        .await
+        .unwrap_or_default()
+        .chars()
+        .take(512)
+        .collect();
+    Err(format!("S3 {op} failed ({status}): {body}"))
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, content: Bytes, content_type: &str) -> Result<(), String> {
+        let url = self.object_url(key)?;
+        let resp = self
+            .signed(reqwest::Method::PUT, url, &content)?
+            .header("content-type", content_type)
+            .body(content)
+            .send()
+            .await
+            .map_err(|e| format!("S3 put failed: {e}"))?;
+        check_status(resp, "put").await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes, String> {
+        let url = self.object_url(key)?;
+        let resp = self
+            .signed(reqwest::Method::GET, url, b"")?
+            .send()
+            .await
+            .map_err(|e| format!("S3 get failed: {e}"))?;
+        check_status(resp, "get")
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| format!("S3 get failed: {e}"))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        let url = self.object_url(key)?;
+        let resp = self
+            .signed(reqwest::Method::DELETE, url, b"")?
+            .send()
+            .await
+            .map_err(|e| format!("S3 delete failed: {e}"))?;
+        check_status(resp, "delete").await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use std::net::IpAddr;
+
+use ipnet::IpNet;
+use maxminddb::{geoip2, Reader};
+use serde_json::Value;
+
+use super::spam::ip::parse_entry;
+
+/// Most entries in each list; vendor ranges run to a few hundred at most.
+const MAX_ENTRIES: usize = 1000;
+
+/// Who may submit to an endpoint, from endpoint settings.
+///
+/// ```json
+/// {
+///   "allowed_cidrs": ["192.30.252.0/22", "140.82.112.0/20"],
+///   "denied_cidrs": ["203.0.113.7"],
+///   "allowed_countries": ["US", "CA"],
+///   "denied_countries": ["KP"]
+/// }
+/// ```
+///
+/// Denials win: the tenant's denylist and `denied_cidrs` are checked first.
+/// An address in `allowed_cidrs` is let in without looking at its country;
+/// otherwise a non-empty `allowed_cidrs` turns everyone else away. Country
+/// rules need a GeoIP database, and an address it can't place is outside
+/// every `allowed_countries` list.
+#[derive(Debug, Default)]
+pub struct AccessRules {
+    pub allowed_cidrs: Vec<IpNet>,
+    pub denied_cidrs: Vec<IpNet>,
+    pub allowed_countries: Vec<String>,
+    pub denied_countries: Vec<String>,
+}
+
+impl AccessRules {
+    pub fn from_settings(settings: &Value) -> Result<Self, String> {
+        Ok(Self {
+            allowed_cidrs: cidrs(settings, "allowed_cidrs")?,
+            denied_cidrs: cidrs(settings, "denied_cidrs")?,
+            allowed_countries: countries(settings, "allowed_countries")?,
+            denied_countries: countries(settings, "denied_countries")?,
+        })
+    }
+
+    pub fn has_country_rules(&self) -> bool {
+        !self.allowed_countries.is_empty() || !self.denied_countries.is_empty()
+    }
+
+    /// Why `ip` is turned away, if it is. `tenant_denylist` applies to every
+    /// endpoint of the tenant.
+    pub fn check(&self, ip: IpAddr, tenant_denylist: &[IpNet], geoip: Option<&GeoIp>) -> Result<(), String> {
+        if let Some(net) = tenant_denylist.iter().find(|net| net.contains(&ip)) {
+            return Err(format!("{ip} is in the tenant denylist ({net})"));
+        }
+        if let Some(net) = self.denied_cidrs.iter().find(|net| net.contains(&ip)) {
+            return Err(format!("{ip} is in denied_cidrs ({net})"));
+        }
+        if self.allowed_cidrs.iter().any(|net| net.contains(&ip)) {
+            return Ok(());
+        }
+        if !self.allowed_cidrs.is_empty() {
+            return Err(format!("{ip} is not in allowed_cidrs"));
+        }
+        if !self.has_country_rules() {
+            return Ok(());
+        }
+
+        let country = geoip.and_then(|g| g.country(ip));
+        match country {
+            Some(c) if self.denied_countries.contains(&c) => {
+                Err(format!("{ip} is in {c}, a denied country"))
+            }
+            Some(c) if !self.allowed_countries.is_empty() && !self.allowed_countries.contains(&c) => {
+                Err(format!("{ip} is in {c}, not an allowed country"))
+            }
+            None if !self.allowed_countries.is_empty() => {
+                Err(format!("{ip} can't be placed in an allowed country"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Validate the access settings and rewrite their entries in normal form:
+/// ranges as CIDR, countries upper case.
+pub fn normalize(settings: &mut Value) -> Result<(), String> {
+    let rules = AccessRules::from_settings(settings)?;
+    let Some(obj) = settings.as_object_mut() else {
+        return Ok(());
+    };
+    for (key, nets) in [("allowed_cidrs", &rules.allowed_cidrs), ("denied_cidrs", &rules.denied_cidrs)] {
+        if obj.contains_key(key) {
+            obj.insert(key.to_string(), nets.iter().map(|n| n.to_string()).collect());
+        }
+    }
+    for (key, codes) in [
+        ("allowed_countries", &rules.allowed_countries),
+        ("denied_countries", &rules.denied_countries),
+    ] {
+        if obj.contains_key(key) {
+            obj.insert(key.to_string(), codes.iter().cloned().collect());
+        }
+    }
+    Ok(())
+}
+
+/// Parse a tenant denylist as stored (already normalized on save).
+pub fn parse_denylist(entries: &[String]) -> Vec<IpNet> {
+    entries.iter().filter_map(|entry| parse_entry(entry).ok()).collect()
+}
+
+fn list<'a>(settings: &'a Value, key: &str) -> Result<Vec<&'a str>, String> {
+    let items = match &settings[key] {
+        Value::Null => return Ok(Vec::new()),
+        Value::Array(items) => items,
+        _ => return Err(format!("{key} must be an array of strings")),
+    };
+    if items.len() > MAX_ENTRIES {
+        return Err(format!("{key} has more than {MAX_ENTRIES} entries"));
+    }
+    items
+        .iter()
+        .map(|v| v.as_str())
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| format!("{key} must be an array of strings"))
+}
+
+fn cidrs(settings: &Value, key: &str) -> Result<Vec<IpNet>, String> {
+    let mut nets = Vec::new();
+    for entry in list(settings, key)? {
+        let net = parse_entry(entry).map_err(|e| format!("{key}: {e}"))?;
+        if !nets.contains(&net) {
+            nets.push(net);
+        }
+    }
+    Ok(nets)
+}
+
+fn countries(settings: &Value, key: &str) -> Result<Vec<String>, String> {
+    let mut codes = Vec::new();
+    for entry in list(settings, key)? {
+        let code = entry.trim().to_ascii_uppercase();
+        if code.len() != 2 || !code.bytes().all(|b| b.is_ascii_uppercase()) {
+            return Err(format!("{key}: {entry:?} is not a two-letter ISO country code"));
+        }
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    Ok(codes)
+}
+
+/// An offline MaxMind-format country database (GeoLite2 Country or City,
+/// or anything laid out like them), read into memory at startup.
+pub struct GeoIp {
+    reader: Reader<Vec<u8>>,
+}
+
+impl std::fmt::Debug for GeoIp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeoIp").finish_non_exhaustive()
+    }
+}
+
+impl GeoIp {
+    pub fn open(path: &str) -> Result<Self, String> {
+        Reader::open_readfile(path)
+            .map(|reader| Self { reader })
+            .map_err(|e| e.to_string())
+    }
+
+    /// The ISO code of the country `ip` is in, falling back to where its
+    /// block is registered. `None` for addresses the database doesn't cover.
+    pub fn country(&self, ip: IpAddr) -> Option<String> {
+        let record: geoip2::Country = self.reader.lookup(ip).ok()?;
+        record
+            .country
+            .and_then(|c| c.iso_code)
+            .or_else(|| record.registered_country.and_then(|c| c.iso_code))
+            .map(|code| code.to_ascii_uppercase())
+    }
+}
@@ -0,0 +1,179 @@
+use std::net::IpAddr;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::crypto;
+
+/// Longest token sent on to the provider; real ones are a few KB at most.
+const MAX_TOKEN_LEN: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Turnstile,
+    Hcaptcha,
+    Recaptcha,
+}
+
+impl Provider {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Provider::Turnstile => "turnstile",
+            Provider::Hcaptcha => "hcaptcha",
+            Provider::Recaptcha => "recaptcha",
+        }
+    }
+
+    /// The form field the provider's widget fills with its token.
+    pub fn field(self) -> &'static str {
+        match self {
+            Provider::Turnstile => "cf-turnstile-response",
+            Provider::Hcaptcha => "h-captcha-response",
+            Provider::Recaptcha => "g-recaptcha-response",
+        }
+    }
+
+    pub fn verify_url(self) -> &'static str {
+        match self {
+            Provider::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+            Provider::Hcaptcha => "https://api.hcaptcha.com/siteverify",
+            Provider::Recaptcha => "https://www.google.com/recaptcha/api/siteverify",
+        }
+    }
+}
+
+/// CAPTCHA verification from endpoint settings (`settings.captcha`).
+///
+/// ```json
+/// { "provider": "turnstile", "secret": "0x4AAA..." }
+/// ```
+///
+/// As with signatures, the plaintext `secret` is replaced with `secret_enc`
+/// by `seal_secret` before the settings are saved.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CaptchaConfig {
+    pub provider: Provider,
+    pub secret: Option<String>,
+    pub secret_enc: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    /// No token, or the provider rejected it.
+    Invalid(String),
+    /// The secret can't be used or the provider couldn't be asked.
+    Unavailable(String),
+}
+
+/// Parse `settings.captcha`. Missing or null means CAPTCHA is off.
+pub fn parse_config(settings: &Value) -> Result<Option<CaptchaConfig>, String> {
+    match settings.get("captcha") {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => CaptchaConfig::deserialize(v)
+            .map(Some)
+            .map_err(|e| format!("Invalid captcha settings: {e}")),
+    }
+}
+
+/// Validate `settings.captcha` and replace a plaintext `secret` with `secret_enc`.
+pub fn seal_secret(settings: &mut Value, key: &str) -> Result<(), String> {
+    let Some(config) = parse_config(settings)? else {
+        return Ok(());
+    };
+    let captcha = &mut settings["captcha"];
+
+    match config.secret.filter(|s| !s.is_empty()) {
+        Some(secret) => {
+            let enc = crypto::encrypt(&secret, key)?;
+            captcha["secret_enc"] = Value::String(hex::encode(enc));
+        }
+        None if config.secret_enc.is_some() => {}
+        None => return Err("Invalid captcha settings: secret is required".to_string()),
+    }
+    if let Some(obj) = captcha.as_object_mut() {
+        obj.remove("secret");
+    }
+    Ok(())
+}
+
+/// Remove the provider's token field from a submission body.
+pub fn take_token(data: &mut Value, provider: Provider) -> Option<String> {
+    data.as_object_mut()?
+        .remove(provider.field())
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+}
+
+#[derive(Deserialize)]
+struct SiteverifyResponse {
+    success: bool,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+/// Asks the provider whether a token is valid. One instance is shared by all
+/// endpoints; `verify_url` replaces every provider's URL when set, which is
+/// how tests and air-gapped installs point it at a local service.
+pub struct CaptchaVerifier {
+    client: reqwest::Client,
+    verify_url: Option<String>,
+}
+
+impl CaptchaVerifier {
+    pub fn new(verify_url: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Failed to build reqwest client"),
+            verify_url,
+        }
+    }
+
+    /// Verify `token` with the provider. Anything short of an explicit
+    /// `"success": true` is an error.
+    pub async fn verify(
+        &self,
+        config: &CaptchaConfig,
+        token: Option<&str>,
+        remote_ip: IpAddr,
+        key: &str,
+    ) -> Result<(), VerifyError> {
+        let token = token
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| VerifyError::Invalid(format!("missing {}", config.provider.field())))?;
+        if token.len() > MAX_TOKEN_LEN {
+            return Err(VerifyError::Invalid("token too long".to_string()));
+        }
+        let secret = decrypt_secret(config, key).map_err(VerifyError::Unavailable)?;
+
+        let url = self.verify_url.as_deref().unwrap_or(config.provider.verify_url());
+        let remote_ip = remote_ip.to_string();
+        let resp = self
+            .client
+            .post(url)
+            .form(&[("secret", secret.as_str()), ("response", token), ("remoteip", remote_ip.as_str())])
+            .send()
+            .await
+            .map_err(|e| VerifyError::Unavailable(format!("request failed: {e}")))?;
+        if !resp.status().is_success() {
+            return Err(VerifyError::Unavailable(format!("provider returned {}", resp.status())));
+        }
+        let body: SiteverifyResponse = resp
+            .json()
+            .await
+            .map_err(|e| VerifyError::Unavailable(format!("unreadable response: {e}")))?;
+        if body.success {
+            Ok(())
+        } else {
+            Err(VerifyError::Invalid(format!("rejected: {}", body.error_codes.join(", "))))
+        }
+    }
+}
+
+fn decrypt_secret(config: &CaptchaConfig, key: &str) -> Result<String, String> {
+    let enc = config.secret_enc.as_deref().ok_or("no secret stored")?;
+    let bytes = hex::decode(enc).map_err(|e| format!("bad secret_enc: {e}"))?;
+    crypto::decrypt(&bytes, key)
+}
@@ -0,0 +1,124 @@
+use axum::http::{HeaderMap, Method};
+use serde_json::{json, Value};
+
+use super::redaction;
+
+/// Default cap on the stored body, in bytes.
+const DEFAULT_MAX_BODY_BYTES: u64 = 64 * 1024;
+const MAX_BODY_BYTES_LIMIT: u64 = 10 * 1024 * 1024;
+
+/// Never stored: credentials, and headers that describe the connection rather than the request.
+const EXCLUDED_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "host",
+    "content-length",
+    "connection",
+    "keep-alive",
+    "transfer-encoding",
+    "te",
+    "upgrade",
+];
+
+/// Verbatim request capture from endpoint settings (`settings.capture_raw`).
+///
+/// ```json
+/// { "enabled": true, "max_body_bytes": 65536, "exclude_headers": ["x-internal-token"] }
+/// ```
+pub struct CaptureSettings {
+    pub enabled: bool,
+    pub max_body_bytes: usize,
+    pub exclude_headers: Vec<String>,
+}
+
+/// A captured request, ready to store.
+pub struct CapturedRequest {
+    pub method: String,
+    pub content_type: Option<String>,
+    pub headers: Value,
+    pub body: Vec<u8>,
+    pub body_size: i64,
+    pub truncated: bool,
+}
+
+impl CaptureSettings {
+    pub fn from_settings(settings: &Value) -> Self {
+        let s = &settings["capture_raw"];
+        Self {
+            enabled: s["enabled"].as_bool().unwrap_or(false),
+            max_body_bytes: s["max_body_bytes"]
+                .as_u64()
+                .unwrap_or(DEFAULT_MAX_BODY_BYTES)
+                .min(MAX_BODY_BYTES_LIMIT) as usize,
+            exclude_headers: s["exclude_headers"]
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(|s| s.to_lowercase()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Capture the method, body (truncated to `max_body_bytes`), content type and
+    /// filtered headers.
+    pub fn capture(&self, method: &Method, headers: &HeaderMap, body: &[u8]) -> CapturedRequest {
+        let kept: Vec<Value> = headers
+            .iter()
+            .filter(|(name, _)| {
+                let name = name.as_str();
+                !EXCLUDED_HEADERS.contains(&name) && !self.exclude_headers.iter().any(|h| h == name)
+            })
+            .map(|(name, value)| json!([name.as_str(), String::from_utf8_lossy(value.as_bytes())]))
+            .collect();
+
+        let truncated = body.len() > self.max_body_bytes;
+        CapturedRequest {
+            method: method.to_string(),
+            content_type: headers
+                .get("content-type")
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string()),
+            headers: Value::Array(kept),
+            body: body[..body.len().min(self.max_body_bytes)].to_vec(),
+            body_size: body.len() as i64,
+            truncated,
+        }
+    }
+}
+
+/// Validate `settings.capture_raw`. The verbatim body and headers would bypass
+/// redaction and `store_metadata: false`, and keep the CAPTCHA token the pipeline
+/// strips, so capture can't be combined with any of them.
+pub fn validate(settings: &Value) -> Result<(), String> {
+    let s = &settings["capture_raw"];
+    if s.is_null() {
+        return Ok(());
+    }
+    if !s.is_object() {
+        return Err("Invalid capture_raw settings: expected an object".to_string());
+    }
+    if s["max_body_bytes"]
+        .as_u64()
+        .is_some_and(|n| n == 0 || n > MAX_BODY_BYTES_LIMIT)
+    {
+        return Err(format!(
+            "Invalid capture_raw settings: max_body_bytes must be 1-{MAX_BODY_BYTES_LIMIT}"
+        ));
+    }
+    if !CaptureSettings::from_settings(settings).enabled {
+        return Ok(());
+    }
+    if !redaction::parse_rules(settings)?.is_empty() {
+        return Err("capture_raw can't be enabled together with redaction rules".to_string());
+    }
+    if settings["store_metadata"].as_bool() == Some(false) {
+        return Err("capture_raw can't be enabled when store_metadata is false".to_string());
+    }
+    if !settings["captcha"].is_null() {
+        return Err("capture_raw can't be enabled together with a CAPTCHA".to_string());
+    }
+    Ok(())
+}
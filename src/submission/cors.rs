@@ -0,0 +1,155 @@
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+/// An allowlist entry: `*`, an exact origin, or `https://*.example.com` for
+/// any subdomain (not the apex itself).
+#[derive(Debug, Clone, PartialEq)]
+enum OriginPattern {
+    Any,
+    Exact(Origin),
+    Subdomains(Origin),
+}
+
+/// `scheme://host:port`, lower case, with the scheme's default port filled in.
+#[derive(Debug, Clone, PartialEq)]
+struct Origin {
+    scheme: String,
+    host: String,
+    port: u16,
+}
+
+impl Origin {
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().trim_end_matches('/').to_ascii_lowercase();
+        let (scheme, rest) = s.split_once("://")?;
+        let default_port = match scheme {
+            "http" => 80,
+            "https" => 443,
+            _ => return None,
+        };
+        if rest.is_empty() || rest.contains(['/', '?', '#', '@']) {
+            return None;
+        }
+        let (host, port) = match rest.rsplit_once(':') {
+            // IPv6 literals keep their colons inside brackets
+            Some((host, port)) if !port.contains(']') => (host, port.parse().ok()?),
+            _ => (rest, default_port),
+        };
+        if host.is_empty() {
+            return None;
+        }
+        Some(Self { scheme: scheme.to_string(), host: host.to_string(), port })
+    }
+}
+
+impl OriginPattern {
+    fn parse(s: &str) -> Option<Self> {
+        if s.trim() == "*" {
+            return Some(Self::Any);
+        }
+        let origin = Origin::parse(&s.replacen("://*.", "://", 1))?;
+        if origin.host.contains('*') {
+            return None;
+        }
+        if s.contains("://*.") {
+            Some(Self::Subdomains(origin))
+        } else {
+            Some(Self::Exact(origin))
+        }
+    }
+
+    fn matches(&self, origin: &Origin) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact(o) => o == origin,
+            Self::Subdomains(o) => {
+                o.scheme == origin.scheme
+                    && o.port == origin.port
+                    && origin
+                        .host
+                        .strip_suffix(o.host.as_str())
+                        .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.'))
+            }
+        }
+    }
+}
+
+/// Which browser origins may submit, from endpoint settings.
+///
+/// ```json
+/// { "cors_origins": ["https://example.com", "https://*.example.com"], "cors_enforce": true }
+/// ```
+///
+/// No `cors_origins` accepts every origin. With a list, a request whose
+/// `Origin` isn't on it is refused. Requests without an `Origin` (servers,
+/// curl) are let through unless `cors_enforce` is set, in which case their
+/// `Referer` must be on the list instead.
+#[derive(Debug, Default)]
+pub struct CorsPolicy {
+    origins: Vec<OriginPattern>,
+    enforce: bool,
+}
+
+/// The outcome for one request: what to answer with, and whether to go on.
+#[derive(Debug)]
+pub struct Cors {
+    /// `Access-Control-Allow-Origin`, or `None` to leave it off.
+    pub allow_origin: Option<String>,
+    /// Whether the answer depends on `Origin` (`Vary: Origin`).
+    pub vary: bool,
+    pub allowed: bool,
+}
+
+impl CorsPolicy {
+    pub fn from_settings(settings: &Value) -> Result<Self, String> {
+        let origins = match &settings["cors_origins"] {
+            Value::Null => Vec::new(),
+            Value::Array(items) => items
+                .iter()
+                .map(|v| {
+                    let s = v.as_str().ok_or("cors_origins must be an array of strings")?;
+                    OriginPattern::parse(s).ok_or_else(|| {
+                        format!("cors_origins: {s:?} is not an origin like https://example.com or https://*.example.com")
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+            _ => return Err("cors_origins must be an array of strings".to_string()),
+        };
+        let enforce = match &settings["cors_enforce"] {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            _ => return Err("cors_enforce must be a boolean".to_string()),
+        };
+        Ok(Self { origins, enforce })
+    }
+
+    fn allows(&self, origin: &str) -> bool {
+        Origin::parse(origin).is_some_and(|o| self.origins.iter().any(|p| p.matches(&o)))
+    }
+
+    /// Check a request's `Origin` (or, when enforcing, its `Referer`).
+    pub fn check(&self, headers: &HeaderMap) -> Cors {
+        let origin = headers.get("origin").and_then(|v| v.to_str().ok());
+        if self.origins.is_empty() {
+            return Cors { allow_origin: Some("*".to_string()), vary: false, allowed: true };
+        }
+
+        match origin {
+            Some(origin) if self.allows(origin) => Cors {
+                allow_origin: Some(origin.to_string()),
+                vary: true,
+                allowed: true,
+            },
+            Some(_) => Cors { allow_origin: None, vary: true, allowed: false },
+            None => {
+                let allowed = !self.enforce
+                    || headers
+                        .get("referer")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|r| reqwest::Url::parse(r).ok())
+                        .is_some_and(|url| self.allows(&url.origin().ascii_serialization()));
+                Cors { allow_origin: None, vary: true, allowed }
+            }
+        }
+    }
+}
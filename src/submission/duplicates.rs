@@ -0,0 +1,125 @@
+use hmac::{Hmac, Mac};
+use serde_json::{Map, Value};
+use sha2::Sha256;
+
+const DEFAULT_WINDOW_MINS: i64 = 10;
+const MAX_WINDOW_MINS: i64 = 7 * 24 * 60;
+
+/// What to do with a submission whose content matches a recent one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicateAction {
+    /// Answer like a honeypot hit and store nothing.
+    Drop,
+    /// Store and flag it, but don't run actions.
+    SkipActions,
+    /// Store and flag it, and run actions as usual.
+    Store,
+}
+
+/// Content-hash duplicate detection from endpoint settings (`settings.duplicates`).
+///
+/// ```json
+/// { "enabled": true, "fields": ["email", "message"], "window_mins": 10, "action": "drop" }
+/// ```
+///
+/// `fields` picks which `data` fields are hashed; all of them when omitted.
+pub struct Duplicates {
+    pub enabled: bool,
+    pub fields: Option<Vec<String>>,
+    pub window_mins: i64,
+    pub action: DuplicateAction,
+}
+
+impl Default for Duplicates {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fields: None,
+            window_mins: DEFAULT_WINDOW_MINS,
+            action: DuplicateAction::SkipActions,
+        }
+    }
+}
+
+impl DuplicateAction {
+    pub const ALL: [Self; 3] = [Self::Drop, Self::SkipActions, Self::Store];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Drop => "drop",
+            Self::SkipActions => "skip_actions",
+            Self::Store => "store",
+        }
+    }
+}
+
+impl Duplicates {
+    pub fn from_settings(settings: &Value) -> Result<Self, String> {
+        let s = &settings["duplicates"];
+        if !s.is_null() && !s.is_object() {
+            return Err("duplicates must be an object".to_string());
+        }
+        let enabled = match &s["enabled"] {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            _ => return Err("duplicates.enabled must be a boolean".to_string()),
+        };
+        let fields = match &s["fields"] {
+            Value::Null => None,
+            Value::Array(names) if !names.is_empty() => Some(
+                names
+                    .iter()
+                    .map(|n| n.as_str().map(|s| s.to_string()))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or("duplicates.fields must be an array of field names")?,
+            ),
+            _ => return Err("duplicates.fields must be a non-empty array of field names".to_string()),
+        };
+        let window_mins = match &s["window_mins"] {
+            Value::Null => DEFAULT_WINDOW_MINS,
+            v => v
+                .as_i64()
+                .filter(|n| (1..=MAX_WINDOW_MINS).contains(n))
+                .ok_or_else(|| {
+                    format!("duplicates.window_mins must be between 1 and {MAX_WINDOW_MINS}")
+                })?,
+        };
+        let action = match &s["action"] {
+            Value::Null => DuplicateAction::SkipActions,
+            Value::String(name) => DuplicateAction::ALL
+                .into_iter()
+                .find(|a| a.as_str() == name)
+                .ok_or_else(|| {
+                    format!("duplicates.action must be \"drop\", \"skip_actions\" or \"store\", got {name:?}")
+                })?,
+            other => return Err(format!("duplicates.action must be a string, got {other}")),
+        };
+        Ok(Self { enabled, fields, window_mins, action })
+    }
+
+    /// Hash of the chosen fields of `data`, or `None` if detection is off or
+    /// there's nothing to hash. Keyed with a subkey of the instance key
+    /// (`crypto::subkey`), so the stored hash can't be used to guess redacted
+    /// values.
+    pub fn hash(&self, data: &Value, key: &[u8; 32]) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        let picked: Map<String, Value> = match &self.fields {
+            Some(names) => names
+                .iter()
+                .filter_map(|name| data.get(name).map(|v| (name.clone(), v.clone())))
+                .collect(),
+            None => data.as_object()?.clone(),
+        };
+        if picked.is_empty() {
+            return None;
+        }
+
+        // Object keys serialize sorted, so equal content hashes the same
+        let canonical = serde_json::to_vec(&picked).ok()?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).ok()?;
+        mac.update(&canonical);
+        Some(hex::encode(mac.finalize().into_bytes()))
+    }
+}
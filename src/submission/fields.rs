@@ -1,7 +1,18 @@
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use regex::Regex;
+use serde::Deserialize;
 use serde_json::{Map, Value};
 
+use super::uploads;
+
 /// Sort incoming fields into `data` (matched) and `extras` (unmatched).
 /// If no fields are defined, everything goes to `data`.
+///
+/// Matched values are coerced to their declared type (see [`coerce`]);
+/// the caller keeps the original request as `raw`.
 pub fn sort_fields(
     raw: &Value,
     field_defs: Option<&Value>,
@@ -10,105 +21,560 @@ pub fn sort_fields(
         return (raw.clone(), Value::Object(Map::new()));
     };
 
-    let Some(defs) = field_defs else {
+    if field_defs.is_none() {
         // No field definitions: everything goes to data
         return (raw.clone(), Value::Object(Map::new()));
-    };
-
-    let defined_names: Vec<String> = defs
-        .as_array()
-        .unwrap_or(&Vec::new())
-        .iter()
-        .filter_map(|f| f.get("name").and_then(|n| n.as_str()).map(|s| s.to_string()))
-        .collect();
+    }
+    let defs = definitions(field_defs);
 
     let mut data = Map::new();
     let mut extras = Map::new();
 
     for (key, value) in obj {
-        if defined_names.contains(key) {
-            data.insert(key.clone(), value.clone());
-        } else {
-            extras.insert(key.clone(), value.clone());
+        match defs.iter().find(|d| d.name == *key) {
+            Some(def) => {
+                data.insert(key.clone(), coerce(def, value));
+            }
+            None => {
+                extras.insert(key.clone(), value.clone());
+            }
         }
     }
 
     (Value::Object(data), Value::Object(extras))
 }
 
-/// Validate field types. Returns warnings (doesn't reject).
-pub fn validate_fields(
-    data: &Value,
-    field_defs: Option<&Value>,
-) -> Vec<String> {
-    let mut warnings = Vec::new();
+/// Convert a submitted value to the field's declared type. Form bodies
+/// carry only strings, so `"42"` becomes `42`, a checkbox's `"on"` becomes
+/// `true`, and dates are normalized to ISO 8601. A `multiple` field always
+/// becomes an array. Values that don't parse are left alone for validation
+/// to report.
+pub fn coerce(def: &FieldDef, value: &Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(|v| coerce_one(def, v)).collect()),
+        single if def.multiple && !is_blank(single) => Value::Array(vec![coerce_one(def, single)]),
+        single => coerce_one(def, single),
+    }
+}
+
+fn coerce_one(def: &FieldDef, value: &Value) -> Value {
+    let Value::String(s) = value else {
+        return value.clone();
+    };
+    let coerced = match def.field_type {
+        FieldType::Number => as_number(value).map(|n| {
+            if n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 {
+                Value::from(n as i64)
+            } else {
+                Value::from(n)
+            }
+        }),
+        FieldType::Boolean => match s.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Some(Value::Bool(true)),
+            "false" | "0" | "no" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        FieldType::Date => parse_date(s).map(|d| Value::String(d.format("%Y-%m-%d").to_string())),
+        FieldType::Datetime => parse_datetime(s)
+            .map(|dt| Value::String(dt.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string())),
+        _ => None,
+    };
+    coerced.unwrap_or_else(|| value.clone())
+}
+
+/// Validation problems keyed by field name.
+pub type FieldErrors = BTreeMap<String, Vec<String>>;
+
+/// What to do with a submission that fails validation, from `settings.validation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValidationMode {
+    /// Store it, recording the problems as warnings (default).
+    Warn,
+    /// Refuse it with a 422 listing the problems per field.
+    Reject,
+}
+
+impl ValidationMode {
+    pub fn from_settings(settings: &Value) -> Result<Self, String> {
+        match &settings["validation"] {
+            Value::Null => Ok(Self::Warn),
+            Value::String(s) if s == "warn" => Ok(Self::Warn),
+            Value::String(s) if s == "reject" => Ok(Self::Reject),
+            other => Err(format!("validation must be \"warn\" or \"reject\", got {other}")),
+        }
+    }
+}
+
+/// One entry of an endpoint's `fields` definition.
+/// Unknown keys (labels, UI hints) are ignored.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldDef {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub field_type: FieldType,
+    #[serde(default)]
+    pub required: bool,
+    /// String length bounds, in characters.
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    /// Value bounds: numbers for `number`, dates or datetimes for `date` / `datetime`.
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+    /// Regex the whole value must match.
+    pub pattern: Option<String>,
+    /// Allowed values for `select`.
+    #[serde(default)]
+    pub options: Vec<String>,
+    /// Accept an array of values, each checked on its own.
+    #[serde(default)]
+    pub multiple: bool,
+    /// `file`: max size per file, in bytes.
+    pub max_size: Option<u64>,
+    /// `file`: allowed content types, exact (`application/pdf`) or wildcard (`image/*`).
+    #[serde(default)]
+    pub accept: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    #[default]
+    Text,
+    Textarea,
+    Number,
+    #[serde(alias = "checkbox")]
+    Boolean,
+    Date,
+    Datetime,
+    Email,
+    Url,
+    Phone,
+    Select,
+    File,
+}
+
+impl FieldType {
+    pub const ALL: [FieldType; 11] = [
+        Self::Text,
+        Self::Textarea,
+        Self::Number,
+        Self::Boolean,
+        Self::Date,
+        Self::Datetime,
+        Self::Email,
+        Self::Url,
+        Self::Phone,
+        Self::Select,
+        Self::File,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Textarea => "textarea",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::Date => "date",
+            Self::Datetime => "datetime",
+            Self::Email => "email",
+            Self::Url => "url",
+            Self::Phone => "phone",
+            Self::Select => "select",
+            Self::File => "file",
+        }
+    }
 
-    let Some(defs) = field_defs.and_then(|d| d.as_array()) else {
-        return warnings;
+    /// The HTML `<input type>` for this field (`select` and `textarea` are their own elements).
+    pub fn input_type(&self) -> &'static str {
+        match self {
+            Self::Boolean => "checkbox",
+            Self::Datetime => "datetime-local",
+            Self::Phone => "tel",
+            other => other.as_str(),
+        }
+    }
+}
+
+/// Parse field definitions strictly, for saving. Errors name the field.
+pub fn parse_definitions(field_defs: Option<&Value>) -> Result<Vec<FieldDef>, String> {
+    let Some(defs) = field_defs.filter(|d| !d.is_null()) else {
+        return Ok(Vec::new());
     };
+    let defs = defs.as_array().ok_or("fields must be an array")?;
+
+    let mut parsed: Vec<FieldDef> = Vec::with_capacity(defs.len());
+    for (i, def) in defs.iter().enumerate() {
+        let def: FieldDef = serde_json::from_value(def.clone())
+            .map_err(|e| format!("Invalid definition for field #{}: {e}", i + 1))?;
+        if def.name.trim().is_empty() {
+            return Err(format!("Field #{} has no name", i + 1));
+        }
+        if parsed.iter().any(|d| d.name == def.name) {
+            return Err(format!("Duplicate field name: {}", def.name));
+        }
+        def.check().map_err(|e| format!("Field '{}': {e}", def.name))?;
+        parsed.push(def);
+    }
+    Ok(parsed)
+}
 
-    let obj = match data.as_object() {
-        Some(o) => o,
-        None => return warnings,
+/// Parse field definitions leniently, for submissions and the dashboard.
+/// Definitions are checked when saved, so this only meets legacy oddities:
+/// unknown types are treated as `text`, and unparseable entries are skipped.
+pub fn definitions(field_defs: Option<&Value>) -> Vec<FieldDef> {
+    let parse = |def: &Value| {
+        serde_json::from_value::<FieldDef>(def.clone()).ok().or_else(|| {
+            let mut def = def.clone();
+            def.as_object_mut()?.remove("type");
+            serde_json::from_value(def).ok()
+        })
     };
+    field_defs
+        .and_then(|d| d.as_array())
+        .map(|defs| defs.iter().filter_map(parse).collect())
+        .unwrap_or_default()
+}
+
+impl FieldDef {
+    /// Check that the constraints make sense for the field type.
+    fn check(&self) -> Result<(), String> {
+        use FieldType::*;
+
+        if let (Some(min), Some(max)) = (self.min_length, self.max_length)
+            && min > max
+        {
+            return Err("min_length is greater than max_length".to_string());
+        }
+        if let Some(pattern) = &self.pattern {
+            compile_pattern(pattern).map_err(|e| format!("invalid pattern: {e}"))?;
+        }
+        match (self.field_type, self.options.is_empty()) {
+            (Select, true) => return Err("select fields need options".to_string()),
+            (Select, false) | (_, true) => {}
+            (_, false) => return Err("options only apply to select fields".to_string()),
+        }
+        if self.field_type != File && (self.max_size.is_some() || !self.accept.is_empty()) {
+            return Err("max_size and accept only apply to file fields".to_string());
+        }
 
-    for def in defs {
-        let name = match def.get("name").and_then(|n| n.as_str()) {
-            Some(n) => n,
-            None => continue,
+        if self.min.is_none() && self.max.is_none() {
+            return Ok(());
+        }
+        let bound = |v: &Option<Value>, key: &str| -> Result<Option<Bound>, String> {
+            let Some(v) = v else { return Ok(None) };
+            self.bound(v)
+                .map(Some)
+                .ok_or_else(|| format!("{key} must be a valid {}", self.field_type.as_str()))
         };
+        if !matches!(self.field_type, Number | Date | Datetime) {
+            return Err("min and max only apply to number, date and datetime fields".to_string());
+        }
+        if let (Some(min), Some(max)) = (bound(&self.min, "min")?, bound(&self.max, "max")?)
+            && min > max
+        {
+            return Err("min is greater than max".to_string());
+        }
+        Ok(())
+    }
 
-        let required = def.get("required").and_then(|r| r.as_bool()).unwrap_or(false);
-        let field_type = def.get("type").and_then(|t| t.as_str()).unwrap_or("text");
+    /// Parse `min` / `max` for this field's type.
+    fn bound(&self, v: &Value) -> Option<Bound> {
+        match self.field_type {
+            FieldType::Number => as_number(v).map(Bound::Number),
+            FieldType::Date => v.as_str().and_then(parse_date).map(Bound::Date),
+            FieldType::Datetime => v.as_str().and_then(parse_datetime).map(Bound::Datetime),
+            _ => None,
+        }
+    }
 
-        match obj.get(name) {
-            None | Some(Value::Null) => {
-                if required {
-                    warnings.push(format!("Missing required field: {name}"));
-                }
+    /// Problems with a submitted value (`None` if the field is absent).
+    pub fn validate(&self, value: Option<&Value>) -> Vec<String> {
+        let mut errors = Vec::new();
+        let value = value.unwrap_or(&Value::Null);
+
+        if is_blank(value) {
+            if self.required {
+                errors.push("This field is required".to_string());
             }
-            Some(Value::String(s)) => {
-                if required && s.is_empty() {
-                    warnings.push(format!("Required field is empty: {name}"));
-                }
-                match field_type {
-                    "email" if !s.contains('@') => {
-                        warnings.push(format!("Invalid email format: {name}"));
-                    }
-                    "url" if !s.starts_with("http") => {
-                        warnings.push(format!("Invalid URL format: {name}"));
-                    }
-                    "number" if s.parse::<f64>().is_err() => {
-                        warnings.push(format!("Invalid number format: {name}"));
-                    }
-                    "boolean" if !matches!(s.as_str(), "true" | "false" | "1" | "0" | "yes" | "no") => {
-                        warnings.push(format!("Invalid boolean format: {name}"));
-                    }
-                    "date" => {
-                        // Accept ISO 8601: YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS...
-                        let valid = s.len() >= 10
-                            && s.as_bytes().get(4) == Some(&b'-')
-                            && s.as_bytes().get(7) == Some(&b'-')
-                            && s[..4].parse::<u16>().is_ok()
-                            && s[5..7].parse::<u8>().is_ok()
-                            && s[8..10].parse::<u8>().is_ok();
-                        if !valid {
-                            warnings.push(format!("Invalid date format: {name}"));
-                        }
-                    }
-                    _ => {}
+            return errors;
+        }
+
+        match value {
+            Value::Array(items) if self.multiple => {
+                for item in items.iter().filter(|v| !is_blank(v)) {
+                    self.check_value(item, &mut errors);
                 }
             }
-            Some(Value::Number(_)) if field_type == "boolean" => {
-                warnings.push(format!("Expected boolean, got number: {name}"));
+            Value::Array(_) => errors.push("Only one value is allowed".to_string()),
+            single => self.check_value(single, &mut errors),
+        }
+
+        errors.dedup();
+        errors
+    }
+
+    fn check_value(&self, value: &Value, errors: &mut Vec<String>) {
+        use FieldType::*;
+
+        if self.field_type == File {
+            return self.check_file(value, errors);
+        }
+        if value.get("file_id").is_some() {
+            errors.push("Unexpected file upload".to_string());
+            return;
+        }
+
+        let mut error = |msg: String| errors.push(msg);
+        match (self.field_type, value) {
+            (Number, Value::Bool(_)) => error("Expected number, got boolean".to_string()),
+            (Number, v) => match as_number(v) {
+                Some(n) => self.check_range(Bound::Number(n), &mut error),
+                None => error("Invalid number format".to_string()),
+            },
+            (Boolean, Value::Number(_)) => error("Expected boolean, got number".to_string()),
+            (Boolean, Value::String(s))
+                if !matches!(s.as_str(), "true" | "false" | "1" | "0" | "yes" | "no" | "on") =>
+            {
+                error("Invalid boolean format".to_string())
+            }
+            (Date, Value::String(s)) => match parse_date(s) {
+                Some(d) => self.check_range(Bound::Date(d), &mut error),
+                None => error("Invalid date format".to_string()),
+            },
+            (Datetime, Value::String(s)) => match parse_datetime(s) {
+                Some(dt) => self.check_range(Bound::Datetime(dt), &mut error),
+                None => error("Invalid datetime format".to_string()),
+            },
+            (Email, Value::String(s)) if !is_valid_email(s) => {
+                error("Invalid email format".to_string())
             }
-            Some(Value::Bool(_)) if field_type == "number" => {
-                warnings.push(format!("Expected number, got boolean: {name}"));
+            (Url, Value::String(s)) if !is_valid_url(s) => error("Invalid URL format".to_string()),
+            (Phone, Value::String(s)) if !is_valid_phone(s) => {
+                error("Invalid phone number format (use E.164, e.g. +14155550123)".to_string())
+            }
+            (Select, v) => {
+                let text = match v {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                if !self.options.contains(&text) {
+                    error(format!("Must be one of: {}", self.options.join(", ")));
+                }
             }
-            // JSON numbers and booleans are accepted natively for their respective types
             _ => {}
         }
+
+        if let Value::String(s) = value {
+            let len = s.chars().count();
+            if let Some(min) = self.min_length
+                && len < min
+            {
+                error(format!("Must be at least {min} characters"));
+            }
+            if let Some(max) = self.max_length
+                && len > max
+            {
+                error(format!("Must be at most {max} characters"));
+            }
+            if let Some(re) = self.pattern.as_deref().and_then(|p| compile_pattern(p).ok())
+                && !re.is_match(s)
+            {
+                error("Does not match the required format".to_string());
+            }
+        }
+    }
+
+    fn check_range(&self, value: Bound, error: &mut impl FnMut(String)) {
+        if let Some(min) = self.min.as_ref().and_then(|m| self.bound(m))
+            && value < min
+        {
+            error(match min {
+                Bound::Number(_) => format!("Must be at least {min}"),
+                _ => format!("Must be on or after {min}"),
+            });
+        }
+        if let Some(max) = self.max.as_ref().and_then(|m| self.bound(m))
+            && value > max
+        {
+            error(match max {
+                Bound::Number(_) => format!("Must be at most {max}"),
+                _ => format!("Must be on or before {max}"),
+            });
+        }
+    }
+
+    fn check_file(&self, value: &Value, errors: &mut Vec<String>) {
+        if value.get("file_id").is_none() {
+            errors.push("Expected a file upload".to_string());
+            return;
+        }
+        if let (Some(max), Some(size)) = (self.max_size, value["size"].as_u64())
+            && size > max
+        {
+            errors.push(format!("File must be at most {max} bytes"));
+        }
+        let content_type = value["content_type"].as_str().unwrap_or_default();
+        if !self.accept.is_empty() && !uploads::type_matches(&self.accept, content_type) {
+            errors.push(format!("File type '{content_type}' is not allowed"));
+        }
+    }
+}
+
+/// A comparable `min` / `max` / value.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Bound {
+    Number(f64),
+    Date(NaiveDate),
+    Datetime(NaiveDateTime),
+}
+
+impl std::fmt::Display for Bound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Bound::Number(n) => write!(f, "{n}"),
+            Bound::Date(d) => write!(f, "{}", d.format("%Y-%m-%d")),
+            Bound::Datetime(dt) => write!(f, "{}", dt.format("%Y-%m-%dT%H:%M:%SZ")),
+        }
+    }
+}
+
+/// Validate submitted data against the endpoint's field definitions.
+pub fn validate_fields(
+    data: &Value,
+    field_defs: Option<&Value>,
+) -> FieldErrors {
+    let mut errors = FieldErrors::new();
+
+    let Some(obj) = data.as_object() else {
+        return errors;
+    };
+
+    for def in definitions(field_defs) {
+        let problems = def.validate(obj.get(&def.name));
+        if !problems.is_empty() {
+            errors.insert(def.name.clone(), problems);
+        }
     }
 
-    warnings
+    errors
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(a) => a.is_empty(),
+        _ => false,
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok().filter(|n| n.is_finite()),
+        _ => None,
+    }
+}
+
+/// Patterns must match the whole value, like the HTML `pattern` attribute.
+fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?:{pattern})$"))
+}
+
+/// `YYYY-MM-DD`, or the date part of a datetime.
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .or_else(|| parse_datetime(s).map(|dt| dt.date()))
+}
+
+/// RFC 3339, or a datetime without offset (as sent by `datetime-local`), taken as UTC.
+pub fn parse_datetime(s: &str) -> Option<NaiveDateTime> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.naive_utc());
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+}
+
+/// Addresses per RFC 5321/5322: a dot-atom or quoted local part, and a
+/// domain name or address literal. Non-ASCII is allowed (RFC 6531).
+fn is_valid_email(s: &str) -> bool {
+    if s.len() > 254 {
+        return false;
+    }
+    let Some((local, domain)) = s.rsplit_once('@') else {
+        return false;
+    };
+    if local.is_empty() || local.len() > 64 {
+        return false;
+    }
+
+    let local_ok = match local.strip_prefix('"').and_then(|l| l.strip_suffix('"')) {
+        Some(quoted) => {
+            let mut chars = quoted.chars();
+            let mut ok = true;
+            while let Some(c) = chars.next() {
+                ok &= match c {
+                    '\\' => chars.next().is_some(),
+                    '"' => false,
+                    c => c == ' ' || c.is_ascii_graphic() || !c.is_ascii(),
+                };
+            }
+            ok
+        }
+        None => local.split('.').all(|atom| {
+            !atom.is_empty()
+                && atom
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c) || !c.is_ascii())
+        }),
+    };
+    if !local_ok {
+        return false;
+    }
+
+    if let Some(literal) = domain.strip_prefix('[').and_then(|d| d.strip_suffix(']')) {
+        return literal.parse::<Ipv4Addr>().is_ok()
+            || literal
+                .strip_prefix("IPv6:")
+                .is_some_and(|v6| v6.parse::<Ipv6Addr>().is_ok());
+    }
+    domain.contains('.') && is_hostname(domain)
+}
+
+fn is_hostname(host: &str) -> bool {
+    let labels: Vec<&str> = host.split('.').collect();
+    host.len() <= 253
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || !c.is_ascii())
+        })
+        // A numeric TLD means this is really an IP address
+        && labels.last().is_some_and(|tld| !tld.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Absolute http(s) URLs with a host.
+fn is_valid_url(s: &str) -> bool {
+    reqwest::Url::parse(s)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host().is_some())
+}
+
+/// E.164: `+`, then up to 15 digits with no leading zero. Spaces, dots,
+/// dashes and parentheses are ignored.
+fn is_valid_phone(s: &str) -> bool {
+    let compact: String = s
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect();
+    let Some(digits) = compact.strip_prefix('+') else {
+        return false;
+    };
+    (2..=15).contains(&digits.len())
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && !digits.starts_with('0')
 }
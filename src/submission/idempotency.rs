@@ -0,0 +1,104 @@
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+pub const HEADER: &str = "idempotency-key";
+
+const DEFAULT_WINDOW_SECS: i64 = 24 * 60 * 60;
+const MAX_WINDOW_SECS: i64 = 30 * 24 * 60 * 60;
+const MAX_KEY_LEN: usize = 255;
+
+/// Duplicate suppression from endpoint settings (`settings.idempotency`).
+///
+/// ```json
+/// { "enabled": true, "field": "order.id", "window_secs": 86400 }
+/// ```
+///
+/// The key comes from `field` when set (a dotted path like `items.0.sku`, or
+/// a JSON Pointer like `/order/id`), otherwise from the `Idempotency-Key` header.
+pub struct Idempotency {
+    pub enabled: bool,
+    pub field: Option<String>,
+    pub window_secs: i64,
+}
+
+impl Default for Idempotency {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            field: None,
+            window_secs: DEFAULT_WINDOW_SECS,
+        }
+    }
+}
+
+impl Idempotency {
+    pub fn from_settings(settings: &Value) -> Result<Self, String> {
+        let s = &settings["idempotency"];
+        if !s.is_null() && !s.is_object() {
+            return Err("idempotency must be an object".to_string());
+        }
+        let enabled = match &s["enabled"] {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            _ => return Err("idempotency.enabled must be a boolean".to_string()),
+        };
+        let field = match &s["field"] {
+            Value::Null => None,
+            Value::String(f) if f.is_empty() => None,
+            Value::String(f) => Some(f.clone()),
+            _ => return Err("idempotency.field must be a string".to_string()),
+        };
+        let window_secs = match &s["window_secs"] {
+            Value::Null => DEFAULT_WINDOW_SECS,
+            v => v
+                .as_i64()
+                .filter(|n| (1..=MAX_WINDOW_SECS).contains(n))
+                .ok_or_else(|| {
+                    format!("idempotency.window_secs must be between 1 and {MAX_WINDOW_SECS}")
+                })?,
+        };
+        Ok(Self { enabled, field, window_secs })
+    }
+
+    /// The submission's key, if it has one. `headers` is `None` for items of
+    /// a batch, which share one request and so can only be keyed by `field`.
+    pub fn key(&self, headers: Option<&HeaderMap>, body: &Value) -> Result<Option<String>, String> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let key = match &self.field {
+            Some(path) => match lookup(body, path) {
+                Some(Value::String(s)) => Some(s.clone()),
+                Some(Value::Number(n)) => Some(n.to_string()),
+                _ => None,
+            },
+            None => match headers.and_then(|h| h.get(HEADER)) {
+                Some(v) => Some(
+                    v.to_str()
+                        .map_err(|_| "Idempotency-Key header must be visible ASCII".to_string())?
+                        .trim()
+                        .to_string(),
+                ),
+                None => None,
+            },
+        };
+        match key {
+            Some(k) if k.len() > MAX_KEY_LEN => Err(format!(
+                "Idempotency key must be at most {MAX_KEY_LEN} characters"
+            )),
+            Some(k) if k.is_empty() => Ok(None),
+            key => Ok(key),
+        }
+    }
+}
+
+/// Resolve a dotted path or JSON Pointer. Numeric segments index arrays.
+fn lookup<'a>(body: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.starts_with('/') {
+        return body.pointer(path);
+    }
+    path.split('.').try_fold(body, |value, segment| match value {
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => value.get(segment),
+    })
+}
@@ -23,32 +23,35 @@ pub fn extract(
         .to_string();
 
     json!({
-        "ip": ip,
+        "ip": ip.to_string(),
         "user_agent": user_agent,
         "referer": referer,
     })
 }
 
-fn extract_ip(
+/// The client's address: the peer, or the first untrusted `X-Forwarded-For`
+/// hop when the peer is a trusted proxy.
+pub fn extract_ip(
     headers: &HeaderMap,
     peer_addr: Option<IpAddr>,
     trusted_proxies: &[IpNet],
-) -> String {
+) -> IpAddr {
     let peer = peer_addr.unwrap_or(IpAddr::from([127, 0, 0, 1]));
 
     // Only trust X-Forwarded-For if the direct connection is from a trusted proxy
-    if !trusted_proxies.is_empty() && trusted_proxies.iter().any(|net| net.contains(&peer)) {
-        if let Some(xff) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
-            // Take the first (leftmost) IP that isn't a trusted proxy
-            for ip_str in xff.split(',').map(|s| s.trim()) {
-                if let Ok(ip) = ip_str.parse::<IpAddr>() {
-                    if !trusted_proxies.iter().any(|net| net.contains(&ip)) {
-                        return ip.to_string();
-                    }
-                }
+    if !trusted_proxies.is_empty()
+        && trusted_proxies.iter().any(|net| net.contains(&peer))
+        && let Some(xff) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok())
+    {
+        // Take the first (leftmost) IP that isn't a trusted proxy
+        for ip_str in xff.split(',').map(|s| s.trim()) {
+            if let Ok(ip) = ip_str.parse::<IpAddr>()
+                && !trusted_proxies.iter().any(|net| net.contains(&ip))
+            {
+                return ip;
             }
         }
     }
 
-    peer.to_string()
+    peer
 }
@@ -1,5 +1,15 @@
+pub mod access;
+pub mod capture;
+pub mod captcha;
+pub mod cors;
 pub mod pipeline;
 pub mod parser;
 pub mod honeypot;
+pub mod duplicates;
+pub mod idempotency;
 pub mod fields;
 pub mod metadata;
+pub mod redaction;
+pub mod signature;
+pub mod spam;
+pub mod uploads;
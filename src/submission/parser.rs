@@ -1,40 +1,353 @@
 use axum::http::HeaderMap;
 use serde_json::{Map, Value};
-use std::collections::HashMap;
-
-/// Parse a request body based on Content-Type header.
-pub fn parse_body(content_type: Option<&str>, body: &[u8]) -> Result<Value, String> {
-    let ct = content_type.unwrap_or("application/json");
-
-    if ct.contains("application/json") {
-        serde_json::from_slice(body).map_err(|e| format!("Invalid JSON: {e}"))
-    } else if ct.contains("application/x-www-form-urlencoded") {
-        parse_form_urlencoded(body)
-    } else if ct.contains("multipart/form-data") {
-        Err("multipart".to_string())
-    } else {
-        // Try JSON first, then form-urlencoded
-        serde_json::from_slice(body)
-            .or_else(|_| parse_form_urlencoded(body))
-            .map_err(|e| format!("Unable to parse body: {e}"))
+
+use super::uploads::UploadedFile;
+
+/// Bracket segments allowed after the base name; deeper keys are kept verbatim.
+const MAX_KEY_DEPTH: usize = 8;
+
+/// How form field names map onto the parsed JSON, from `settings.form_keys`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormKeys {
+    /// Repeated keys become arrays, and bracket notation (`a[b][c]`,
+    /// `items[]`) becomes nested objects and arrays (default).
+    Nested,
+    /// Every key is kept verbatim; a repeated key keeps its last value.
+    Flat,
+}
+
+impl FormKeys {
+    pub fn from_settings(settings: &Value) -> Result<Self, String> {
+        match &settings["form_keys"] {
+            Value::Null => Ok(Self::Nested),
+            Value::String(s) if s == "nested" => Ok(Self::Nested),
+            Value::String(s) if s == "flat" => Ok(Self::Flat),
+            other => Err(format!("form_keys must be \"nested\" or \"flat\", got {other}")),
+        }
+    }
+
+    /// Add one form field to `map`.
+    pub fn insert(self, map: &mut Map<String, Value>, key: &str, value: Value) {
+        if self == Self::Flat {
+            map.insert(key.to_string(), value);
+            return;
+        }
+        // A key that doesn't fit the shape built so far (`a=1&a[b]=2`) is kept verbatim
+        let value = match split_key(key) {
+            Some((base, path)) => {
+                let slot = map.entry(base).or_insert(Value::Null);
+                match insert_at(slot, &path, value) {
+                    Ok(()) => return,
+                    Err(value) => value,
+                }
+            }
+            None => value,
+        };
+        append(map.entry(key).or_insert(Value::Null), value);
     }
 }
 
-fn parse_form_urlencoded(body: &[u8]) -> Result<Value, String> {
-    let body_str = std::str::from_utf8(body).map_err(|e| format!("Invalid UTF-8: {e}"))?;
-    let pairs: HashMap<String, String> = form_urlencoded::parse(body_str.as_bytes())
-        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+/// Query-string submissions from `settings.get_ingest`. Off unless `enabled`.
+///
+/// ```json
+/// { "enabled": true, "pixel": true }
+/// ```
+pub struct GetIngest {
+    pub enabled: bool,
+    /// Answer with a 1×1 transparent GIF instead of JSON, for `<img>` beacons.
+    pub pixel: bool,
+}
+
+impl GetIngest {
+    pub fn from_settings(settings: &Value) -> Self {
+        let s = &settings["get_ingest"];
+        Self {
+            enabled: s["enabled"].as_bool().unwrap_or(false),
+            pixel: s["pixel"].as_bool().unwrap_or(false),
+        }
+    }
+
+    /// A query string can't be signed, so endpoints that verify signatures
+    /// don't take GET submissions.
+    pub fn validate(settings: &Value) -> Result<(), String> {
+        if Self::from_settings(settings).enabled && !settings["signature"].is_null() {
+            return Err(
+                "get_ingest can't be enabled on an endpoint that verifies signatures".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// One `[...]` segment of a form key.
+#[derive(Debug, PartialEq)]
+enum Segment {
+    /// `[name]`
+    Key(String),
+    /// `[]`
+    Push,
+}
+
+/// Split `a[b][]` into `a` and its segments. `None` for plain keys, and for
+/// keys that aren't well-formed bracket notation or are nested too deep.
+fn split_key(key: &str) -> Option<(String, Vec<Segment>)> {
+    let open = key.find('[').filter(|&i| i > 0)?;
+    let (base, mut rest) = key.split_at(open);
+
+    let mut path = Vec::new();
+    while !rest.is_empty() {
+        let inner = rest.strip_prefix('[')?;
+        let close = inner.find(']')?;
+        let name = &inner[..close];
+        if name.contains('[') {
+            return None;
+        }
+        path.push(match name {
+            "" => Segment::Push,
+            name => Segment::Key(name.to_string()),
+        });
+        rest = &inner[close + 1..];
+    }
+
+    (path.len() <= MAX_KEY_DEPTH).then(|| (base.to_string(), path))
+}
+
+/// Store a value where one may already be: a second value turns it into an array.
+fn append(slot: &mut Value, value: Value) {
+    match slot {
+        Value::Null => *slot = value,
+        Value::Array(items) => items.push(value),
+        existing => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+    }
+}
+
+/// Insert `value` at `path` below `slot`, creating objects and arrays on
+/// the way. Gives the value back if the path runs into a value of another
+/// shape; nothing is changed in that case.
+fn insert_at(slot: &mut Value, path: &[Segment], value: Value) -> Result<(), Value> {
+    let Some((segment, rest)) = path.split_first() else {
+        append(slot, value);
+        return Ok(());
+    };
+
+    match segment {
+        Segment::Key(key) => {
+            if slot.is_null() {
+                *slot = Value::Object(Map::new());
+            }
+            let Value::Object(obj) = slot else {
+                return Err(value);
+            };
+            insert_at(obj.entry(key.as_str()).or_insert(Value::Null), rest, value)
+        }
+        Segment::Push => {
+            match slot {
+                Value::Null => *slot = Value::Array(Vec::new()),
+                Value::Object(_) => return Err(value),
+                Value::Array(_) => {}
+                // `a=1&a[]=2` collects both
+                _ => *slot = Value::Array(vec![slot.take()]),
+            }
+            let Value::Array(items) = slot else {
+                unreachable!("slot was just made an array");
+            };
+            // `items[][name]=a&items[][name]=b`: like Rails, keep filling the
+            // last element until it already has the key, then start a new one
+            let reuse = match (items.last(), rest.first()) {
+                (Some(Value::Object(last)), Some(Segment::Key(key))) => !last.contains_key(key),
+                _ => false,
+            };
+            if !reuse {
+                items.push(Value::Null);
+            }
+            let last = items.last_mut().expect("items is not empty");
+            insert_at(last, rest, value)
+        }
+    }
+}
+
+/// Most lines accepted in one NDJSON body.
+pub const MAX_NDJSON_LINES: usize = 1000;
+
+/// A request body format, from the Content-Type header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BodyKind {
+    Json,
+    Form,
+    Multipart,
+    Xml,
+    Text,
+    /// Newline-delimited JSON; each line is a separate submission.
+    Ndjson,
+}
+
+impl BodyKind {
+    pub const ALL: [BodyKind; 6] = [
+        Self::Json,
+        Self::Form,
+        Self::Multipart,
+        Self::Xml,
+        Self::Text,
+        Self::Ndjson,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Form => "form",
+            Self::Multipart => "multipart",
+            Self::Xml => "xml",
+            Self::Text => "text",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    /// `None` for a content type we can't parse. A missing header means JSON.
+    pub fn from_content_type(content_type: Option<&str>) -> Option<Self> {
+        let Some(ct) = content_type else {
+            return Some(Self::Json);
+        };
+        let essence = ct.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        match essence.as_str() {
+            "application/json" | "text/json" => Some(Self::Json),
+            "application/x-www-form-urlencoded" => Some(Self::Form),
+            "multipart/form-data" => Some(Self::Multipart),
+            "application/xml" | "text/xml" => Some(Self::Xml),
+            "text/plain" => Some(Self::Text),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl"
+            | "application/x-jsonlines" => Some(Self::Ndjson),
+            other if other.ends_with("+json") => Some(Self::Json),
+            // e.g. application/soap+xml
+            other if other.ends_with("+xml") => Some(Self::Xml),
+            _ => None,
+        }
+    }
+
+    /// Formats the endpoint accepts, from `settings.content_types`. All by default.
+    pub fn allowed(settings: &Value) -> Result<Vec<Self>, String> {
+        match &settings["content_types"] {
+            Value::Null => Ok(Self::ALL.to_vec()),
+            Value::Array(names) => names
+                .iter()
+                .map(|name| {
+                    let name = name.as_str().unwrap_or_default();
+                    Self::ALL
+                        .into_iter()
+                        .find(|k| k.as_str() == name)
+                        .ok_or_else(|| format!("Unknown content type in content_types: {name:?}"))
+                })
+                .collect(),
+            _ => Err("content_types must be an array".to_string()),
+        }
+    }
+}
+
+/// Parse a single-submission request body. Multipart and NDJSON have their
+/// own parsers.
+pub fn parse_body(kind: BodyKind, body: &[u8], keys: FormKeys) -> Result<Value, String> {
+    match kind {
+        BodyKind::Json => serde_json::from_slice(body).map_err(|e| format!("Invalid JSON: {e}")),
+        BodyKind::Form => parse_form_urlencoded(body, keys),
+        BodyKind::Xml => parse_xml(body),
+        BodyKind::Text => {
+            let text = std::str::from_utf8(body).map_err(|e| format!("Invalid UTF-8: {e}"))?;
+            Ok(serde_json::json!({ "_body": text }))
+        }
+        BodyKind::Multipart | BodyKind::Ndjson => {
+            Err(format!("{} bodies are not parsed here", kind.as_str()))
+        }
+    }
+}
+
+/// One value per non-blank line. Fails on the first line that isn't JSON.
+pub fn parse_ndjson(body: &[u8]) -> Result<Vec<Value>, String> {
+    let text = std::str::from_utf8(body).map_err(|e| format!("Invalid UTF-8: {e}"))?;
+
+    let mut items = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if items.len() == MAX_NDJSON_LINES {
+            return Err(format!("Too many lines (max {MAX_NDJSON_LINES})"));
+        }
+        let item = serde_json::from_str(line)
+            .map_err(|e| format!("Invalid JSON on line {}: {e}", i + 1))?;
+        items.push(item);
+    }
+    if items.is_empty() {
+        return Err("Empty NDJSON body".to_string());
+    }
+    Ok(items)
+}
+
+/// Convert an XML document to JSON, keyed by the root element's name.
+///
+/// Elements become objects keyed by child name (repeated children become an
+/// array), attributes go under `@attr`, and text goes under `#text`. An
+/// element with only text becomes a plain string. Namespace prefixes are
+/// dropped. DTDs are refused, so entity expansion can't be abused.
+fn parse_xml(body: &[u8]) -> Result<Value, String> {
+    let text = std::str::from_utf8(body).map_err(|e| format!("Invalid UTF-8: {e}"))?;
+    let doc = roxmltree::Document::parse(text).map_err(|e| format!("Invalid XML: {e}"))?;
+
+    let root = doc.root_element();
+    let mut map = Map::new();
+    map.insert(root.tag_name().name().to_string(), xml_element(root));
+    Ok(Value::Object(map))
+}
+
+fn xml_element(node: roxmltree::Node) -> Value {
+    let mut obj = Map::new();
+
+    let attrs: Map<String, Value> = node
+        .attributes()
+        .map(|a| (a.name().to_string(), Value::String(a.value().to_string())))
         .collect();
+    if !attrs.is_empty() {
+        obj.insert("@attr".to_string(), Value::Object(attrs));
+    }
+
+    let mut text = String::new();
+    for child in node.children() {
+        if child.is_element() {
+            let slot = obj.entry(child.tag_name().name()).or_insert(Value::Null);
+            append(slot, xml_element(child));
+        } else if child.is_text() {
+            text.push_str(child.text().unwrap_or_default());
+        }
+    }
+
+    let text = text.trim();
+    if obj.is_empty() {
+        return Value::String(text.to_string());
+    }
+    if !text.is_empty() {
+        obj.insert("#text".to_string(), Value::String(text.to_string()));
+    }
+    Value::Object(obj)
+}
+
+fn parse_form_urlencoded(body: &[u8], keys: FormKeys) -> Result<Value, String> {
+    let body_str = std::str::from_utf8(body).map_err(|e| format!("Invalid UTF-8: {e}"))?;
 
     let mut map = Map::new();
-    for (k, v) in pairs {
-        map.insert(k, Value::String(v));
+    for (k, v) in form_urlencoded::parse(body_str.as_bytes()) {
+        keys.insert(&mut map, &k, Value::String(v.into_owned()));
     }
     Ok(Value::Object(map))
 }
 
-/// Parse multipart form data using multer.
-pub async fn parse_multipart(headers: &HeaderMap, body: bytes::Bytes) -> Result<Value, String> {
+/// Parse multipart form data using multer. Parts with a filename are returned
+/// separately as files; everything else is collected as text fields.
+pub async fn parse_multipart(
+    headers: &HeaderMap,
+    body: bytes::Bytes,
+    keys: FormKeys,
+) -> Result<(Value, Vec<UploadedFile>), String> {
     let boundary = headers
         .get("content-type")
         .and_then(|v| v.to_str().ok())
@@ -45,18 +358,57 @@ pub async fn parse_multipart(headers: &HeaderMap, body: bytes::Bytes) -> Result<
     let mut multipart = multer::Multipart::new(stream, boundary);
 
     let mut map = Map::new();
+    let mut files = Vec::new();
     while let Some(field) = multipart
         .next_field()
         .await
         .map_err(|e| format!("Multipart error: {e}"))?
     {
         let name = field.name().unwrap_or("unknown").to_string();
+
+        if let Some(filename) = field.file_name().map(|f| f.to_string()) {
+            let content_type = field
+                .content_type()
+                .map(|m| m.essence_str().to_string())
+                .unwrap_or_else(|| "application/octet-stream".to_string());
+            let content = field
+                .bytes()
+                .await
+                .map_err(|e| format!("Field read error: {e}"))?;
+            // Browsers send an empty part for a file input left blank
+            if filename.is_empty() && content.is_empty() {
+                continue;
+            }
+            files.push(UploadedFile {
+                field_name: name,
+                filename: sanitize_filename(&filename),
+                content_type,
+                content,
+            });
+            continue;
+        }
+
         let value = field
             .text()
             .await
             .map_err(|e| format!("Field read error: {e}"))?;
-        map.insert(name, Value::String(value));
+        keys.insert(&mut map, &name, Value::String(value));
     }
 
-    Ok(Value::Object(map))
+    Ok((Value::Object(map), files))
+}
+
+/// Strip any client-supplied path and control characters from an upload filename.
+fn sanitize_filename(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or("");
+    let cleaned: String = base
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(255)
+        .collect();
+    if cleaned.is_empty() || cleaned == "." || cleaned == ".." {
+        "upload".to_string()
+    } else {
+        cleaned
+    }
 }
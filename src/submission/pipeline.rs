@@ -1,21 +1,38 @@
 use std::net::IpAddr;
 
 use axum::http::HeaderMap;
+use chrono::Utc;
 use serde_json::json;
+use sqlx::{Connection, PgConnection};
 use uuid::Uuid;
 
+use crate::actions::condition::Condition;
+use crate::crypto;
 use crate::db;
-use crate::models::Endpoint;
+use crate::error::AppError;
+use crate::models::{Action, Endpoint};
+use crate::quota::{self, TenantQuota};
 use crate::state::SharedState;
 
-use super::fields;
+use super::captcha::{self, VerifyError};
+use super::capture::CapturedRequest;
+use super::duplicates::{DuplicateAction, Duplicates};
+use super::fields::{self, ValidationMode};
 use super::honeypot;
+use super::idempotency::Idempotency;
 use super::metadata;
+use super::parser::FormKeys;
+use super::redaction::{self, Target};
+use super::spam::{self, timing, SpamInput, SpamSettings, Verdict};
+use super::uploads::{self, UploadLimits, UploadedFile};
 
 pub struct PipelineResult {
     pub submission_id: Option<Uuid>,
     pub redirect_url: Option<String>,
     pub spam: bool,
+    /// A repeat of an earlier submission's idempotency key; `submission_id`
+    /// is the original and nothing new was stored.
+    pub duplicate: bool,
 }
 
 pub async fn run(
@@ -23,75 +40,553 @@ pub async fn run(
     endpoint: &Endpoint,
     headers: &HeaderMap,
     peer_addr: Option<IpAddr>,
+    mut raw_data: serde_json::Value,
+    files: Vec<UploadedFile>,
+    capture: Option<&CapturedRequest>,
+) -> Result<PipelineResult, AppError> {
+    let settings = endpoint_settings(endpoint);
+    check_rate_limit(state, endpoint, &settings, peer_addr).await?;
+    let quota = quota::load(state, endpoint.project_id).await?;
+    quota.check(1)?;
+    check_captcha(state, endpoint, &settings, headers, peer_addr, &mut raw_data).await?;
+
+    let actions = db::actions::list_enabled_ordered(&state.pool, endpoint.id)
+        .await
+        .unwrap_or_default();
+
+    let spam = spam_settings(state, endpoint, &settings).await?;
+
+    let idempotency_key = Idempotency::from_settings(&settings)
+        .unwrap_or_default()
+        .key(Some(headers), &raw_data)
+        .map_err(AppError::BadRequest)?;
+
+    let req = Request {
+        endpoint,
+        tenant_id: quota.tenant_id,
+        settings: &settings,
+        actions: &actions,
+        spam: &spam,
+        headers,
+        peer_addr,
+        capture,
+    };
+    let mut tx = state.pool.begin().await?;
+    let item = Item { raw_data, files, idempotency_key };
+    let processed = process(state, &mut tx, &req, item).await?;
+
+    let committed = async {
+        db::action_queue::enqueue_many(&mut *tx, &processed.enqueue).await?;
+        tx.commit().await
+    }
+    .await;
+    if let Err(e) = committed {
+        discard_blobs(state, &processed.blobs).await;
+        return Err(AppError::Database(e));
+    }
+
+    if let Some(bytes) = processed.stored_bytes {
+        quota::send_warnings(state, &quota, 1, bytes);
+    }
+    Ok(processed.result)
+}
+
+/// Run several submissions in one transaction, counted as a single request
+/// by the rate limiter. Each item gets its own savepoint, so one that fails
+/// (validation, storage) is reported without losing the others. Actions for
+/// all stored items are enqueued with one insert.
+///
+/// Items can only be keyed for idempotency by a body field, since they share
+/// the request's headers.
+///
+/// A CAPTCHA token answers for one submission, so endpoints that require
+/// one refuse batches. A batch that doesn't fit in the tenant's quota is
+/// refused whole.
+///
+/// With request capture on, every stored item gets its own copy of the
+/// batch's request, so replaying one resends the whole batch.
+///
+/// The outer error is for the batch as a whole (rate limit, database).
+pub async fn run_batch(
+    state: &SharedState,
+    endpoint: &Endpoint,
+    headers: &HeaderMap,
+    peer_addr: Option<IpAddr>,
+    items: Vec<serde_json::Value>,
+    capture: Option<&CapturedRequest>,
+) -> Result<Vec<Result<PipelineResult, AppError>>, AppError> {
+    let settings = endpoint_settings(endpoint);
+    check_rate_limit(state, endpoint, &settings, peer_addr).await?;
+    let quota = quota::load(state, endpoint.project_id).await?;
+    quota.check(items.len() as i64)?;
+    if !matches!(captcha::parse_config(&settings), Ok(None)) {
+        return Err(AppError::BadRequest(
+            "This endpoint requires a CAPTCHA and doesn't accept batches".to_string(),
+        ));
+    }
+
+    let actions = db::actions::list_enabled_ordered(&state.pool, endpoint.id)
+        .await
+        .unwrap_or_default();
+
+    let spam = spam_settings(state, endpoint, &settings).await?;
+    let idempotency = Idempotency::from_settings(&settings).unwrap_or_default();
+
+    let req = Request {
+        endpoint,
+        tenant_id: quota.tenant_id,
+        settings: &settings,
+        actions: &actions,
+        spam: &spam,
+        headers,
+        peer_addr,
+        capture,
+    };
+    let mut tx = state.pool.begin().await?;
+    let mut results = Vec::with_capacity(items.len());
+    let mut enqueue = Vec::new();
+    let (mut stored, mut stored_bytes) = (0, 0);
+    for raw_data in items {
+        let idempotency_key = match idempotency.key(None, &raw_data) {
+            Ok(key) => key,
+            Err(e) => {
+                results.push(Err(AppError::BadRequest(e)));
+                continue;
+            }
+        };
+        let mut savepoint = tx.begin().await?;
+        let item = Item { raw_data, files: Vec::new(), idempotency_key };
+        match process(state, &mut savepoint, &req, item).await {
+            Ok(processed) => {
+                savepoint.commit().await?;
+                if let Some(bytes) = processed.stored_bytes {
+                    stored += 1;
+                    stored_bytes += bytes;
+                }
+                enqueue.extend(processed.enqueue);
+                results.push(Ok(processed.result));
+            }
+            Err(e) => {
+                savepoint.rollback().await?;
+                results.push(Err(e));
+            }
+        }
+    }
+
+    db::action_queue::enqueue_many(&mut *tx, &enqueue).await?;
+    tx.commit().await?;
+    if stored > 0 {
+        quota::send_warnings(state, &quota, stored, stored_bytes);
+    }
+    Ok(results)
+}
+
+/// One submission's parsed body and file parts.
+struct Item {
     raw_data: serde_json::Value,
-) -> Result<PipelineResult, String> {
-    let settings = endpoint
+    files: Vec<UploadedFile>,
+    idempotency_key: Option<String>,
+}
+
+/// A stored (or silently dropped) submission, not yet committed.
+struct Processed {
+    result: PipelineResult,
+    /// `(submission_id, action_id)` pairs to enqueue.
+    enqueue: Vec<(Uuid, Uuid)>,
+    /// Blob keys to discard if the transaction doesn't commit.
+    blobs: Vec<String>,
+    /// Set when a new submission was stored: its size as counted against
+    /// the tenant's storage quota.
+    stored_bytes: Option<i64>,
+}
+
+/// What every item of one request shares.
+struct Request<'a> {
+    endpoint: &'a Endpoint,
+    tenant_id: Uuid,
+    settings: &'a serde_json::Value,
+    actions: &'a [Action],
+    spam: &'a SpamSettings,
+    headers: &'a HeaderMap,
+    peer_addr: Option<IpAddr>,
+    /// The verbatim request, stored with each submission when captured.
+    capture: Option<&'a CapturedRequest>,
+}
+
+impl Processed {
+    /// Nothing stored, answered as if it had been (honeypot, spam, duplicates).
+    fn dropped(settings: &serde_json::Value) -> Self {
+        Self {
+            result: PipelineResult {
+                submission_id: None,
+                redirect_url: settings["redirect_url"]
+                    .as_str()
+                    .map(|s| s.to_string()),
+                spam: true,
+                duplicate: false,
+            },
+            enqueue: Vec::new(),
+            blobs: Vec::new(),
+            stored_bytes: None,
+        }
+    }
+}
+
+fn endpoint_settings(endpoint: &Endpoint) -> serde_json::Value {
+    endpoint
         .settings
         .as_ref()
         .cloned()
-        .unwrap_or(json!({}));
+        .unwrap_or(json!({}))
+}
 
+async fn check_rate_limit(
+    state: &SharedState,
+    endpoint: &Endpoint,
+    settings: &serde_json::Value,
+    peer_addr: Option<IpAddr>,
+) -> Result<(), AppError> {
     let rate_limit = settings["rate_limit"].as_u64().unwrap_or(10) as u32;
     let rate_window = settings["rate_limit_window_secs"].as_u64().unwrap_or(60);
     let ip = peer_addr.unwrap_or(IpAddr::from([127, 0, 0, 1]));
 
-    if let Err(retry_after) =
-        state
-            .submission_limiter
-            .check(endpoint.id, ip, rate_limit, rate_window)
+    state
+        .submission_limiter
+        .check(endpoint.id, ip, rate_limit, rate_window)
+        .await
+        .map_err(|retry_after| {
+            AppError::RateLimited(format!("Rate limited. Retry after {retry_after}s"))
+        })
+}
+
+/// Verify the CAPTCHA token if the endpoint requires one, removing it from
+/// the body either way. Fails closed: unusable settings or an unreachable
+/// provider reject the submission too.
+async fn check_captcha(
+    state: &SharedState,
+    endpoint: &Endpoint,
+    settings: &serde_json::Value,
+    headers: &HeaderMap,
+    peer_addr: Option<IpAddr>,
+    raw_data: &mut serde_json::Value,
+) -> Result<(), AppError> {
+    let config = match captcha::parse_config(settings) {
+        Ok(Some(config)) => config,
+        Ok(None) => return Ok(()),
+        Err(e) => return Err(AppError::Internal(format!("CAPTCHA settings unusable: {e}"))),
+    };
+    let token = captcha::take_token(raw_data, config.provider);
+    let client_ip = metadata::extract_ip(headers, peer_addr, &state.config.trusted_proxies);
+    match state
+        .captcha
+        .verify(&config, token.as_deref(), client_ip, &state.config.encryption_key)
+        .await
     {
-        return Err(format!("Rate limited. Retry after {retry_after}s"));
+        Ok(()) => Ok(()),
+        Err(VerifyError::Invalid(reason)) => {
+            tracing::debug!("CAPTCHA check failed for endpoint {}: {reason}", endpoint.id);
+            Err(AppError::Forbidden("CAPTCHA verification failed".to_string()))
+        }
+        Err(VerifyError::Unavailable(reason)) => {
+            tracing::warn!(
+                "CAPTCHA verification unavailable for endpoint {} ({}): {reason}",
+                endpoint.id,
+                config.provider.as_str()
+            );
+            Err(AppError::Forbidden("CAPTCHA verification failed".to_string()))
+        }
     }
+}
+
+/// The endpoint's spam checks, plus the tenant's IP blocklist.
+async fn spam_settings(
+    state: &SharedState,
+    endpoint: &Endpoint,
+    settings: &serde_json::Value,
+) -> Result<SpamSettings, AppError> {
+    let blocklist = db::tenants::ip_blocklist_for_project(&state.pool, endpoint.project_id).await?;
+    let nets = blocklist
+        .iter()
+        .filter_map(|entry| spam::ip::parse_entry(entry).ok())
+        .collect();
+    // Invalid settings are rejected on save
+    Ok(SpamSettings::from_settings(settings, &state.config.encryption_key)
+        .unwrap_or_default()
+        .with_ip_blocklist(nets))
+}
+
+/// Everything after the rate limit for one submission. Database writes go
+/// through `conn`; the caller commits and enqueues the actions.
+async fn process(
+    state: &SharedState,
+    conn: &mut PgConnection,
+    req: &Request<'_>,
+    item: Item,
+) -> Result<Processed, AppError> {
+    let &Request {
+        endpoint,
+        tenant_id,
+        settings,
+        actions,
+        spam,
+        headers,
+        peer_addr,
+        capture,
+    } = req;
+    let Item { mut raw_data, files, idempotency_key } = item;
 
     let honeypot_field = settings["honeypot_field"].as_str();
     if honeypot::is_spam(&raw_data, honeypot_field) {
-        return Ok(PipelineResult {
-            submission_id: None,
-            redirect_url: settings["redirect_url"]
-                .as_str()
-                .map(|s| s.to_string()),
-            spam: true,
-        });
+        return Ok(Processed::dropped(settings));
     }
 
-    let raw = raw_data.clone();
-    let (data, extras) = fields::sort_fields(&raw_data, endpoint.fields.as_ref());
+    let form_token = if spam.uses_form_token() {
+        raw_data
+            .as_object_mut()
+            .and_then(|map| map.remove(timing::FIELD))
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+    } else {
+        None
+    };
+    let client_ip = metadata::extract_ip(headers, peer_addr, &state.config.trusted_proxies);
+    let spam_report = spam.evaluate(&SpamInput {
+        endpoint_id: endpoint.id,
+        body: &raw_data,
+        client_ip: Some(client_ip),
+        form_token: form_token.as_deref(),
+        now: Utc::now().timestamp(),
+    });
+    let verdict = spam_report.as_ref().map_or(Verdict::Accept, |r| r.verdict);
+    if verdict == Verdict::Drop {
+        return Ok(Processed::dropped(settings));
+    }
+    let quarantined = verdict == Verdict::Quarantine;
 
-    let warnings = fields::validate_fields(&data, endpoint.fields.as_ref());
-    if !warnings.is_empty() {
-        tracing::debug!("Validation warnings for endpoint {}: {:?}", endpoint.id, warnings);
+    // Claim the key before any work, so a repeat stores and uploads nothing.
+    // The claim is released if this submission fails to store.
+    if let Some(key) = &idempotency_key {
+        let window_secs = Idempotency::from_settings(settings).unwrap_or_default().window_secs;
+        if let Some(original) =
+            db::idempotency_keys::claim(&mut *conn, endpoint.id, key, window_secs).await?
+        {
+            return Ok(Processed {
+                result: PipelineResult {
+                    submission_id: Some(original),
+                    redirect_url: settings["redirect_url"]
+                        .as_str()
+                        .map(|s| s.to_string()),
+                    spam: false,
+                    duplicate: true,
+                },
+                enqueue: Vec::new(),
+                blobs: Vec::new(),
+                stored_bytes: None,
+            });
+        }
     }
 
-    let meta = metadata::extract(headers, peer_addr, &state.config.trusted_proxies);
+    UploadLimits::from_settings(settings).check(&files)?;
 
-    let submission = db::submissions::create(
-        &state.pool,
-        endpoint.id,
-        &data,
-        &extras,
-        &raw,
-        &meta,
-    )
-    .await
-    .map_err(|e| format!("Failed to store submission: {e}"))?;
+    // Fail closed: if the rules can't be parsed, don't store unredacted data.
+    let rules = redaction::parse_rules(settings).map_err(AppError::Internal)?;
 
-    let actions = db::actions::list_enabled_ordered(&state.pool, endpoint.id)
-        .await
-        .unwrap_or_default();
+    // Upload file content first so the submission can reference it by id
+    let mut stored = Vec::with_capacity(files.len());
+    let mut blobs = Vec::with_capacity(files.len());
+    let mut refs = Vec::with_capacity(files.len());
+    for file in &files {
+        let file_id = Uuid::now_v7();
+        let key = uploads::storage_key(endpoint.id, file_id);
+        if let Err(e) = state
+            .blob_store
+            .put(&key, file.content.clone(), &file.content_type)
+            .await
+        {
+            discard_blobs(state, &blobs).await;
+            return Err(AppError::Internal(format!("Failed to store file: {e}")));
+        }
+        refs.push((file.field_name.clone(), uploads::file_ref(file_id, file)));
+        blobs.push(key.clone());
+        stored.push((file_id, key, file));
+    }
+    if !refs.is_empty() {
+        let keys = FormKeys::from_settings(settings).unwrap_or(FormKeys::Nested);
+        uploads::insert_refs(&mut raw_data, refs, keys);
+    }
+
+    let mut raw = raw_data.clone();
+    let (mut data, mut extras) = fields::sort_fields(&raw_data, endpoint.fields.as_ref());
 
-    for action in &actions {
-        if let Err(e) = db::action_queue::enqueue(&state.pool, submission.id, action.id).await {
-            tracing::error!("Failed to enqueue action {}: {e}", action.id);
+    let errors = fields::validate_fields(&data, endpoint.fields.as_ref());
+    let mode = ValidationMode::from_settings(settings).unwrap_or(ValidationMode::Warn);
+    if !errors.is_empty() && mode == ValidationMode::Reject {
+        discard_blobs(state, &blobs).await;
+        return Err(AppError::Validation(errors));
+    }
+    let warnings = (!errors.is_empty()).then(|| json!(errors));
+
+    // Hashed before redaction, which may not be deterministic
+    let dedup = Duplicates::from_settings(settings).unwrap_or_default();
+    let dedup_key = crypto::subkey(&state.config.encryption_key, "duplicates");
+    let content_hash = dedup.hash(&data, &dedup_key);
+    let duplicate = match &content_hash {
+        Some(hash) => {
+            match db::submissions::has_recent_hash(&mut *conn, endpoint.id, hash, dedup.window_mins).await {
+                Ok(found) => found,
+                Err(e) => {
+                    discard_blobs(state, &blobs).await;
+                    return Err(AppError::Database(e));
+                }
+            }
         }
+        None => false,
+    };
+    if duplicate && dedup.action == DuplicateAction::Drop {
+        discard_blobs(state, &blobs).await;
+        return Ok(Processed::dropped(settings));
+    }
+
+    let mut meta = if settings["store_metadata"].as_bool().unwrap_or(true) {
+        metadata::extract(headers, peer_addr, &state.config.trusted_proxies)
+    } else {
+        json!({})
+    };
+    // The score is kept even without metadata, it explains the verdict
+    if let Some(report) = &spam_report {
+        meta["spam"] = report.to_json();
     }
 
+    let key = crypto::subkey(&state.config.encryption_key, "redaction");
+    redaction::apply(&rules, Target::Data, &mut data, &key);
+    redaction::apply(&rules, Target::Extras, &mut extras, &key);
+    redaction::apply(&rules, Target::Raw, &mut raw, &key);
+    redaction::apply(&rules, Target::Metadata, &mut meta, &key);
+
+    // JSON as serialized, file content and the captured body as stored; kept
+    // on the submission so deletes and the recount use the same figure
+    let mut bytes = [&data, &extras, &raw, &meta]
+        .iter()
+        .map(|v| v.to_string().len() as i64)
+        .sum::<i64>();
+    bytes += stored.iter().map(|(_, _, file)| file.content.len() as i64).sum::<i64>();
+    bytes += capture.map_or(0, |c| c.body.len() as i64);
+
+    let written = async {
+        let submission = db::submissions::create(
+            &mut *conn,
+            endpoint.id,
+            &data,
+            &extras,
+            &raw,
+            &meta,
+            warnings.as_ref(),
+            content_hash.as_deref(),
+            duplicate,
+            quarantined,
+            bytes,
+        )
+        .await?;
+
+        // Locks the tenant row, so the check sees every committed submission
+        let usage = db::quotas::record(&mut *conn, tenant_id, 1, bytes).await?;
+        TenantQuota::new(usage, &state.config.default_quotas).check_recorded(bytes)?;
+
+        if let Some(key) = &idempotency_key {
+            db::idempotency_keys::attach(&mut *conn, endpoint.id, key, submission.id).await?;
+        }
+
+        if let Some(captured) = capture {
+            db::submission_requests::create(
+                &mut *conn,
+                submission.id,
+                &captured.method,
+                captured.content_type.as_deref(),
+                &captured.headers,
+                &captured.body,
+                captured.body_size,
+                captured.truncated,
+            )
+            .await?;
+        }
+
+        for (file_id, key, file) in &stored {
+            db::submission_files::create(
+                &mut *conn,
+                *file_id,
+                submission.id,
+                &file.field_name,
+                &file.filename,
+                &file.content_type,
+                file.content.len() as i64,
+                key,
+            )
+            .await?;
+        }
+
+        let mut enqueue = Vec::with_capacity(actions.len());
+        let skip_reason = if quarantined {
+            Some("quarantined as spam")
+        } else if duplicate && dedup.action == DuplicateAction::SkipActions {
+            Some("duplicate submission")
+        } else {
+            None
+        };
+        for action in actions {
+            if let Some(reason) = skip_reason {
+                let response = json!({ "reason": reason });
+                db::action_log::create(&mut *conn, action.id, submission.id, "skipped", Some(&response))
+                    .await?;
+                continue;
+            }
+            if let Some(source) = &action.condition {
+                let skip = match Condition::parse(source) {
+                    Ok(cond) => (!cond.eval(&data, &extras, &meta, &raw))
+                        .then(|| ("skipped", json!({ "reason": "condition false", "condition": source }))),
+                    // Validated on save, so this only happens if the grammar changes
+                    Err(e) => Some(("failed", json!({ "error": format!("Invalid condition: {e}") }))),
+                };
+                if let Some((status, response)) = skip {
+                    db::action_log::create(&mut *conn, action.id, submission.id, status, Some(&response))
+                        .await?;
+                    continue;
+                }
+            }
+            enqueue.push((submission.id, action.id));
+        }
+        Ok::<_, AppError>((submission, enqueue))
+    }
+    .await;
+
+    let (submission, enqueue) = match written {
+        Ok(w) => w,
+        Err(e) => {
+            discard_blobs(state, &blobs).await;
+            return Err(e);
+        }
+    };
+
     let redirect_url = settings["redirect_url"]
         .as_str()
         .map(|s| s.to_string());
 
-    Ok(PipelineResult {
-        submission_id: Some(submission.id),
-        redirect_url,
-        spam: false,
+    Ok(Processed {
+        result: PipelineResult {
+            submission_id: Some(submission.id),
+            redirect_url,
+            spam: false,
+            duplicate: false,
+        },
+        enqueue,
+        blobs,
+        stored_bytes: Some(bytes),
     })
 }
+
+/// Best-effort removal of blobs uploaded before the submission failed to store.
+async fn discard_blobs(state: &SharedState, keys: &[String]) {
+    for key in keys {
+        if let Err(e) = state.blob_store.delete(key).await {
+            tracing::warn!("Failed to discard blob {key}: {e}");
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::net::IpAddr;
+
+use hmac::{Hmac, Mac};
+use ipnet::IpNet;
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::Sha256;
+
+/// A single field-level redaction rule from endpoint settings (`settings.redaction`).
+///
+/// ```json
+/// { "field": "ssn", "action": "drop", "targets": ["raw"] }
+/// { "field": "ip", "action": "truncate_ip", "prefix": 24 }
+/// { "field": "card", "action": "mask", "keep_last": 4 }
+/// { "field": "contact.email", "action": "hash" }
+/// ```
+///
+/// `field` is a top-level key, or a path into nested values written with dots
+/// or as a form key (`contact.email`, `contact[email]`). Arrays on the way
+/// are walked item by item, so `items.email` and `items[][email]` reach every
+/// item; a number picks one (`items.0.email`). A top-level key spelled
+/// exactly like the path wins.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedactionRule {
+    pub field: String,
+    #[serde(flatten)]
+    pub action: RedactAction,
+    #[serde(default = "all_targets")]
+    pub targets: Vec<Target>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RedactAction {
+    /// Remove the key entirely.
+    Drop,
+    /// Replace with a keyed HMAC-SHA256 hex digest (stable per instance, not reversible).
+    Hash,
+    /// Zero the host bits of an IP address. Non-IP values become null.
+    TruncateIp {
+        prefix: Option<u8>,
+        prefix_v6: Option<u8>,
+    },
+    /// Replace all but the last `keep_last` characters with `*`.
+    Mask { keep_last: Option<usize> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    Data,
+    Extras,
+    Raw,
+    Metadata,
+}
+
+fn all_targets() -> Vec<Target> {
+    vec![Target::Data, Target::Extras, Target::Raw, Target::Metadata]
+}
+
+/// Parse and validate `settings.redaction`. Missing or null means no rules.
+pub fn parse_rules(settings: &Value) -> Result<Vec<RedactionRule>, String> {
+    let rules = match settings.get("redaction") {
+        None | Some(Value::Null) => return Ok(Vec::new()),
+        Some(v) => Vec::<RedactionRule>::deserialize(v)
+            .map_err(|e| format!("Invalid redaction rules: {e}"))?,
+    };
+
+    for rule in &rules {
+        if path(&rule.field).is_empty() {
+            return Err("Invalid redaction rules: field must not be empty".to_string());
+        }
+        if let RedactAction::TruncateIp { prefix, prefix_v6 } = rule.action {
+            if prefix.is_some_and(|p| p > 32) {
+                return Err("Invalid redaction rules: prefix must be 0-32".to_string());
+            }
+            if prefix_v6.is_some_and(|p| p > 128) {
+                return Err("Invalid redaction rules: prefix_v6 must be 0-128".to_string());
+            }
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Apply every rule that targets `target` to `value`.
+pub fn apply(rules: &[RedactionRule], target: Target, value: &mut Value, hash_key: &[u8]) {
+    if !value.is_object() {
+        return;
+    }
+
+    for rule in rules.iter().filter(|r| r.targets.contains(&target)) {
+        let field = rule.field.as_str();
+        if value.get(field).is_some() {
+            apply_path(value, &[field], &rule.action, hash_key);
+        } else {
+            apply_path(value, &path(field), &rule.action, hash_key);
+        }
+    }
+}
+
+/// Split `a.b`, `a[b]` and `a[][b]` into their keys; `[]` adds nothing, since
+/// arrays are walked anyway.
+fn path(field: &str) -> Vec<&str> {
+    field.split(['.', '[', ']']).filter(|s| !s.is_empty()).collect()
+}
+
+fn apply_path(value: &mut Value, path: &[&str], action: &RedactAction, hash_key: &[u8]) {
+    let Some((&key, rest)) = path.split_first() else {
+        return;
+    };
+    match value {
+        Value::Array(items) => match key.parse::<usize>() {
+            Ok(i) => match items.get_mut(i) {
+                // Dropping an item would shift the ones after it, so it's nulled
+                Some(item) if rest.is_empty() => *item = redact_value(action, item, hash_key),
+                Some(item) => apply_path(item, rest, action, hash_key),
+                None => {}
+            },
+            Err(_) => {
+                for item in items {
+                    apply_path(item, path, action, hash_key);
+                }
+            }
+        },
+        Value::Object(obj) if rest.is_empty() => {
+            if matches!(action, RedactAction::Drop) {
+                obj.remove(key);
+            } else if let Some(v) = obj.get_mut(key) {
+                *v = redact_value(action, v, hash_key);
+            }
+        }
+        Value::Object(obj) => {
+            if let Some(child) = obj.get_mut(key) {
+                apply_path(child, rest, action, hash_key);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn redact_value(action: &RedactAction, value: &Value, hash_key: &[u8]) -> Value {
+    if value.is_null() {
+        return Value::Null;
+    }
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+
+    match action {
+        RedactAction::Drop => Value::Null,
+        RedactAction::Hash => Value::String(hmac_hex(hash_key, &text)),
+        RedactAction::TruncateIp { prefix, prefix_v6 } => match text.parse::<IpAddr>() {
+            Ok(ip) => {
+                let len = match ip {
+                    IpAddr::V4(_) => prefix.unwrap_or(24),
+                    IpAddr::V6(_) => prefix_v6.unwrap_or(48),
+                };
+                IpNet::new(ip, len)
+                    .map(|net| Value::String(net.network().to_string()))
+                    .unwrap_or(Value::Null)
+            }
+            Err(_) => Value::Null,
+        },
+        RedactAction::Mask { keep_last } => {
+            let keep = keep_last.unwrap_or(4);
+            let count = text.chars().count();
+            let masked: String = text
+                .chars()
+                .enumerate()
+                .map(|(i, c)| if i + keep < count { '*' } else { c })
+                .collect();
+            Value::String(masked)
+        }
+    }
+}
+
+fn hmac_hex(key: &[u8], text: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(text.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
@@ -0,0 +1,233 @@
+use axum::http::HeaderMap;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::Sha256;
+
+use crate::crypto;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Default max age of a signed timestamp (Stripe, Slack), in seconds.
+const DEFAULT_TOLERANCE_SECS: i64 = 300;
+
+/// Inbound signature verification from endpoint settings (`settings.signature`).
+///
+/// ```json
+/// { "scheme": "github", "secret": "..." }
+/// { "scheme": "stripe", "secret": "whsec_...", "tolerance_secs": 300 }
+/// { "scheme": "slack", "secret": "..." }
+/// { "scheme": "hmac", "secret": "...", "header": "X-Shopify-Hmac-Sha256", "encoding": "base64" }
+/// ```
+///
+/// The plaintext `secret` is never stored: `seal_secret` replaces it with
+/// `secret_enc` (hex-encoded `crypto::encrypt` output) before the settings are saved.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignatureConfig {
+    #[serde(flatten)]
+    pub scheme: Scheme,
+    pub secret: Option<String>,
+    pub secret_enc: Option<String>,
+    pub tolerance_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "scheme", rename_all = "snake_case")]
+pub enum Scheme {
+    /// HMAC-SHA256 of the raw body in a single header, e.g. Shopify.
+    Hmac {
+        header: Option<String>,
+        /// Stripped from the header value before decoding, e.g. `sha256=`.
+        prefix: Option<String>,
+        #[serde(default)]
+        encoding: Encoding,
+    },
+    /// `X-Hub-Signature-256: sha256=<hex>`
+    Github,
+    /// `Stripe-Signature: t=<ts>,v1=<hex>[,v1=<hex>]` over `{ts}.{body}`
+    Stripe,
+    /// `X-Slack-Signature: v0=<hex>` over `v0:{ts}:{body}`, with `X-Slack-Request-Timestamp`
+    Slack,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The request is missing a signature or it doesn't match.
+    Invalid(String),
+    /// The endpoint's signature settings can't be used.
+    Config(String),
+}
+
+/// Parse `settings.signature`. Missing or null means verification is off.
+pub fn parse_config(settings: &Value) -> Result<Option<SignatureConfig>, String> {
+    match settings.get("signature") {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => {
+            let config = SignatureConfig::deserialize(v)
+                .map_err(|e| format!("Invalid signature settings: {e}"))?;
+            if config.tolerance_secs.is_some_and(|t| t < 1) {
+                return Err("Invalid signature settings: tolerance_secs must be at least 1".to_string());
+            }
+            if let Scheme::Hmac { header: Some(ref h), .. } = config.scheme
+                && axum::http::HeaderName::from_bytes(h.as_bytes()).is_err()
+            {
+                return Err(format!("Invalid signature settings: bad header name '{h}'"));
+            }
+            Ok(Some(config))
+        }
+    }
+}
+
+/// Validate `settings.signature` and replace a plaintext `secret` with `secret_enc`.
+pub fn seal_secret(settings: &mut Value, key: &str) -> Result<(), String> {
+    let Some(config) = parse_config(settings)? else {
+        return Ok(());
+    };
+    let sig = &mut settings["signature"];
+
+    match config.secret.filter(|s| !s.is_empty()) {
+        Some(secret) => {
+            let enc = crypto::encrypt(&secret, key)?;
+            sig["secret_enc"] = Value::String(hex::encode(enc));
+        }
+        None if config.secret_enc.is_some() => {}
+        None => return Err("Invalid signature settings: secret is required".to_string()),
+    }
+    if let Some(obj) = sig.as_object_mut() {
+        obj.remove("secret");
+    }
+    Ok(())
+}
+
+/// Verify the request signature if the endpoint requires one.
+pub fn verify(
+    settings: &Value,
+    headers: &HeaderMap,
+    body: &[u8],
+    key: &str,
+    now: DateTime<Utc>,
+) -> Result<(), VerifyError> {
+    let Some(config) = parse_config(settings).map_err(VerifyError::Config)? else {
+        return Ok(());
+    };
+    let secret = config
+        .secret_enc
+        .as_deref()
+        .ok_or_else(|| VerifyError::Config("Signature secret is not set".to_string()))
+        .and_then(|enc| hex::decode(enc).map_err(|e| VerifyError::Config(e.to_string())))
+        .and_then(|enc| crypto::decrypt(&enc, key).map_err(VerifyError::Config))?;
+    let tolerance = config.tolerance_secs.unwrap_or(DEFAULT_TOLERANCE_SECS);
+
+    match config.scheme {
+        Scheme::Hmac {
+            header,
+            prefix,
+            encoding,
+        } => {
+            let header = header.as_deref().unwrap_or("X-Signature");
+            let value = header_str(headers, header)?;
+            let value = match prefix.as_deref() {
+                Some(p) => value.strip_prefix(p).unwrap_or(value),
+                None => value,
+            };
+            let sig = decode(value.trim(), encoding)?;
+            check(&secret, &[body], &sig)
+        }
+        Scheme::Github => {
+            let value = header_str(headers, "X-Hub-Signature-256")?;
+            let hex_sig = value
+                .strip_prefix("sha256=")
+                .ok_or_else(|| invalid("Malformed X-Hub-Signature-256 header"))?;
+            check(&secret, &[body], &decode(hex_sig, Encoding::Hex)?)
+        }
+        Scheme::Stripe => {
+            let value = header_str(headers, "Stripe-Signature")?;
+            let mut timestamp = None;
+            let mut candidates = Vec::new();
+            for part in value.split(',') {
+                match part.trim().split_once('=') {
+                    Some(("t", t)) => timestamp = Some(t),
+                    Some(("v1", s)) => candidates.push(s),
+                    _ => {}
+                }
+            }
+            let timestamp = timestamp.ok_or_else(|| invalid("Missing timestamp in Stripe-Signature"))?;
+            check_timestamp(timestamp, tolerance, now)?;
+
+            let matched = candidates.iter().any(|c| {
+                decode(c, Encoding::Hex)
+                    .and_then(|sig| check(&secret, &[timestamp.as_bytes(), b".", body], &sig))
+                    .is_ok()
+            });
+            if matched {
+                Ok(())
+            } else {
+                Err(invalid("Signature mismatch"))
+            }
+        }
+        Scheme::Slack => {
+            let timestamp = header_str(headers, "X-Slack-Request-Timestamp")?;
+            check_timestamp(timestamp, tolerance, now)?;
+            let value = header_str(headers, "X-Slack-Signature")?;
+            let hex_sig = value
+                .strip_prefix("v0=")
+                .ok_or_else(|| invalid("Malformed X-Slack-Signature header"))?;
+            check(
+                &secret,
+                &[b"v0:", timestamp.as_bytes(), b":", body],
+                &decode(hex_sig, Encoding::Hex)?,
+            )
+        }
+    }
+}
+
+fn invalid(msg: &str) -> VerifyError {
+    VerifyError::Invalid(msg.to_string())
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str, VerifyError> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| VerifyError::Invalid(format!("Missing {name} header")))
+}
+
+fn decode(value: &str, encoding: Encoding) -> Result<Vec<u8>, VerifyError> {
+    match encoding {
+        Encoding::Hex => hex::decode(value).map_err(|_| invalid("Malformed signature")),
+        Encoding::Base64 => base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .map_err(|_| invalid("Malformed signature")),
+    }
+}
+
+fn check_timestamp(value: &str, tolerance: i64, now: DateTime<Utc>) -> Result<(), VerifyError> {
+    let ts: i64 = value
+        .parse()
+        .map_err(|_| invalid("Malformed signature timestamp"))?;
+    if now.timestamp().abs_diff(ts) > tolerance as u64 {
+        return Err(invalid("Signature timestamp outside tolerance"));
+    }
+    Ok(())
+}
+
+/// Constant-time comparison of HMAC-SHA256(secret, parts...) against `sig`.
+fn check(secret: &str, parts: &[&[u8]], sig: &[u8]) -> Result<(), VerifyError> {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.verify_slice(sig)
+        .map_err(|_| invalid("Signature mismatch"))
+}
@@ -0,0 +1,88 @@
+use serde_json::Value;
+
+use super::{SpamCheck, SpamHit, SpamInput};
+
+const DEFAULT_SCORE: i64 = 3;
+
+/// Well-known throwaway mailbox providers. Subdomains match too.
+const BUILTIN_DOMAINS: &[&str] = &[
+    "10minutemail.com",
+    "20minutemail.com",
+    "33mail.com",
+    "dispostable.com",
+    "dropmail.me",
+    "emailondeck.com",
+    "fakeinbox.com",
+    "getairmail.com",
+    "getnada.com",
+    "guerrillamail.biz",
+    "guerrillamail.com",
+    "guerrillamail.de",
+    "guerrillamail.info",
+    "guerrillamail.net",
+    "guerrillamail.org",
+    "guerrillamailblock.com",
+    "harakirimail.com",
+    "maildrop.cc",
+    "mailinator.com",
+    "mailinator.net",
+    "mailnesia.com",
+    "mintemail.com",
+    "mohmal.com",
+    "mytemp.email",
+    "sharklasers.com",
+    "spamgourmet.com",
+    "temp-mail.org",
+    "tempail.com",
+    "tempmail.com",
+    "tempmailo.com",
+    "tempr.email",
+    "throwawaymail.com",
+    "trashmail.com",
+    "trashmail.de",
+    "yopmail.com",
+    "yopmail.fr",
+];
+
+/// Flags email addresses at disposable mailbox providers: the built-in list
+/// plus the endpoint's own `domains`.
+pub struct DisposableEmail {
+    extra: Vec<String>,
+    score: i64,
+}
+
+impl DisposableEmail {
+    pub fn from_settings(config: &Value) -> Result<Self, String> {
+        let score = super::score(config, DEFAULT_SCORE, "disposable_email")?;
+        let extra = match &config["domains"] {
+            Value::Null => Vec::new(),
+            Value::Array(items) => items
+                .iter()
+                .map(|v| v.as_str().map(|s| s.trim().trim_start_matches('@').to_lowercase()))
+                .collect::<Option<_>>()
+                .ok_or("spam.disposable_email.domains must be an array of strings")?,
+            _ => return Err("spam.disposable_email.domains must be an array of strings".to_string()),
+        };
+        Ok(Self { extra, score })
+    }
+
+    fn is_disposable(&self, domain: &str) -> bool {
+        let listed = |d: &str| domain == d || domain.ends_with(&format!(".{d}"));
+        BUILTIN_DOMAINS.iter().any(|d| listed(d)) || self.extra.iter().any(|d| listed(d))
+    }
+}
+
+impl SpamCheck for DisposableEmail {
+    fn name(&self) -> &'static str {
+        "disposable_email"
+    }
+
+    fn check(&self, input: &SpamInput) -> Option<SpamHit> {
+        super::strings(input.body).into_iter().find_map(|s| {
+            let (local, domain) = s.trim().rsplit_once('@')?;
+            let domain = domain.to_lowercase();
+            (!local.is_empty() && !local.contains(char::is_whitespace) && self.is_disposable(&domain))
+                .then(|| SpamHit { score: self.score, reason: format!("disposable domain {domain}") })
+        })
+    }
+}
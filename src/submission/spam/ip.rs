@@ -0,0 +1,43 @@
+use std::net::IpAddr;
+
+use ipnet::IpNet;
+
+use super::{SpamCheck, SpamHit, SpamInput};
+
+pub const DEFAULT_SCORE: i64 = 10;
+
+/// Flags clients in the tenant's IP blocklist.
+pub struct IpBlocklist {
+    nets: Vec<IpNet>,
+    score: i64,
+}
+
+impl IpBlocklist {
+    pub fn new(nets: Vec<IpNet>, score: i64) -> Self {
+        Self { nets, score }
+    }
+}
+
+impl SpamCheck for IpBlocklist {
+    fn name(&self) -> &'static str {
+        "ip_blocklist"
+    }
+
+    fn check(&self, input: &SpamInput) -> Option<SpamHit> {
+        let ip = input.client_ip?;
+        self.nets.iter().find(|net| net.contains(&ip)).map(|net| SpamHit {
+            score: self.score,
+            reason: format!("{ip} is in {net}"),
+        })
+    }
+}
+
+/// Parse a blocklist entry: a CIDR range, or a single address.
+pub fn parse_entry(entry: &str) -> Result<IpNet, String> {
+    let entry = entry.trim();
+    entry
+        .parse::<IpNet>()
+        .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+        .map(|net| net.trunc())
+        .map_err(|_| format!("Invalid IP address or CIDR range: {entry:?}"))
+}
@@ -0,0 +1,71 @@
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+
+use super::{SpamCheck, SpamHit, SpamInput};
+
+const DEFAULT_SCORE: i64 = 5;
+/// Compiled size cap per pattern, so settings can't make every submission slow.
+const PATTERN_SIZE_LIMIT: usize = 1 << 20;
+
+/// Flags submissions containing blocked words (case-insensitive substrings)
+/// or matching blocked regular expressions.
+pub struct Blocklist {
+    words: Vec<String>,
+    patterns: Vec<Regex>,
+    score: i64,
+}
+
+impl Blocklist {
+    pub fn from_settings(config: &Value) -> Result<Self, String> {
+        let score = super::score(config, DEFAULT_SCORE, "keywords")?;
+        let words = string_list(&config["words"], "words")?
+            .into_iter()
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_lowercase())
+            .collect();
+        let patterns = string_list(&config["patterns"], "patterns")?
+            .iter()
+            .map(|p| {
+                RegexBuilder::new(p)
+                    .size_limit(PATTERN_SIZE_LIMIT)
+                    .build()
+                    .map_err(|e| format!("Invalid spam.keywords pattern {p:?}: {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { words, patterns, score })
+    }
+}
+
+impl SpamCheck for Blocklist {
+    fn name(&self) -> &'static str {
+        "keywords"
+    }
+
+    fn check(&self, input: &SpamInput) -> Option<SpamHit> {
+        for text in super::strings(input.body) {
+            let lower = text.to_lowercase();
+            if let Some(word) = self.words.iter().find(|w| lower.contains(w.as_str())) {
+                return Some(SpamHit { score: self.score, reason: format!("contains {word:?}") });
+            }
+            if let Some(re) = self.patterns.iter().find(|re| re.is_match(text)) {
+                return Some(SpamHit {
+                    score: self.score,
+                    reason: format!("matches /{}/", re.as_str()),
+                });
+            }
+        }
+        None
+    }
+}
+
+fn string_list(value: &Value, name: &str) -> Result<Vec<String>, String> {
+    match value {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(items) => items
+            .iter()
+            .map(|v| v.as_str().map(|s| s.to_string()))
+            .collect::<Option<_>>()
+            .ok_or_else(|| format!("spam.keywords.{name} must be an array of strings")),
+        _ => Err(format!("spam.keywords.{name} must be an array of strings")),
+    }
+}
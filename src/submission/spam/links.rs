@@ -0,0 +1,46 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::{SpamCheck, SpamHit, SpamInput};
+
+const DEFAULT_MAX: u64 = 2;
+const DEFAULT_SCORE: i64 = 3;
+
+static LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(?:https?://|www\.)|\[url[=\]]").unwrap());
+
+/// Flags submissions with more links than a real message needs.
+pub struct LinkCount {
+    max: u64,
+    score: i64,
+}
+
+impl LinkCount {
+    pub fn from_settings(config: &Value) -> Result<Self, String> {
+        let score = super::score(config, DEFAULT_SCORE, "links")?;
+        let max = match &config["max"] {
+            Value::Null => DEFAULT_MAX,
+            v => v.as_u64().ok_or("spam.links.max must be a non-negative integer")?,
+        };
+        Ok(Self { max, score })
+    }
+}
+
+impl SpamCheck for LinkCount {
+    fn name(&self) -> &'static str {
+        "links"
+    }
+
+    fn check(&self, input: &SpamInput) -> Option<SpamHit> {
+        let count: u64 = super::strings(input.body)
+            .iter()
+            .map(|s| LINK.find_iter(s).count() as u64)
+            .sum();
+        (count > self.max).then(|| SpamHit {
+            score: self.score,
+            reason: format!("{count} links, maximum is {}", self.max),
+        })
+    }
+}
@@ -0,0 +1,226 @@
+pub mod disposable;
+pub mod ip;
+pub mod keywords;
+pub mod links;
+pub mod timing;
+
+use std::net::IpAddr;
+
+use ipnet::IpNet;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+const DEFAULT_QUARANTINE_AT: i64 = 5;
+const DEFAULT_DROP_AT: i64 = 10;
+
+/// What a submission gets to see, for scoring.
+pub struct SpamInput<'a> {
+    pub endpoint_id: Uuid,
+    /// The whole parsed body: spam often lands in fields that aren't defined.
+    pub body: &'a Value,
+    pub client_ip: Option<IpAddr>,
+    /// The form token field, taken out of the body (see `timing`).
+    pub form_token: Option<&'a str>,
+    pub now: i64,
+}
+
+/// One check's contribution to the score.
+pub struct SpamHit {
+    pub score: i64,
+    pub reason: String,
+}
+
+pub trait SpamCheck: Send + Sync {
+    /// Name recorded in the score breakdown.
+    fn name(&self) -> &'static str;
+    /// Points to add and why, or `None` if the submission passes.
+    fn check(&self, input: &SpamInput) -> Option<SpamHit>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Accept,
+    /// Stored and flagged, actions don't run.
+    Quarantine,
+    /// Answered like a honeypot hit and not stored.
+    Drop,
+}
+
+impl Verdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Accept => "accept",
+            Self::Quarantine => "quarantine",
+            Self::Drop => "drop",
+        }
+    }
+}
+
+/// Spam scoring from endpoint settings (`settings.spam`). Each check is on
+/// when its key is present, and adds its `score` when it hits:
+///
+/// ```json
+/// {
+///   "time_to_submit": { "min_secs": 3, "score": 5 },
+///   "links": { "max": 2, "score": 3 },
+///   "keywords": { "words": ["casino"], "patterns": ["(?i)seo\\s+services"], "score": 5 },
+///   "disposable_email": { "domains": ["example-temp.com"], "score": 3 },
+///   "ip_blocklist": { "score": 10 },
+///   "quarantine_at": 5,
+///   "drop_at": 10
+/// }
+/// ```
+///
+/// The tenant's IP blocklist applies to every endpoint, so `ip_blocklist`
+/// only overrides its score.
+pub struct SpamSettings {
+    pub checks: Vec<Box<dyn SpamCheck>>,
+    pub quarantine_at: i64,
+    pub drop_at: i64,
+    ip_score: i64,
+}
+
+impl Default for SpamSettings {
+    fn default() -> Self {
+        Self {
+            checks: Vec::new(),
+            quarantine_at: DEFAULT_QUARANTINE_AT,
+            drop_at: DEFAULT_DROP_AT,
+            ip_score: ip::DEFAULT_SCORE,
+        }
+    }
+}
+
+/// The outcome of scoring one submission.
+pub struct SpamReport {
+    pub score: i64,
+    pub verdict: Verdict,
+    hits: Vec<(&'static str, SpamHit)>,
+}
+
+impl SpamReport {
+    /// Stored under `metadata.spam`.
+    pub fn to_json(&self) -> Value {
+        let checks: Vec<Value> = self
+            .hits
+            .iter()
+            .map(|(name, hit)| json!({ "check": name, "score": hit.score, "reason": hit.reason }))
+            .collect();
+        json!({ "score": self.score, "verdict": self.verdict.as_str(), "checks": checks })
+    }
+}
+
+impl SpamSettings {
+    /// Parse `settings.spam`. `key` signs form tokens.
+    pub fn from_settings(settings: &Value, key: &str) -> Result<Self, String> {
+        let s = &settings["spam"];
+        if s.is_null() {
+            return Ok(Self::default());
+        }
+        if !s.is_object() {
+            return Err("spam must be an object".to_string());
+        }
+
+        let mut checks: Vec<Box<dyn SpamCheck>> = Vec::new();
+        if !s["time_to_submit"].is_null() {
+            checks.push(Box::new(timing::TimeToSubmit::from_settings(&s["time_to_submit"], key)?));
+        }
+        if !s["links"].is_null() {
+            checks.push(Box::new(links::LinkCount::from_settings(&s["links"])?));
+        }
+        if !s["keywords"].is_null() {
+            checks.push(Box::new(keywords::Blocklist::from_settings(&s["keywords"])?));
+        }
+        if !s["disposable_email"].is_null() {
+            checks.push(Box::new(disposable::DisposableEmail::from_settings(
+                &s["disposable_email"],
+            )?));
+        }
+        let ip_score = match &s["ip_blocklist"] {
+            Value::Null => ip::DEFAULT_SCORE,
+            v => score(v, ip::DEFAULT_SCORE, "ip_blocklist")?,
+        };
+
+        Ok(Self {
+            checks,
+            quarantine_at: threshold(&s["quarantine_at"], DEFAULT_QUARANTINE_AT, "quarantine_at")?,
+            drop_at: threshold(&s["drop_at"], DEFAULT_DROP_AT, "drop_at")?,
+            ip_score,
+        })
+    }
+
+    /// Whether the time-to-submit check wants a form token.
+    pub fn uses_form_token(&self) -> bool {
+        self.checks.iter().any(|c| c.name() == timing::NAME)
+    }
+
+    /// Add the tenant's IP blocklist, if it has one.
+    pub fn with_ip_blocklist(mut self, nets: Vec<IpNet>) -> Self {
+        if !nets.is_empty() {
+            self.checks.push(Box::new(ip::IpBlocklist::new(nets, self.ip_score)));
+        }
+        self
+    }
+
+    /// Run every check. `None` when there are none, so endpoints that don't
+    /// use scoring get no `metadata.spam`.
+    pub fn evaluate(&self, input: &SpamInput) -> Option<SpamReport> {
+        if self.checks.is_empty() {
+            return None;
+        }
+        let hits: Vec<_> = self
+            .checks
+            .iter()
+            .filter_map(|c| c.check(input).map(|hit| (c.name(), hit)))
+            .collect();
+        let score = hits.iter().map(|(_, hit)| hit.score).sum();
+        let verdict = if score >= self.drop_at {
+            Verdict::Drop
+        } else if score >= self.quarantine_at {
+            Verdict::Quarantine
+        } else {
+            Verdict::Accept
+        };
+        Some(SpamReport { score, verdict, hits })
+    }
+}
+
+/// A check's `score`, defaulting to `default`.
+pub(crate) fn score(config: &Value, default: i64, check: &str) -> Result<i64, String> {
+    if !config.is_object() {
+        return Err(format!("spam.{check} must be an object"));
+    }
+    match &config["score"] {
+        Value::Null => Ok(default),
+        v => v
+            .as_i64()
+            .filter(|n| *n >= 0)
+            .ok_or_else(|| format!("spam.{check}.score must be a non-negative integer")),
+    }
+}
+
+fn threshold(value: &Value, default: i64, name: &str) -> Result<i64, String> {
+    match value {
+        Value::Null => Ok(default),
+        v => v
+            .as_i64()
+            .filter(|n| *n >= 1)
+            .ok_or_else(|| format!("spam.{name} must be a positive integer")),
+    }
+}
+
+/// Every string in `value`, depth first.
+pub(crate) fn strings(value: &Value) -> Vec<&str> {
+    let mut out = Vec::new();
+    collect_strings(value, &mut out);
+    out
+}
+
+fn collect_strings<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => out.push(s),
+        Value::Array(items) => items.iter().for_each(|v| collect_strings(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_strings(v, out)),
+        _ => {}
+    }
+}
@@ -0,0 +1,89 @@
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use super::{SpamCheck, SpamHit, SpamInput};
+
+pub const NAME: &str = "time_to_submit";
+
+/// Hidden form field carrying the token; removed from the body before storing.
+pub const FIELD: &str = "_wh_ts";
+
+const DEFAULT_MIN_SECS: i64 = 3;
+const DEFAULT_SCORE: i64 = 5;
+/// Older tokens count as missing, so one fetched token can't be reused forever.
+const MAX_AGE_SECS: i64 = 24 * 60 * 60;
+/// Signature bytes kept in the token.
+const SIG_LEN: usize = 16;
+
+/// Flags forms submitted faster than a person could fill them in. The form
+/// fetches a token (`GET /v1/e/{id}/form-token`) when the page loads; it's the
+/// issue time plus an HMAC, so the sender can't backdate it.
+pub struct TimeToSubmit {
+    min_secs: i64,
+    score: i64,
+    key: String,
+}
+
+impl TimeToSubmit {
+    pub fn from_settings(config: &Value, key: &str) -> Result<Self, String> {
+        let score = super::score(config, DEFAULT_SCORE, NAME)?;
+        let min_secs = match &config["min_secs"] {
+            Value::Null => DEFAULT_MIN_SECS,
+            v => v
+                .as_i64()
+                .filter(|n| (1..=3600).contains(n))
+                .ok_or("spam.time_to_submit.min_secs must be between 1 and 3600")?,
+        };
+        Ok(Self { min_secs, score, key: key.to_string() })
+    }
+}
+
+impl SpamCheck for TimeToSubmit {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn check(&self, input: &SpamInput) -> Option<SpamHit> {
+        let reason = match input.form_token.map(|t| verify(&self.key, input.endpoint_id, t)) {
+            None => "no form token".to_string(),
+            Some(None) => "invalid form token".to_string(),
+            Some(Some(issued)) => {
+                let elapsed = input.now - issued;
+                if elapsed > MAX_AGE_SECS {
+                    "expired form token".to_string()
+                } else if elapsed < self.min_secs {
+                    format!("submitted {elapsed}s after load, minimum is {}s", self.min_secs)
+                } else {
+                    return None;
+                }
+            }
+        };
+        Some(SpamHit { score: self.score, reason })
+    }
+}
+
+/// A token for a form loaded at `now`: `<unix time>.<hmac>`.
+pub fn issue(key: &str, endpoint_id: Uuid, now: i64) -> String {
+    format!("{now}.{}", sign(key, endpoint_id, now))
+}
+
+/// The issue time of a valid token for this endpoint.
+fn verify(key: &str, endpoint_id: Uuid, token: &str) -> Option<i64> {
+    let (ts, sig) = token.split_once('.')?;
+    let ts: i64 = ts.parse().ok()?;
+    let sig = hex::decode(sig).ok().filter(|s| s.len() == SIG_LEN)?;
+    mac(key, endpoint_id, ts).verify_truncated_left(&sig).ok()?;
+    Some(ts)
+}
+
+fn sign(key: &str, endpoint_id: Uuid, ts: i64) -> String {
+    hex::encode(&mac(key, endpoint_id, ts).finalize().into_bytes()[..SIG_LEN])
+}
+
+fn mac(key: &str, endpoint_id: Uuid, ts: i64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts any key");
+    mac.update(format!("form-token:{endpoint_id}:{ts}").as_bytes());
+    mac
+}
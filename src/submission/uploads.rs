@@ -0,0 +1,138 @@
+use bytes::Bytes;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+use super::parser::FormKeys;
+
+/// A file part from a multipart submission, held in memory until stored.
+pub struct UploadedFile {
+    pub field_name: String,
+    pub filename: String,
+    pub content_type: String,
+    pub content: Bytes,
+}
+
+/// Per-endpoint upload limits from `settings.file_uploads`.
+/// Uploads are rejected unless `enabled` is true.
+pub struct UploadLimits {
+    pub enabled: bool,
+    pub max_file_size: u64,
+    pub max_files: usize,
+    pub allowed_types: Vec<String>,
+}
+
+impl UploadLimits {
+    pub fn from_settings(settings: &Value) -> Self {
+        let s = &settings["file_uploads"];
+        Self {
+            enabled: s["enabled"].as_bool().unwrap_or(false),
+            max_file_size: s["max_file_size"].as_u64().unwrap_or(10 * 1024 * 1024),
+            max_files: s["max_files"].as_u64().unwrap_or(5) as usize,
+            allowed_types: s["allowed_types"]
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(|s| s.to_lowercase()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn check(&self, files: &[UploadedFile]) -> Result<(), AppError> {
+        if files.is_empty() {
+            return Ok(());
+        }
+        if !self.enabled {
+            return Err(AppError::BadRequest(
+                "File uploads are not enabled for this endpoint".to_string(),
+            ));
+        }
+        if files.len() > self.max_files {
+            return Err(AppError::BadRequest(format!(
+                "Too many files: {} (max {})",
+                files.len(),
+                self.max_files
+            )));
+        }
+        for file in files {
+            if file.content.len() as u64 > self.max_file_size {
+                return Err(AppError::PayloadTooLarge(format!(
+                    "File '{}' exceeds max size of {} bytes",
+                    file.filename, self.max_file_size
+                )));
+            }
+            if !self.type_allowed(&file.content_type) {
+                return Err(AppError::UnsupportedMediaType(format!(
+                    "File type '{}' is not allowed",
+                    file.content_type
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Empty list allows everything.
+    fn type_allowed(&self, content_type: &str) -> bool {
+        self.allowed_types.is_empty() || type_matches(&self.allowed_types, content_type)
+    }
+}
+
+/// Whether `content_type` matches any entry, exact (`application/pdf`)
+/// or a wildcard subtype (`image/*`). Case-insensitive.
+pub fn type_matches(allowed: &[String], content_type: &str) -> bool {
+    let ct = content_type.to_lowercase();
+    allowed.iter().any(|allowed| {
+        let allowed = allowed.to_lowercase();
+        match allowed.strip_suffix("/*") {
+            Some(major) => ct.split('/').next() == Some(major),
+            None => allowed == ct,
+        }
+    })
+}
+
+/// Storage key for a file: `{endpoint_id}/{file_id}`.
+pub fn storage_key(endpoint_id: Uuid, file_id: Uuid) -> String {
+    format!("{endpoint_id}/{file_id}")
+}
+
+/// The JSON reference stored in the submission in place of file content.
+pub fn file_ref(file_id: Uuid, file: &UploadedFile) -> Value {
+    json!({
+        "file_id": file_id,
+        "filename": file.filename,
+        "content_type": file.content_type,
+        "size": file.content.len(),
+    })
+}
+
+/// Insert file references into the submission payload under their field names.
+/// Repeated field names (`<input type="file" multiple>`) become an array, and
+/// bracket names nest like the text fields do.
+pub fn insert_refs(payload: &mut Value, refs: Vec<(String, Value)>, keys: FormKeys) {
+    if !payload.is_object() {
+        *payload = Value::Object(Map::new());
+    }
+    let obj = payload.as_object_mut().expect("payload is an object");
+
+    for (field, r) in refs {
+        if keys == FormKeys::Nested {
+            keys.insert(obj, &field, r);
+            continue;
+        }
+        match obj.get_mut(&field) {
+            Some(Value::Array(arr)) if arr.iter().all(|v| v.get("file_id").is_some()) => {
+                arr.push(r)
+            }
+            Some(existing) if existing.get("file_id").is_some() => {
+                let first = existing.take();
+                *existing = Value::Array(vec![first, r]);
+            }
+            _ => {
+                obj.insert(field, r);
+            }
+        }
+    }
+}
@@ -6,15 +6,29 @@ use crate::auth::extractor::AuthUser;
 use crate::db;
 use crate::error::AppError;
 use crate::models::{Tenant, User};
+use crate::quota::{Quota, TenantQuota};
 use crate::state::SharedState;
 
+use super::submissions::format_size;
+
 #[derive(Template)]
 #[template(path = "admin/tenants.html")]
 #[allow(dead_code)]
 struct TenantsTemplate {
     user_name: String,
     is_system_admin: bool,
-    tenants: Vec<Tenant>,
+    tenants: Vec<TenantRow>,
+}
+
+/// A tenant with its usage formatted as "used / limit".
+struct TenantRow {
+    tenant: Tenant,
+    today: String,
+    this_month: String,
+    stored: String,
+    storage: String,
+    /// The highest share of any quota in use, in percent.
+    peak_percent: i64,
 }
 
 #[derive(Template)]
@@ -38,7 +52,37 @@ pub async fn tenants_page(
         .map(|u| u.name)
         .unwrap_or_default();
 
-    let tenants = db::tenants::list(&state.pool).await?;
+    let defaults = state.config.default_quotas;
+    let tenants = db::quotas::list_usage(&state.pool)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let quota = TenantQuota::new(row.clone(), &defaults);
+            let usage = |q: Quota| {
+                let used = quota.usage.get(q).unwrap_or(0);
+                let show = |n: i64| match q {
+                    Quota::StorageBytes => format_size(n),
+                    _ => n.to_string(),
+                };
+                match quota.limits.get(q) {
+                    Some(limit) => (format!("{} / {}", show(used), show(limit)), used * 100 / limit),
+                    None => (show(used), 0),
+                }
+            };
+            let (today, p1) = usage(Quota::DailySubmissions);
+            let (this_month, p2) = usage(Quota::MonthlySubmissions);
+            let (stored, p3) = usage(Quota::StoredSubmissions);
+            let (storage, p4) = usage(Quota::StorageBytes);
+            TenantRow {
+                tenant: row.tenant,
+                today,
+                this_month,
+                stored,
+                storage,
+                peak_percent: p1.max(p2).max(p3).max(p4),
+            }
+        })
+        .collect();
 
     let template = TenantsTemplate {
         user_name: user,
@@ -36,10 +36,10 @@ pub async fn login_page(
     jar: CookieJar,
 ) -> Response {
     // If already logged in, redirect to dashboard
-    if let Some(cookie) = jar.get("access_token") {
-        if jwt::decode_token(cookie.value(), &state.config.jwt_secret).is_ok() {
-            return Redirect::to("/dashboard").into_response();
-        }
+    if let Some(cookie) = jar.get("access_token")
+        && jwt::decode_token(cookie.value(), &state.config.jwt_secret).is_ok()
+    {
+        return Redirect::to("/dashboard").into_response();
     }
 
     let template = LoginTemplate { error: None };
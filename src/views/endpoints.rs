@@ -1,4 +1,5 @@
 use askama::Template;
+use chrono::Utc;
 use axum::extract::{Path, State};
 use axum::response::{Html, IntoResponse};
 use uuid::Uuid;
@@ -7,7 +8,18 @@ use crate::auth::extractor::AuthUser;
 use crate::db;
 use crate::error::AppError;
 use crate::models::{Action, Endpoint};
+use crate::retention;
 use crate::state::SharedState;
+use crate::submission::capture::CaptureSettings;
+use crate::submission::fields::{self, FieldType, ValidationMode};
+use crate::submission::duplicates::Duplicates;
+use crate::submission::idempotency::Idempotency;
+use crate::submission::spam::{timing, SpamSettings};
+use crate::submission::parser::{BodyKind, FormKeys, GetIngest};
+use crate::submission::access::AccessRules;
+use crate::submission::captcha;
+use crate::submission::signature::{self, Encoding, Scheme};
+use crate::submission::uploads::UploadLimits;
 
 #[derive(Template)]
 #[template(path = "dashboard/submissions.html")]
@@ -32,10 +44,52 @@ struct EndpointSettingsTemplate {
     rate_limit: u64,
     rate_limit_window: u64,
     cors_origins: String,
+    cors_enforce: bool,
     honeypot_field: String,
     store_metadata: bool,
     redirect_url: String,
+    validation_reject: bool,
+    form_keys_flat: bool,
+    content_types: String,
+    get_enabled: bool,
+    get_pixel: bool,
+    idempotency_enabled: bool,
+    idempotency_field: String,
+    idempotency_window: i64,
+    duplicates_enabled: bool,
+    duplicates_fields: String,
+    duplicates_window: i64,
+    duplicates_action: String,
+    error_redirect_url: String,
     retention_days: String,
+    redaction_rules: String,
+    spam_config: String,
+    captcha_provider: String,
+    captcha_secret_set: bool,
+    uploads_enabled: bool,
+    upload_max_file_size: u64,
+    upload_max_files: u64,
+    upload_allowed_types: String,
+    capture_enabled: bool,
+    capture_max_body_bytes: u64,
+    signature_scheme: String,
+    signature_header: String,
+    signature_prefix: String,
+    signature_base64: bool,
+    signature_tolerance: String,
+    signature_secret_set: bool,
+    last_signature_failure_at: String,
+    allowed_cidrs: String,
+    denied_cidrs: String,
+    allowed_countries: String,
+    denied_countries: String,
+    geoip_enabled: bool,
+    last_access_denial_at: String,
+    settings_json: String,
+    fields_json: String,
+    purge_due: Option<i64>,
+    last_purged_at: String,
+    last_purge_count: i64,
 }
 
 #[derive(Template)]
@@ -46,7 +100,11 @@ struct EndpointFieldsTemplate {
     is_system_admin: bool,
     endpoint: Endpoint,
     endpoint_id: String,
-    field_defs: Vec<FieldDef>,
+    field_defs: Vec<FieldRow>,
+    /// Empty row cloned by "Add Field".
+    blank_row: FieldRow,
+    field_types: Vec<&'static str>,
+    settings_json: String,
 }
 
 #[derive(Template)]
@@ -65,6 +123,8 @@ struct ActionsTemplate {
 struct ModuleInfo {
     id: String,
     name: String,
+    /// `config_schema()` as JSON, used to render the config form.
+    schema: String,
 }
 
 #[derive(Template)]
@@ -76,37 +136,190 @@ struct SnippetTemplate {
     endpoint: Endpoint,
     endpoint_id: String,
     base_url: String,
-    fields: Vec<FieldDef>,
+    /// Every field, for the HTML form.
+    fields: Vec<SnippetField>,
+    /// Fields that can be sent as JSON (not files).
+    json_fields: Vec<SnippetField>,
+    has_files: bool,
+    /// The endpoint scores time-to-submit, so the form carries a token field.
+    form_token: bool,
+    form_token_field: &'static str,
 }
 
+/// A field definition as strings, for the fields page inputs.
+#[derive(Default)]
 #[allow(dead_code)]
-struct FieldDef {
+struct FieldRow {
     name: String,
     field_type: String,
     required: bool,
+    min_length: String,
+    max_length: String,
+    min: String,
+    max: String,
+    pattern: String,
+    options: String,
+    multiple: bool,
+    max_size: String,
+    accept: String,
+    has_constraints: bool,
 }
 
-fn parse_field_defs(endpoint: &Endpoint) -> Vec<FieldDef> {
-    endpoint
-        .fields
-        .as_ref()
-        .and_then(|f| f.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|f| {
-                    Some(FieldDef {
-                        name: f.get("name")?.as_str()?.to_string(),
-                        field_type: f
-                            .get("type")
-                            .and_then(|t| t.as_str())
-                            .unwrap_or("text")
-                            .to_string(),
-                        required: f.get("required").and_then(|r| r.as_bool()).unwrap_or(false),
-                    })
-                })
-                .collect()
-        })
-        .unwrap_or_default()
+impl FieldRow {
+    fn new(def: &fields::FieldDef) -> Self {
+        let opt = |v: Option<usize>| v.map(|n| n.to_string()).unwrap_or_default();
+        let bound = |v: &Option<serde_json::Value>| match v {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+        Self {
+            name: def.name.clone(),
+            field_type: def.field_type.as_str().to_string(),
+            required: def.required,
+            min_length: opt(def.min_length),
+            max_length: opt(def.max_length),
+            min: bound(&def.min),
+            max: bound(&def.max),
+            pattern: def.pattern.clone().unwrap_or_default(),
+            options: def.options.join(", "),
+            multiple: def.multiple,
+            max_size: def.max_size.map(|n| n.to_string()).unwrap_or_default(),
+            accept: def.accept.join(", "),
+            has_constraints: def.min_length.is_some()
+                || def.max_length.is_some()
+                || def.min.is_some()
+                || def.max.is_some()
+                || def.pattern.is_some()
+                || !def.options.is_empty()
+                || def.multiple
+                || def.max_size.is_some()
+                || !def.accept.is_empty(),
+        }
+    }
+}
+
+/// A field as it appears in the integration snippets.
+#[allow(dead_code)]
+struct SnippetField {
+    name: String,
+    /// HTML form control with the field's constraints as attributes.
+    markup: String,
+    /// Example value as a JSON (and JavaScript) literal.
+    example: String,
+    /// Example value as a Python literal.
+    example_py: String,
+}
+
+impl SnippetField {
+    fn new(def: &fields::FieldDef) -> Self {
+        let example = example_value(def);
+        Self {
+            name: def.name.clone(),
+            markup: form_control(def),
+            example: example.to_string(),
+            example_py: python_literal(&example),
+        }
+    }
+}
+
+/// Quote a value for an HTML attribute.
+fn attr(value: &str) -> String {
+    format!("\"{}\"", value.replace('&', "&amp;").replace('"', "&quot;"))
+}
+
+fn form_control(def: &fields::FieldDef) -> String {
+    let mut attrs = format!(" name={}", attr(&def.name));
+    if def.required {
+        attrs.push_str(" required");
+    }
+    if def.multiple {
+        attrs.push_str(" multiple");
+    }
+
+    match def.field_type {
+        FieldType::Select => {
+            let mut html = format!("<select{attrs}>\n");
+            for option in &def.options {
+                html.push_str(&format!(
+                    "      <option value={}>{}</option>\n",
+                    attr(option),
+                    option.replace('&', "&amp;").replace('<', "&lt;")
+                ));
+            }
+            html.push_str("    </select>");
+            return html;
+        }
+        FieldType::Boolean => {
+            return format!("<input type=\"checkbox\"{attrs} value=\"true\">");
+        }
+        _ => {}
+    }
+
+    if let Some(n) = def.min_length {
+        attrs.push_str(&format!(" minlength=\"{n}\""));
+    }
+    if let Some(n) = def.max_length {
+        attrs.push_str(&format!(" maxlength=\"{n}\""));
+    }
+    for (key, bound) in [("min", &def.min), ("max", &def.max)] {
+        let value = match bound {
+            Some(serde_json::Value::String(s)) if def.field_type == FieldType::Datetime => {
+                // datetime-local wants YYYY-MM-DDTHH:MM, without an offset
+                fields::parse_datetime(s)
+                    .map(|dt| dt.format("%Y-%m-%dT%H:%M").to_string())
+                    .unwrap_or_else(|| s.clone())
+            }
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => continue,
+        };
+        attrs.push_str(&format!(" {key}={}", attr(&value)));
+    }
+    if let Some(pattern) = &def.pattern {
+        attrs.push_str(&format!(" pattern={}", attr(pattern)));
+    }
+    if !def.accept.is_empty() {
+        attrs.push_str(&format!(" accept={}", attr(&def.accept.join(","))));
+    }
+
+    if def.field_type == FieldType::Textarea {
+        return format!("<textarea{attrs}></textarea>");
+    }
+    format!("<input type=\"{}\"{attrs}>", def.field_type.input_type())
+}
+
+/// A plausible value that passes the field's type checks.
+fn example_value(def: &fields::FieldDef) -> serde_json::Value {
+    use serde_json::json;
+
+    let one = match def.field_type {
+        FieldType::Number => def.min.clone().or(def.max.clone()).unwrap_or(json!(42)),
+        FieldType::Boolean => json!(true),
+        FieldType::Date => def.min.clone().unwrap_or(json!("2025-01-31")),
+        FieldType::Datetime => def.min.clone().unwrap_or(json!("2025-01-31T09:00:00Z")),
+        FieldType::Email => json!("jane@example.com"),
+        FieldType::Url => json!("https://example.com"),
+        FieldType::Phone => json!("+14155550123"),
+        FieldType::Select => json!(def.options.first().cloned().unwrap_or_default()),
+        FieldType::Text | FieldType::Textarea | FieldType::File => json!("value"),
+    };
+    if def.multiple { json!([one]) } else { one }
+}
+
+fn python_literal(value: &serde_json::Value) -> String {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => "None".to_string(),
+        Value::Bool(true) => "True".to_string(),
+        Value::Bool(false) => "False".to_string(),
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(python_literal).collect::<Vec<_>>().join(", ")
+        ),
+        other => other.to_string(),
+    }
 }
 
 pub async fn submissions_page(
@@ -138,6 +351,11 @@ pub async fn submissions_page(
     Ok(Html(template.render().unwrap_or_default()))
 }
 
+/// CIDR ranges one per line, for a textarea.
+fn join_lines(nets: &[ipnet::IpNet]) -> String {
+    nets.iter().map(|n| n.to_string()).collect::<Vec<_>>().join("\n")
+}
+
 pub async fn settings_page(
     auth: AuthUser,
     State(state): State<SharedState>,
@@ -172,6 +390,10 @@ pub async fn settings_page(
                 .join(", ")
         })
         .unwrap_or_default();
+    let cors_enforce = s
+        .and_then(|v| v.get("cors_enforce"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
     let honeypot_field = s
         .and_then(|v| v.get("honeypot_field"))
         .and_then(|v| v.as_str())
@@ -186,11 +408,101 @@ pub async fn settings_page(
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
-    let retention_days = s
+    let validation_reject = s
+        .and_then(|v| ValidationMode::from_settings(v).ok())
+        .is_some_and(|m| m == ValidationMode::Reject);
+    let form_keys_flat = s
+        .and_then(|v| FormKeys::from_settings(v).ok())
+        .is_some_and(|k| k == FormKeys::Flat);
+    let get_ingest = s.map(GetIngest::from_settings);
+    let idempotency = Idempotency::from_settings(s.unwrap_or(&serde_json::Value::Null))
+        .unwrap_or_default();
+    let duplicates = Duplicates::from_settings(s.unwrap_or(&serde_json::Value::Null))
+        .unwrap_or_default();
+    // Empty when unrestricted
+    let content_types = s
+        .filter(|v| !v["content_types"].is_null())
+        .and_then(|v| BodyKind::allowed(v).ok())
+        .map(|kinds| kinds.iter().map(|k| k.as_str()).collect::<Vec<_>>().join(", "))
+        .unwrap_or_default();
+    let error_redirect_url = s
+        .and_then(|v| v.get("error_redirect_url"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    let retention = s
         .and_then(|v| v.get("retention_days"))
-        .and_then(|v| v.as_u64())
-        .map(|d| d.to_string())
+        .and_then(retention::parse_days);
+    let retention_days = retention.map(|d| d.to_string()).unwrap_or_default();
+
+    let purge_due = match retention.and_then(|days| retention::cutoff(Utc::now(), days)) {
+        Some(cutoff) => {
+            Some(db::submissions::count_older_than(&state.pool, endpoint.id, cutoff).await?)
+        }
+        None => None,
+    };
+    let last_purged_at = endpoint
+        .last_purged_at
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    let last_purge_count = endpoint.last_purge_count;
+    let redaction_rules = s
+        .and_then(|v| v.get("redaction"))
+        .filter(|v| !v.is_null())
+        .and_then(|v| serde_json::to_string_pretty(v).ok())
+        .unwrap_or_default();
+    let spam_config = s
+        .and_then(|v| v.get("spam"))
+        .filter(|v| !v.is_null())
+        .and_then(|v| serde_json::to_string_pretty(v).ok())
         .unwrap_or_default();
+    let captcha = s.and_then(|v| captcha::parse_config(v).ok().flatten());
+    let captcha_provider = captcha.as_ref().map(|c| c.provider.as_str()).unwrap_or_default();
+    let captcha_secret_set = captcha.as_ref().is_some_and(|c| c.secret_enc.is_some());
+    let uploads = UploadLimits::from_settings(s.unwrap_or(&serde_json::Value::Null));
+    let capture = CaptureSettings::from_settings(s.unwrap_or(&serde_json::Value::Null));
+    let sig = s.and_then(|v| signature::parse_config(v).ok().flatten());
+    let (signature_scheme, signature_header, signature_prefix, signature_base64) =
+        match sig.as_ref().map(|c| &c.scheme) {
+            Some(Scheme::Hmac {
+                header,
+                prefix,
+                encoding,
+            }) => (
+                "hmac",
+                header.clone().unwrap_or_default(),
+                prefix.clone().unwrap_or_default(),
+                matches!(encoding, Encoding::Base64),
+            ),
+            Some(Scheme::Github) => ("github", String::new(), String::new(), false),
+            Some(Scheme::Stripe) => ("stripe", String::new(), String::new(), false),
+            Some(Scheme::Slack) => ("slack", String::new(), String::new(), false),
+            None => ("", String::new(), String::new(), false),
+        };
+    let signature_tolerance = sig
+        .as_ref()
+        .and_then(|c| c.tolerance_secs)
+        .map(|t| t.to_string())
+        .unwrap_or_default();
+    let signature_secret_set = sig.as_ref().is_some_and(|c| c.secret_enc.is_some());
+    let last_signature_failure_at = endpoint
+        .last_signature_failure_at
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    let access = AccessRules::from_settings(s.unwrap_or(&serde_json::Value::Null))
+        .unwrap_or_default();
+    let last_access_denial_at = endpoint
+        .last_access_denial_at
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    let settings_json = s
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "{}".to_string());
+    let fields_json = endpoint
+        .fields
+        .as_ref()
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "null".to_string());
 
     let template = EndpointSettingsTemplate {
         user_name: user,
@@ -200,10 +512,52 @@ pub async fn settings_page(
         rate_limit,
         rate_limit_window,
         cors_origins,
+        cors_enforce,
         honeypot_field,
         store_metadata,
         redirect_url,
+        validation_reject,
+        form_keys_flat,
+        content_types,
+        get_enabled: get_ingest.as_ref().is_some_and(|g| g.enabled),
+        get_pixel: get_ingest.as_ref().is_some_and(|g| g.pixel),
+        idempotency_enabled: idempotency.enabled,
+        idempotency_field: idempotency.field.unwrap_or_default(),
+        idempotency_window: idempotency.window_secs,
+        duplicates_enabled: duplicates.enabled,
+        duplicates_fields: duplicates.fields.unwrap_or_default().join(", "),
+        duplicates_window: duplicates.window_mins,
+        duplicates_action: duplicates.action.as_str().to_string(),
+        error_redirect_url,
         retention_days,
+        redaction_rules,
+        spam_config,
+        captcha_provider: captcha_provider.to_string(),
+        captcha_secret_set,
+        uploads_enabled: uploads.enabled,
+        upload_max_file_size: uploads.max_file_size,
+        upload_max_files: uploads.max_files as u64,
+        upload_allowed_types: uploads.allowed_types.join(", "),
+        capture_enabled: capture.enabled,
+        capture_max_body_bytes: capture.max_body_bytes as u64,
+        signature_scheme: signature_scheme.to_string(),
+        signature_header,
+        signature_prefix,
+        signature_base64,
+        signature_tolerance,
+        signature_secret_set,
+        last_signature_failure_at,
+        allowed_cidrs: join_lines(&access.allowed_cidrs),
+        denied_cidrs: join_lines(&access.denied_cidrs),
+        allowed_countries: access.allowed_countries.join(", "),
+        denied_countries: access.denied_countries.join(", "),
+        geoip_enabled: state.geoip.is_some(),
+        last_access_denial_at,
+        settings_json,
+        fields_json,
+        purge_due,
+        last_purged_at,
+        last_purge_count,
     };
     Ok(Html(template.render().unwrap_or_default()))
 }
@@ -222,7 +576,15 @@ pub async fn fields_page(
         .map(|u| u.name)
         .unwrap_or_default();
 
-    let field_defs = parse_field_defs(&endpoint);
+    let field_defs = fields::definitions(endpoint.fields.as_ref())
+        .iter()
+        .map(FieldRow::new)
+        .collect();
+    let settings_json = endpoint
+        .settings
+        .as_ref()
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "{}".to_string());
 
     let template = EndpointFieldsTemplate {
         user_name: user,
@@ -230,6 +592,9 @@ pub async fn fields_page(
         endpoint_id: endpoint.id.to_string(),
         endpoint,
         field_defs,
+        blank_row: FieldRow::default(),
+        field_types: FieldType::ALL.iter().map(|t| t.as_str()).collect(),
+        settings_json,
     };
     Ok(Html(template.render().unwrap_or_default()))
 }
@@ -250,15 +615,17 @@ pub async fn actions_page(
         .map(|u| u.name)
         .unwrap_or_default();
 
-    let available_modules: Vec<ModuleInfo> = state
+    let mut available_modules: Vec<ModuleInfo> = state
         .modules
         .list()
         .iter()
         .map(|m| ModuleInfo {
             id: m.id().to_string(),
             name: m.name().to_string(),
+            schema: m.config_schema().to_string(),
         })
         .collect();
+    available_modules.sort_by(|a, b| a.name.cmp(&b.name));
 
     let template = ActionsTemplate {
         user_name: user,
@@ -285,7 +652,19 @@ pub async fn snippet_page(
         .map(|u| u.name)
         .unwrap_or_default();
 
-    let fields = parse_field_defs(&endpoint);
+    let defs = fields::definitions(endpoint.fields.as_ref());
+    let has_files = defs.iter().any(|d| d.field_type == FieldType::File);
+    let json_fields = defs
+        .iter()
+        .filter(|d| d.field_type != FieldType::File)
+        .map(SnippetField::new)
+        .collect();
+    let fields = defs.iter().map(SnippetField::new).collect();
+    let form_token = endpoint
+        .settings
+        .as_ref()
+        .and_then(|s| SpamSettings::from_settings(s, &state.config.encryption_key).ok())
+        .is_some_and(|spam| spam.uses_form_token());
 
     let template = SnippetTemplate {
         user_name: user,
@@ -294,6 +673,10 @@ pub async fn snippet_page(
         endpoint,
         base_url: state.config.base_url.clone(),
         fields,
+        json_fields,
+        has_files,
+        form_token,
+        form_token_field: timing::FIELD,
     };
     Ok(Html(template.render().unwrap_or_default()))
 }
@@ -28,6 +28,8 @@ pub fn view_routes() -> Router<SharedState> {
         .route("/endpoints/{id}/fields", get(endpoints::fields_page))
         .route("/endpoints/{id}/actions", get(endpoints::actions_page))
         .route("/endpoints/{id}/snippet", get(endpoints::snippet_page))
+        // Submissions
+        .route("/submissions/{id}", get(submissions::show))
         // Settings
         .route("/settings", get(settings::account_page))
         .route("/settings/smtp", get(settings::smtp_page))
@@ -7,6 +7,7 @@ use uuid::Uuid;
 use crate::auth::extractor::AuthUser;
 use crate::db;
 use crate::error::AppError;
+use crate::models::{Endpoint, Submission};
 use crate::state::SharedState;
 
 #[allow(dead_code)]
@@ -17,6 +18,19 @@ struct SubmissionRow {
     extras: String,
     metadata: String,
     raw: String,
+    files: Vec<FileLink>,
+    captured: bool,
+    /// `field: message` for each validation warning.
+    warnings: Vec<String>,
+    duplicate: bool,
+    quarantined: bool,
+}
+
+#[allow(dead_code)]
+struct FileLink {
+    id: String,
+    filename: String,
+    size: String,
 }
 
 #[derive(Template)]
@@ -32,9 +46,25 @@ struct SubmissionsTableTemplate {
     sort_by: String,
     sort_order: String,
     search: String,
+    /// Duplicate filter as sent: `true`, `false` or empty for all.
+    duplicate: String,
+    /// Quarantine filter as sent, like `duplicate`.
+    quarantined: String,
     field_names: Vec<String>,
 }
 
+#[derive(Template)]
+#[template(path = "dashboard/submission.html")]
+#[allow(dead_code)]
+struct SubmissionTemplate {
+    user_name: String,
+    is_system_admin: bool,
+    endpoint: Endpoint,
+    endpoint_id: String,
+    row: SubmissionRow,
+    fields: Vec<(String, String)>,
+}
+
 #[derive(Deserialize)]
 pub struct TableParams {
     pub page: Option<i64>,
@@ -42,6 +72,8 @@ pub struct TableParams {
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
     pub search: Option<String>,
+    pub duplicate: Option<String>,
+    pub quarantined: Option<String>,
 }
 
 pub async fn table_partial(
@@ -55,11 +87,13 @@ pub async fn table_partial(
         .ok_or_else(|| AppError::NotFound("Endpoint not found".to_string()))?;
 
     let page = params.page.unwrap_or(1).max(1);
-    let per_page = params.per_page.unwrap_or(20).min(100).max(1);
+    let per_page = params.per_page.unwrap_or(20).clamp(1, 100);
     let offset = (page - 1) * per_page;
     let sort_by = params.sort_by.unwrap_or_else(|| "created_at".to_string());
     let sort_order = params.sort_order.unwrap_or_else(|| "desc".to_string());
     let search = params.search.clone().unwrap_or_default();
+    let duplicate = params.duplicate.unwrap_or_default();
+    let quarantined = params.quarantined.unwrap_or_default();
 
     let list_params = db::submissions::ListParams {
         endpoint_id,
@@ -72,19 +106,12 @@ pub async fn table_partial(
         } else {
             Some(search.clone())
         },
+        duplicate: bool_filter(&duplicate),
+        quarantined: bool_filter(&quarantined),
     };
 
     let submissions = db::submissions::list(&state.pool, &list_params).await?;
-    let total = db::submissions::count(
-        &state.pool,
-        endpoint_id,
-        if search.is_empty() {
-            None
-        } else {
-            Some(&search)
-        },
-    )
-    .await?;
+    let total = db::submissions::count(&state.pool, &list_params).await?;
 
     let field_names: Vec<String> = endpoint
         .fields
@@ -109,6 +136,10 @@ pub async fn table_partial(
             keys
         });
 
+    let submission_ids: Vec<Uuid> = submissions.iter().map(|s| s.id).collect();
+    let files = db::submission_files::list_by_submissions(&state.pool, &submission_ids).await?;
+    let captured = db::submission_requests::list_captured(&state.pool, &submission_ids).await?;
+
     // Pre-process submissions into rows
     let rows: Vec<SubmissionRow> = submissions
         .iter()
@@ -134,6 +165,19 @@ pub async fn table_partial(
                 extras: serde_json::to_string_pretty(&sub.extras).unwrap_or_default(),
                 metadata: serde_json::to_string_pretty(&sub.metadata).unwrap_or_default(),
                 raw: serde_json::to_string_pretty(&sub.raw).unwrap_or_default(),
+                files: files
+                    .iter()
+                    .filter(|f| f.submission_id == Some(sub.id))
+                    .map(|f| FileLink {
+                        id: f.id.to_string(),
+                        filename: f.filename.clone(),
+                        size: format_size(f.size_bytes),
+                    })
+                    .collect(),
+                captured: captured.contains(&sub.id),
+                warnings: warning_list(sub),
+                duplicate: sub.duplicate,
+                quarantined: sub.quarantined,
             }
         })
         .collect();
@@ -150,7 +194,100 @@ pub async fn table_partial(
         sort_by,
         sort_order,
         search,
+        duplicate,
+        quarantined,
         field_names,
     };
     Ok(Html(template.render().unwrap_or_default()))
 }
+
+/// Single submission page, linked from notifications.
+pub async fn show(
+    auth: AuthUser,
+    State(state): State<SharedState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let sub = db::submissions::find_by_id_scoped(&state.pool, id, auth.tenant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Submission not found".to_string()))?;
+    let endpoint = db::endpoints::find_by_id_scoped(&state.pool, sub.endpoint_id, auth.tenant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Endpoint not found".to_string()))?;
+
+    let user = db::users::find_by_id(&state.pool, auth.user_id)
+        .await?
+        .map(|u| u.name)
+        .unwrap_or_default();
+
+    let files = db::submission_files::list_by_submissions(&state.pool, &[sub.id]).await?;
+    let captured = !db::submission_requests::list_captured(&state.pool, &[sub.id])
+        .await?
+        .is_empty();
+
+    let fields = crate::actions::notify::field_pairs(&sub.data);
+    let row = SubmissionRow {
+        id: sub.id.to_string(),
+        cells: Vec::new(),
+        created_at: sub.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        extras: serde_json::to_string_pretty(&sub.extras).unwrap_or_default(),
+        metadata: serde_json::to_string_pretty(&sub.metadata).unwrap_or_default(),
+        raw: serde_json::to_string_pretty(&sub.raw).unwrap_or_default(),
+        files: files
+            .iter()
+            .map(|f| FileLink {
+                id: f.id.to_string(),
+                filename: f.filename.clone(),
+                size: format_size(f.size_bytes),
+            })
+            .collect(),
+        captured,
+        warnings: warning_list(&sub),
+        duplicate: sub.duplicate,
+        quarantined: sub.quarantined,
+    };
+
+    let template = SubmissionTemplate {
+        user_name: user,
+        is_system_admin: auth.is_system_admin,
+        endpoint_id: endpoint.id.to_string(),
+        endpoint,
+        row,
+        fields,
+    };
+    Ok(Html(template.render().unwrap_or_default()))
+}
+
+/// A `true`/`false` filter from a select; anything else means no filter.
+fn bool_filter(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+fn warning_list(sub: &Submission) -> Vec<String> {
+    let Some(warnings) = sub.validation_warnings.as_ref().and_then(|w| w.as_object()) else {
+        return Vec::new();
+    };
+    warnings
+        .iter()
+        .flat_map(|(field, messages)| {
+            messages
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|m| m.as_str())
+                .map(move |m| format!("{field}: {m}"))
+        })
+        .collect()
+}
+
+pub(crate) fn format_size(bytes: i64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{b} B"),
+        b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b if b < 1024 * 1024 * 1024 => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        b => format!("{:.1} GB", b as f64 / (1024.0 * 1024.0 * 1024.0)),
+    }
+}
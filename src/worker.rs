@@ -2,6 +2,7 @@ use serde_json::json;
 use tokio::sync::watch;
 
 use crate::actions::context::ActionContext;
+use crate::actions::{secrets, signing};
 use crate::actions::ActionStatus;
 use crate::db;
 use crate::state::SharedState;
@@ -69,7 +70,7 @@ async fn run(id: usize, state: SharedState, mut shutdown: watch::Receiver<bool>)
 }
 
 /// Try to claim and process the next queue item. Returns true if an item was processed.
-async fn process_next(state: &SharedState) -> Result<bool, String> {
+pub async fn process_next(state: &SharedState) -> Result<bool, String> {
     let item = db::action_queue::claim_next(&state.pool)
         .await
         .map_err(|e| format!("Failed to claim queue item: {e}"))?;
@@ -182,30 +183,71 @@ async fn process_next(state: &SharedState) -> Result<bool, String> {
         }
     };
 
+    let signing_secrets = match signing::active_secrets(
+        &action,
+        &state.config.encryption_key,
+        chrono::Utc::now(),
+    ) {
+        Ok(secrets) => secrets,
+        Err(e) => {
+            // Don't send unsigned requests for an action that is meant to be signed
+            let error = format!("Failed to decrypt signing secret: {e}");
+            let _ = db::action_queue::mark_failed(
+                &state.pool,
+                item.id,
+                item.attempts,
+                item.max_attempts,
+                &error,
+            )
+            .await;
+            let _ = db::action_log::create(
+                &state.pool,
+                item.action_id,
+                item.submission_id,
+                "failed",
+                Some(&json!({ "error": &error })),
+            )
+            .await;
+            return Ok(true);
+        }
+    };
+
     let ctx = ActionContext {
         submission,
         endpoint,
         project,
         tenant,
+        signing_secrets,
     };
 
     let module = state.modules.get(&action.action_type);
     let (status, response) = if let Some(module) = module {
-        match tokio::time::timeout(
-            std::time::Duration::from_secs(30),
-            module.execute(&ctx, &action.config),
-        )
-        .await
-        {
-            Ok(Ok(result)) => {
-                let status_str = match result.status {
-                    ActionStatus::Success => "success",
-                    ActionStatus::Failed => "failed",
-                };
-                (status_str, result.response)
-            }
-            Ok(Err(e)) => ("failed", Some(json!({ "error": e.message }))),
-            Err(_) => ("failed", Some(json!({ "error": "Action timed out after 30s" }))),
+        let config = secrets::open(
+            &action.config,
+            module.secret_fields(),
+            &state.config.encryption_key,
+        );
+        match config {
+            Err(e) => (
+                "failed",
+                Some(json!({ "error": format!("Failed to decrypt action config: {e}") })),
+            ),
+            Ok(config) => match tokio::time::timeout(
+                std::time::Duration::from_secs(30),
+                module.execute(&ctx, &config),
+            )
+            .await
+            {
+                Ok(Ok(result)) => {
+                    let status_str = match result.status {
+                        ActionStatus::Success => "success",
+                        ActionStatus::Failed => "failed",
+                    };
+                    (status_str, result.response)
+                }
+                Ok(Err(e)) => ("failed", Some(json!({ "error": e.message }))),
+                Err(_) => ("failed", Some(json!({ "error": "Action timed out after 30s" }))),
+            },
         }
     } else {
         (
@@ -2,6 +2,7 @@ mod common;
 
 use reqwest::StatusCode;
 use serde_json::json;
+use webhooker::config::RateLimitBackend;
 
 // ── Health ──────────────────────────────────────────────────────
 
@@ -493,6 +494,1965 @@ async fn field_sorting_data_and_extras() {
     common::cleanup(app).await;
 }
 
+#[tokio::test]
+async fn store_metadata_disabled_skips_metadata() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "Form",
+            "form",
+            None,
+            Some(json!({ "store_metadata": false })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let (body, status) = app.submit_json(endpoint_id, &json!({ "name": "Alice" })).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let sub_id = body["submission_id"].as_str().unwrap();
+
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{sub_id}"), &token)
+        .await;
+    assert_eq!(sub["metadata"], json!({}));
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn redaction_rules_applied_before_storage() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let project_id = project["id"].as_str().unwrap();
+
+    // Invalid rules are rejected up front
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/projects/{project_id}/endpoints"),
+            &token,
+            &json!({ "name": "Bad", "settings": { "redaction": [{ "field": "x", "action": "shred" }] } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/projects/{project_id}/endpoints"),
+            &token,
+            &json!({
+                "name": "Bad",
+                "settings": { "redaction": [{ "field": "[]", "action": "drop" }] }
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let settings = json!({
+        "redaction": [
+            { "field": "ssn", "action": "drop" },
+            { "field": "card", "action": "mask", "keep_last": 4 },
+            { "field": "email", "action": "hash", "targets": ["data", "raw"] },
+            { "field": "ip", "action": "truncate_ip", "targets": ["metadata"] },
+            { "field": "contact[email]", "action": "drop" },
+            { "field": "contact.phone", "action": "mask", "keep_last": 2 },
+            { "field": "items.ssn", "action": "drop" },
+            { "field": "items.0.card", "action": "mask" }
+        ]
+    });
+    let endpoint = app
+        .create_endpoint(&token, project_id, "Form", "form", None, Some(settings))
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let (body, status) = app
+        .submit_json(
+            endpoint_id,
+            &json!({ "ssn": "123-45-6789", "card": "4111111111111111", "email": "a@b.com" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let sub_id = body["submission_id"].as_str().unwrap();
+
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{sub_id}"), &token)
+        .await;
+    assert!(sub["data"].get("ssn").is_none());
+    assert!(sub["raw"].get("ssn").is_none());
+    assert_eq!(sub["data"]["card"], "************1111");
+    assert_eq!(sub["raw"]["card"], "************1111");
+    let hashed = sub["data"]["email"].as_str().unwrap();
+    assert_eq!(hashed.len(), 64);
+    // Keyed with a subkey derived from the instance key, not the key itself
+    use hmac::{Hmac, Mac};
+    let key = b"test-encryption-key-32-chars-ok!";
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(key).unwrap();
+    mac.update(b"redaction");
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(&mac.finalize().into_bytes()).unwrap();
+    mac.update(b"a@b.com");
+    assert_eq!(hashed, hex::encode(mac.finalize().into_bytes()));
+    assert_eq!(sub["raw"]["email"], hashed);
+    assert_eq!(sub["metadata"]["ip"], "127.0.0.0");
+
+    // Nested values, as bracketed form keys produce, are reached by path
+    let (body, status) = app
+        .submit_form(
+            endpoint_id,
+            &[
+                ("contact[email]", "a@b.com"),
+                ("contact[phone]", "5551234"),
+                ("contact[name]", "Ada"),
+                ("items[][ssn]", "1"),
+                ("items[][card]", "4111111111111111"),
+                ("items[][ssn]", "2"),
+                ("items[][card]", "5500000000000004"),
+            ],
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let sub_id = body["submission_id"].as_str().unwrap();
+    let (sub, _) = app.get_auth(&format!("/api/v1/submissions/{sub_id}"), &token).await;
+    for target in ["data", "raw"] {
+        assert_eq!(sub[target]["contact"], json!({ "phone": "*****34", "name": "Ada" }));
+        assert_eq!(
+            sub[target]["items"],
+            json!([{ "card": "************1111" }, { "card": "5500000000000004" }])
+        );
+    }
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn multipart_file_upload_and_download() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "Form",
+            "form",
+            None,
+            Some(json!({
+                "file_uploads": { "enabled": true, "max_file_size": 64, "allowed_types": ["text/*"] }
+            })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let part = |filename: &str, ct: &str, content: &str| {
+        format!(
+            "--XB\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nAlice\r\n\
+             --XB\r\nContent-Disposition: form-data; name=\"resume\"; filename=\"{filename}\"\r\n\
+             Content-Type: {ct}\r\n\r\n{content}\r\n--XB--\r\n"
+        )
+    };
+
+    // Disallowed type and oversized file are rejected
+    let (_, status) = app
+        .submit_multipart(endpoint_id, "XB", part("a.pdf", "application/pdf", "%PDF"))
+        .await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let (_, status) = app
+        .submit_multipart(endpoint_id, "XB", part("a.txt", "text/plain", &"x".repeat(65)))
+        .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let (body, status) = app
+        .submit_multipart(endpoint_id, "XB", part("../cv.txt", "text/plain", "hello\x00world"))
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let sub_id = body["submission_id"].as_str().unwrap();
+
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{sub_id}"), &token)
+        .await;
+    assert_eq!(sub["data"]["name"], "Alice");
+    assert_eq!(sub["data"]["resume"]["filename"], "cv.txt");
+    let file_id = sub["data"]["resume"]["file_id"].as_str().unwrap();
+
+    let (files, _) = app
+        .get_auth(&format!("/api/v1/submissions/{sub_id}/files"), &token)
+        .await;
+    assert_eq!(files[0]["id"], file_id);
+
+    let resp = app
+        .client
+        .get(app.url(&format!("/api/v1/files/{file_id}")))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.headers()["content-disposition"].to_str().unwrap().starts_with("attachment"));
+    assert_eq!(resp.bytes().await.unwrap().as_ref(), b"hello\x00world");
+
+    // Downloads require auth
+    let resp = app
+        .client
+        .get(app.url(&format!("/api/v1/files/{file_id}")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Uploads are rejected on endpoints that haven't enabled them
+    let plain = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Plain", "plain", None, None)
+        .await;
+    let (_, status) = app
+        .submit_multipart(plain["id"].as_str().unwrap(), "XB", part("a.txt", "text/plain", "hi"))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn field_validation_warn_and_reject_modes() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let fields = json!([
+        { "name": "email", "type": "email", "required": true },
+        { "name": "age", "type": "number" }
+    ]);
+    let endpoint = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Signup", "signup", Some(fields.clone()), None)
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    // Warn mode (default): stored, with warnings attached
+    let (body, status) = app
+        .submit_json(endpoint_id, &json!({ "email": "nope", "age": "old" }))
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let sub_id = body["submission_id"].as_str().unwrap();
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{sub_id}"), &token)
+        .await;
+    assert_eq!(
+        sub["validation_warnings"],
+        json!({ "age": ["Invalid number format"], "email": ["Invalid email format"] })
+    );
+
+    let resp = app
+        .client
+        .get(app.url(&format!("/htmx/submissions/{endpoint_id}")))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.text().await.unwrap().contains("2 warnings"));
+
+    // Unknown modes are rejected
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "Signup", "fields": fields, "settings": { "validation": "strict" } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "Signup", "fields": fields, "settings": { "validation": "reject" } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Reject mode: JSON clients get a per-field error map
+    let (body, status) = app.submit_json(endpoint_id, &json!({ "age": "old" })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"], "Validation failed");
+    assert_eq!(body["errors"]["email"], json!(["This field is required"]));
+    assert_eq!(body["errors"]["age"], json!(["Invalid number format"]));
+
+    // Form posts are sent back to the referring page
+    let resp = app
+        .client
+        .post(app.url(&format!("/v1/e/{endpoint_id}")))
+        .header("referer", "https://example.com/signup?plan=pro")
+        .form(&[("email", "nope")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+    let location = reqwest::Url::parse(resp.headers()["location"].to_str().unwrap()).unwrap();
+    assert_eq!(location.path(), "/signup");
+    let params: std::collections::HashMap<_, _> = location.query_pairs().into_owned().collect();
+    assert_eq!(params["plan"], "pro");
+    let errors: serde_json::Value = serde_json::from_str(&params["errors"]).unwrap();
+    assert_eq!(errors, json!({ "email": ["Invalid email format"] }));
+
+    // Without a usable page to return to, forms get the 422 too
+    let (_, status) = app.submit_form(endpoint_id, &[("email", "nope")]).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (_, status) = app
+        .submit_json(endpoint_id, &json!({ "email": "a@example.com", "age": 30 }))
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    assert_eq!(list["total"], 2);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn field_constraints_are_enforced() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let project_id = project["id"].as_str().unwrap();
+
+    // Definitions are checked on save
+    for bad in [
+        json!([{ "name": "plan", "type": "select" }]),
+        json!([{ "name": "code", "type": "text", "pattern": "(" }]),
+        json!([{ "name": "qty", "type": "number", "min": 5, "max": 1 }]),
+        json!([{ "name": "a" }, { "name": "a" }]),
+        json!([{ "name": "colour", "type": "colour" }]),
+    ] {
+        let (body, status) = app
+            .post_auth(
+                &format!("/api/v1/projects/{project_id}/endpoints"),
+                &token,
+                &json!({ "name": "Bad", "fields": bad }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    }
+
+    let fields = json!([
+        { "name": "name", "type": "text", "min_length": 2, "max_length": 5, "pattern": "[A-Za-z]+" },
+        { "name": "qty", "type": "number", "min": 1, "max": 10 },
+        { "name": "plan", "type": "select", "options": ["free", "pro"] },
+        { "name": "tags", "type": "select", "options": ["a", "b"], "multiple": true },
+        { "name": "phone", "type": "phone" },
+        { "name": "email", "type": "email" },
+        { "name": "site", "type": "url" },
+        { "name": "start", "type": "date", "min": "2025-01-01" },
+        { "name": "at", "type": "datetime", "max": "2030-01-01T00:00:00Z" }
+    ]);
+    let endpoint = app
+        .create_endpoint(&token, project_id, "Order", "order", Some(fields), Some(json!({ "validation": "reject" })))
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let (body, status) = app
+        .submit_json(
+            endpoint_id,
+            &json!({
+                "name": "J", "qty": 11, "plan": ["free"], "tags": ["a", "c"],
+                "phone": "555-1234", "email": "jane@@example.com", "site": "ftp://example.com",
+                "start": "2024-12-31", "at": "2031-01-01T00:00:00Z"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        body["errors"],
+        json!({
+            "name": ["Must be at least 2 characters"],
+            "qty": ["Must be at most 10"],
+            "plan": ["Only one value is allowed"],
+            "tags": ["Must be one of: a, b"],
+            "phone": ["Invalid phone number format (use E.164, e.g. +14155550123)"],
+            "email": ["Invalid email format"],
+            "site": ["Invalid URL format"],
+            "start": ["Must be on or after 2025-01-01"],
+            "at": ["Must be on or before 2030-01-01T00:00:00Z"]
+        })
+    );
+
+    let (body, status) = app
+        .submit_json(
+            endpoint_id,
+            &json!({
+                "name": "Jane", "qty": "3", "plan": "pro", "tags": ["a", "b"],
+                "phone": "+1 (415) 555-0123", "email": "jane.doe+tag@mail.example.co.uk",
+                "site": "https://example.com/x", "start": "2025-06-01", "at": "2029-05-01T10:00"
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+
+    // The snippet's form carries the constraints
+    let resp = app
+        .client
+        .get(app.url(&format!("/endpoints/{endpoint_id}/snippet")))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    let html = resp.text().await.unwrap();
+    assert!(html.contains("minlength=&#34;2&#34; maxlength=&#34;5&#34;"), "{html}");
+    assert!(html.contains("type=&#34;tel&#34;"));
+    assert!(html.contains("&#60;option value=&#34;pro&#34;&#62;pro&#60;/option&#62;"));
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn form_values_coerced_to_field_types() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+
+    let fields = json!([
+        { "name": "qty", "type": "number" },
+        { "name": "price", "type": "number" },
+        { "name": "agree", "type": "checkbox" },
+        { "name": "start", "type": "date" },
+        { "name": "at", "type": "datetime" },
+        { "name": "tags", "type": "text", "multiple": true },
+        { "name": "zip", "type": "text" },
+        { "name": "age", "type": "number" }
+    ]);
+    let endpoint = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Form", "form", Some(fields), None)
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let (body, status) = app
+        .submit_form(
+            endpoint_id,
+            &[
+                ("qty", "42"),
+                ("price", "9.5"),
+                ("agree", "on"),
+                ("start", "2025-03-01"),
+                ("at", "2025-03-01T09:30"),
+                ("tags", "new"),
+                ("zip", "02134"),
+                ("age", "old"),
+                ("other", "7"),
+            ],
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let sub_id = body["submission_id"].as_str().unwrap();
+
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{sub_id}"), &token)
+        .await;
+    assert_eq!(
+        sub["data"],
+        json!({
+            "qty": 42,
+            "price": 9.5,
+            "agree": true,
+            "start": "2025-03-01",
+            "at": "2025-03-01T09:30:00Z",
+            "tags": ["new"],
+            "zip": "02134",
+            "age": "old"
+        })
+    );
+    // Undefined fields and the raw body keep the strings as sent
+    assert_eq!(sub["extras"]["other"], "7");
+    assert_eq!(sub["raw"]["qty"], "42");
+    assert_eq!(sub["raw"]["agree"], "on");
+    // Values that don't parse are left for validation to flag
+    assert_eq!(sub["validation_warnings"]["age"], json!(["Invalid number format"]));
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn form_repeated_keys_and_bracket_notation() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let project_id = project["id"].as_str().unwrap();
+
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project_id,
+            "Form",
+            "form",
+            None,
+            Some(json!({ "file_uploads": { "enabled": true } })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let pairs = [
+        ("tags", "a"),
+        ("tags", "b"),
+        ("address[city]", "Oslo"),
+        ("address[geo][lat]", "59.9"),
+        ("items[]", "x"),
+        ("items[]", "y"),
+        ("rows[][sku]", "A1"),
+        ("rows[][qty]", "2"),
+        ("rows[][sku]", "B2"),
+        ("weird[", "kept"),
+    ];
+    let (body, status) = app.submit_form(endpoint_id, &pairs).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{}", body["submission_id"].as_str().unwrap()), &token)
+        .await;
+    assert_eq!(
+        sub["data"],
+        json!({
+            "tags": ["a", "b"],
+            "address": { "city": "Oslo", "geo": { "lat": "59.9" } },
+            "items": ["x", "y"],
+            "rows": [{ "sku": "A1", "qty": "2" }, { "sku": "B2" }],
+            "weird[": "kept"
+        })
+    );
+
+    // Multipart nests the same way, file parts included
+    let body = "--XB\r\nContent-Disposition: form-data; name=\"contact[name]\"\r\n\r\nAlice\r\n\
+                --XB\r\nContent-Disposition: form-data; name=\"tags\"\r\n\r\na\r\n\
+                --XB\r\nContent-Disposition: form-data; name=\"tags\"\r\n\r\nb\r\n\
+                --XB\r\nContent-Disposition: form-data; name=\"docs[]\"; filename=\"a.txt\"\r\n\
+                Content-Type: text/plain\r\n\r\nhi\r\n--XB--\r\n";
+    let (body, status) = app.submit_multipart(endpoint_id, "XB", body.to_string()).await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{}", body["submission_id"].as_str().unwrap()), &token)
+        .await;
+    assert_eq!(sub["data"]["contact"], json!({ "name": "Alice" }));
+    assert_eq!(sub["data"]["tags"], json!(["a", "b"]));
+    assert_eq!(sub["data"]["docs"][0]["filename"], "a.txt");
+
+    // Opting out keeps the old flat keys
+    let flat = app
+        .create_endpoint(&token, project_id, "Flat", "flat", None, Some(json!({ "form_keys": "flat" })))
+        .await;
+    let (body, status) = app.submit_form(flat["id"].as_str().unwrap(), &pairs).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{}", body["submission_id"].as_str().unwrap()), &token)
+        .await;
+    assert_eq!(sub["data"]["tags"], "b");
+    assert_eq!(sub["data"]["address[city]"], "Oslo");
+
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/projects/{project_id}/endpoints"),
+            &token,
+            &json!({ "name": "Bad", "settings": { "form_keys": "deep" } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn xml_text_and_ndjson_ingest() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let project_id = project["id"].as_str().unwrap();
+    let endpoint = app
+        .create_endpoint(&token, project_id, "Feed", "feed", None, None)
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let post = |ct: &'static str, body: &'static str| {
+        app.client
+            .post(app.url(&format!("/v1/e/{endpoint_id}")))
+            .header("content-type", ct)
+            .body(body)
+            .send()
+    };
+
+    // XML: attributes under @attr, repeated elements as arrays, namespaces dropped
+    let resp = post(
+        "application/soap+xml; charset=utf-8",
+        r#"<?xml version="1.0"?>
+        <s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope">
+          <s:Body>
+            <Order id="42" currency="EUR"><Item>A</Item><Item>B</Item><Note lang="en">Rush</Note></Order>
+          </s:Body>
+        </s:Envelope>"#,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{}", body["submission_id"].as_str().unwrap()), &token)
+        .await;
+    assert_eq!(
+        sub["data"]["Envelope"]["Body"]["Order"],
+        json!({
+            "@attr": { "id": "42", "currency": "EUR" },
+            "Item": ["A", "B"],
+            "Note": { "@attr": { "lang": "en" }, "#text": "Rush" }
+        })
+    );
+
+    let resp = post("application/xml", "<a><b>").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // Plain text is stored under _body
+    let resp = post("text/plain", "temp=21.5").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{}", body["submission_id"].as_str().unwrap()), &token)
+        .await;
+    assert_eq!(sub["data"], json!({ "_body": "temp=21.5" }));
+
+    // NDJSON: one submission per line
+    let resp = post("application/x-ndjson", "{\"t\":1}\n\n{\"t\":2}\n").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[1]["line"], 2);
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{}", results[1]["submission_id"].as_str().unwrap()), &token)
+        .await;
+    assert_eq!(sub["data"]["t"], 2);
+
+    let resp = post("application/x-ndjson", "{\"t\":1}\nnope\n").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("line 2"));
+
+    let resp = post("application/octet-stream", "\u{1}").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    // Lines failing validation are reported without losing the others
+    let strict = app
+        .create_endpoint(
+            &token,
+            project_id,
+            "Strict",
+            "strict",
+            Some(json!([{ "name": "t", "type": "number", "required": true }])),
+            Some(json!({ "validation": "reject", "content_types": ["ndjson"] })),
+        )
+        .await;
+    let strict_url = app.url(&format!("/v1/e/{}", strict["id"].as_str().unwrap()));
+    let resp = app
+        .client
+        .post(&strict_url)
+        .header("content-type", "application/x-ndjson")
+        .body("{\"t\":1}\n{\"t\":\"x\"}\n")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::MULTI_STATUS);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert!(body["results"][0]["submission_id"].is_string());
+    assert_eq!(body["results"][1]["errors"]["t"], json!(["Invalid number format"]));
+
+    // Formats the endpoint doesn't list are refused
+    let resp = app.client.post(&strict_url).json(&json!({ "t": 1 })).send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/projects/{project_id}/endpoints"),
+            &token,
+            &json!({ "name": "Bad", "settings": { "content_types": ["yaml"] } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn batch_ingest_stores_items_in_one_request() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "Backfill",
+            "backfill",
+            Some(json!([{ "name": "n", "type": "number", "required": true }])),
+            Some(json!({ "validation": "reject", "rate_limit": 1, "batch_max_items": 4 })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    let (receiver_url, _) = common::spawn_receiver().await;
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+            &token,
+            &json!({ "action_type": "webhook", "config": { "url": format!("{receiver_url}/hook") } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let batch_url = app.url(&format!("/v1/e/{endpoint_id}/batch"));
+    let post = |body: serde_json::Value| app.client.post(&batch_url).json(&body).send();
+
+    // Over the per-endpoint cap
+    let resp = post(json!([{ "n": 1 }, { "n": 2 }, { "n": 3 }, { "n": 4 }, { "n": 5 }]))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    // One bad item doesn't stop the rest; the batch counts as one request
+    let resp = post(json!([{ "n": 1 }, { "n": "x" }, { "n": 3 }])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::MULTI_STATUS);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert!(results[0]["submission_id"].is_string());
+    assert_eq!(results[1]["index"], 1);
+    assert_eq!(results[1]["errors"]["n"], json!(["Invalid number format"]));
+    assert!(results[2]["submission_id"].is_string());
+
+    let queued: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM action_queue q JOIN submissions s ON s.id = q.submission_id
+         WHERE s.endpoint_id = $1",
+    )
+    .bind(endpoint_id.parse::<uuid::Uuid>().unwrap())
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(queued, 2);
+
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    assert_eq!(list["total"], 2);
+
+    // The rate limit (1 per window) is now spent
+    let resp = post(json!([{ "n": 4 }])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let resp = post(json!({ "n": 1 })).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn get_ingestion_and_tracking_pixel() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let project_id = project["id"].as_str().unwrap();
+
+    // Off by default
+    let plain = app
+        .create_endpoint(&token, project_id, "Plain", "plain", None, None)
+        .await;
+    let resp = app
+        .client
+        .get(app.url(&format!("/v1/e/{}?a=1", plain["id"].as_str().unwrap())))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project_id,
+            "Beacon",
+            "beacon",
+            None,
+            Some(json!({
+                "get_ingest": { "enabled": true },
+                "honeypot_field": "_gotcha",
+                "rate_limit": 3
+            })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    let get = |query: &str| app.client.get(app.url(&format!("/v1/e/{endpoint_id}?{query}"))).send();
+
+    let resp = get("campaign=spring&tags=a&tags=b").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{}", body["submission_id"].as_str().unwrap()), &token)
+        .await;
+    assert_eq!(sub["data"], json!({ "campaign": "spring", "tags": ["a", "b"] }));
+
+    // Honeypot applies
+    let resp = get("campaign=x&_gotcha=bot").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    assert_eq!(list["total"], 1);
+
+    // Pixel mode answers with a GIF
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({
+                "name": "Beacon",
+                "settings": { "get_ingest": { "enabled": true, "pixel": true }, "rate_limit": 3 }
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let resp = get("open=1").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "image/gif");
+    assert!(resp.bytes().await.unwrap().starts_with(b"GIF89a"));
+
+    // Rate limiting applies (3 per window, all used)
+    let resp = get("open=2").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // A query string can't be signed: refused on save, and GETs to a signed
+    // endpoint saved before that are a 405 that doesn't count as a bad signature
+    let signature = json!({ "scheme": "github", "secret": "gh-secret" });
+    let (body, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({
+                "name": "Beacon",
+                "settings": { "get_ingest": { "enabled": true }, "signature": signature }
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("verifies signatures"));
+    sqlx::query(
+        "UPDATE endpoints SET settings = settings || jsonb_build_object('signature', $2::jsonb)
+         WHERE id = $1",
+    )
+    .bind(endpoint_id.parse::<uuid::Uuid>().unwrap())
+    .bind(&signature)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let resp = get("open=3").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    let failures: i64 = sqlx::query_scalar("SELECT signature_failures FROM endpoints WHERE id = $1")
+        .bind(endpoint_id.parse::<uuid::Uuid>().unwrap())
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(failures, 0);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn idempotency_keys_suppress_repeats() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let project_id = project["id"].as_str().unwrap();
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project_id,
+            "Orders",
+            "orders",
+            None,
+            Some(json!({ "idempotency": { "enabled": true } })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    let (receiver_url, _) = common::spawn_receiver().await;
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+            &token,
+            &json!({ "action_type": "webhook", "config": { "url": format!("{receiver_url}/hook") } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let url = app.url(&format!("/v1/e/{endpoint_id}"));
+    let send = |key: &'static str, body: serde_json::Value| {
+        app.client
+            .post(&url)
+            .header("Idempotency-Key", key)
+            .json(&body)
+            .send()
+    };
+
+    let resp = send("order-1", json!({ "n": 1 })).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let first: serde_json::Value = resp.json().await.unwrap();
+
+    // A repeat returns the original, even with a different body
+    let resp = send("order-1", json!({ "n": 2 })).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let repeat: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(repeat["status"], "duplicate");
+    assert_eq!(repeat["submission_id"], first["submission_id"]);
+
+    let resp = send("order-2", json!({ "n": 3 })).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    // No key, no deduplication
+    for _ in 0..2 {
+        let resp = app.client.post(&url).json(&json!({ "n": 4 })).send().await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+    }
+
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    assert_eq!(list["total"], 4);
+    let queued: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM action_queue q JOIN submissions s ON s.id = q.submission_id
+         WHERE s.endpoint_id = $1",
+    )
+    .bind(endpoint_id.parse::<uuid::Uuid>().unwrap())
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(queued, 4);
+
+    // Once the window has passed, the key is free again
+    sqlx::query("UPDATE idempotency_keys SET expires_at = now() WHERE idempotency_key = 'order-1'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let resp = send("order-1", json!({ "n": 5 })).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let renewed: serde_json::Value = resp.json().await.unwrap();
+    assert_ne!(renewed["submission_id"], first["submission_id"]);
+
+    // Keyed by a body path; batch items are deduplicated against each other too
+    let keyed = app
+        .create_endpoint(
+            &token,
+            project_id,
+            "Events",
+            "events",
+            None,
+            Some(json!({ "idempotency": { "enabled": true, "field": "event.id" } })),
+        )
+        .await;
+    let keyed_id = keyed["id"].as_str().unwrap();
+    let resp = app
+        .client
+        .post(app.url(&format!("/v1/e/{keyed_id}/batch")))
+        .json(&json!([
+            { "event": { "id": "evt_1" } },
+            { "event": { "id": "evt_2" } },
+            { "event": { "id": "evt_1" } },
+        ]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results[2]["duplicate"], true);
+    assert_eq!(results[2]["submission_id"], results[0]["submission_id"]);
+    assert!(results[1]["duplicate"].is_null());
+
+    let resp = app
+        .client
+        .post(app.url(&format!("/v1/e/{keyed_id}")))
+        .json(&json!({ "event": { "id": "evt_2" } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Settings are checked on save
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{keyed_id}"),
+            &token,
+            &json!({ "name": "Events", "settings": { "idempotency": { "enabled": true, "window_secs": 0 } } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn content_duplicates_flagged_and_dropped() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let settings = json!({
+        "duplicates": { "enabled": true, "fields": ["email", "message"], "action": "skip_actions" }
+    });
+    let endpoint = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Contact", "contact", None, Some(settings))
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    let (receiver_url, _) = common::spawn_receiver().await;
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+            &token,
+            &json!({ "action_type": "webhook", "config": { "url": format!("{receiver_url}/hook") } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Fields outside the compared set don't matter
+    for form in [
+        &[("email", "a@example.com"), ("message", "buy now"), ("n", "1")][..],
+        &[("email", "a@example.com"), ("message", "buy now"), ("n", "2")],
+        &[("email", "a@example.com"), ("message", "hello")],
+    ] {
+        let (_, status) = app.submit_form(endpoint_id, form).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (dupes, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions?duplicate=true"), &token)
+        .await;
+    assert_eq!(dupes["total"], 1);
+    assert_eq!(dupes["submissions"][0]["data"]["n"], "2");
+
+    // Keyed with a subkey derived from the instance key, not the key itself
+    use hmac::{Hmac, Mac};
+    let id: uuid::Uuid = dupes["submissions"][0]["id"].as_str().unwrap().parse().unwrap();
+    let stored: String = sqlx::query_scalar("SELECT content_hash FROM submissions WHERE id = $1")
+        .bind(id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    let key = b"test-encryption-key-32-chars-ok!";
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(key).unwrap();
+    mac.update(b"duplicates");
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(&mac.finalize().into_bytes()).unwrap();
+    mac.update(br#"{"email":"a@example.com","message":"buy now"}"#);
+    assert_eq!(stored, hex::encode(mac.finalize().into_bytes()));
+
+    let (originals, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions?duplicate=false"), &token)
+        .await;
+    assert_eq!(originals["total"], 2);
+
+    // The duplicate's actions are logged as skipped, not queued
+    let id: uuid::Uuid = dupes["submissions"][0]["id"].as_str().unwrap().parse().unwrap();
+    let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM action_queue WHERE submission_id = $1")
+        .bind(id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(queued, 0);
+    let skipped: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM action_log WHERE submission_id = $1 AND status = 'skipped'",
+    )
+    .bind(id)
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(skipped, 1);
+
+    // Dropped duplicates look like a success but aren't stored
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "Contact", "settings": { "duplicates": { "enabled": true, "action": "drop" } } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (body, status) = app
+        .submit_json(endpoint_id, &json!({ "email": "a@example.com", "message": "hello" }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    assert_eq!(list["total"], 3);
+
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "Contact", "settings": { "duplicates": { "action": "ignore" } } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn spam_scoring_quarantines_and_drops() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let settings = json!({
+        "spam": {
+            // Tokens count whole seconds, so 2 keeps "too fast" clear of a second boundary
+            "time_to_submit": { "min_secs": 2, "score": 5 },
+            "links": { "max": 1, "score": 3 },
+            "keywords": { "words": ["Casino"], "patterns": ["(?i)seo\\s+services"] },
+            "disposable_email": { "score": 5 },
+            "quarantine_at": 5,
+            "drop_at": 8
+        }
+    });
+    let endpoint = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Contact", "contact", None, Some(settings))
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    let (receiver_url, _) = common::spawn_receiver().await;
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+            &token,
+            &json!({ "action_type": "webhook", "config": { "url": format!("{receiver_url}/hook") } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let resp = app
+        .client
+        .get(app.url(&format!("/v1/e/{endpoint_id}/form-token")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let issued: serde_json::Value = resp.json().await.unwrap();
+    let form_token = issued["token"].as_str().unwrap().to_string();
+
+    // Too fast: the time check alone quarantines
+    let (fast, status) = app
+        .submit_form(endpoint_id, &[("message", "hi"), ("_wh_ts", &form_token)])
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    tokio::time::sleep(std::time::Duration::from_millis(2100)).await;
+    let (clean, status) = app
+        .submit_form(endpoint_id, &[("message", "hi, see https://example.com"), ("_wh_ts", &form_token)])
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{}", clean["submission_id"].as_str().unwrap()), &token)
+        .await;
+    assert_eq!(sub["quarantined"], false);
+    assert_eq!(sub["metadata"]["spam"]["score"], 0);
+    assert_eq!(sub["metadata"]["spam"]["verdict"], "accept");
+    assert!(sub["raw"].get("_wh_ts").is_none());
+
+    // A tampered token counts as invalid
+    let (_, status) = app
+        .submit_form(endpoint_id, &[("message", "hi"), ("_wh_ts", "1.00ff")])
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (_, status) = app
+        .submit_json(endpoint_id, &json!({ "message": "Best CASINO", "_wh_ts": form_token }))
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Keyword (5) + links (3) reaches drop_at: looks accepted, isn't stored
+    let (dropped, status) = app
+        .submit_json(
+            endpoint_id,
+            &json!({ "message": "Cheap SEO  services http://a.test http://b.test", "_wh_ts": form_token }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(dropped["status"], "ok");
+
+    let (quarantined, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions?quarantined=true"), &token)
+        .await;
+    assert_eq!(quarantined["total"], 3);
+    let (all, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    assert_eq!(all["total"], 4);
+
+    let fast_id: uuid::Uuid = fast["submission_id"].as_str().unwrap().parse().unwrap();
+    let (sub, _) = app.get_auth(&format!("/api/v1/submissions/{fast_id}"), &token).await;
+    assert_eq!(sub["metadata"]["spam"]["checks"][0]["check"], "time_to_submit");
+    let queued: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM action_queue q JOIN submissions s ON s.id = q.submission_id
+         WHERE s.endpoint_id = $1",
+    )
+    .bind(endpoint_id.parse::<uuid::Uuid>().unwrap())
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(queued, 1);
+
+    // The tenant blocklist applies to every endpoint
+    let (_, status) = app
+        .put_auth("/api/v1/tenant/ip-blocklist", &token, &json!({ "entries": ["not an ip"] }))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (list, status) = app
+        .put_auth("/api/v1/tenant/ip-blocklist", &token, &json!({ "entries": ["127.0.0.1", "10.1.2.3/8"] }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["entries"], json!(["127.0.0.1/32", "10.0.0.0/8"]));
+    let other = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Other", "other", None, None)
+        .await;
+    let (body, status) = app
+        .submit_json(other["id"].as_str().unwrap(), &json!({ "message": "hi" }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "Contact", "settings": { "spam": { "keywords": { "patterns": ["("] } } } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn captcha_verified_before_storing() {
+    let (verify_url, received) = common::spawn_captcha_verifier("good-token").await;
+    let app = common::spawn_app_with(|config| config.captcha_verify_url = Some(verify_url)).await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "Contact",
+            "contact",
+            None,
+            Some(json!({ "captcha": { "provider": "turnstile", "secret": "captcha-secret" } })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    assert!(endpoint["settings"]["captcha"].get("secret").is_none());
+    assert!(endpoint["settings"]["captcha"]["secret_enc"].is_string());
+
+    let (_, status) = app.submit_form(endpoint_id, &[("message", "hi")]).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (_, status) = app
+        .submit_form(endpoint_id, &[("message", "hi"), ("cf-turnstile-response", "bad-token")])
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (body, status) = app
+        .submit_form(endpoint_id, &[("message", "hi"), ("cf-turnstile-response", "good-token")])
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (sub, _) = app
+        .get_auth(&format!("/api/v1/submissions/{}", body["submission_id"].as_str().unwrap()), &token)
+        .await;
+    assert_eq!(sub["data"]["message"], "hi");
+    assert!(sub["data"].get("cf-turnstile-response").is_none());
+    assert!(sub["extras"].get("cf-turnstile-response").is_none());
+    assert!(sub["raw"].get("cf-turnstile-response").is_none());
+
+    // Only the two requests that carried a token reached the provider
+    let calls = received.lock().unwrap().clone();
+    assert_eq!(calls.len(), 2);
+    assert!(calls[1].contains("secret=captcha-secret"));
+    assert!(calls[1].contains("remoteip=127.0.0.1"));
+
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    assert_eq!(list["total"], 1);
+
+    let resp = app
+        .client
+        .post(app.url(&format!("/v1/e/{endpoint_id}/batch")))
+        .json(&json!([{ "message": "hi" }]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/projects/{}/endpoints", project["id"].as_str().unwrap()),
+            &token,
+            &json!({ "name": "Other", "slug": "other", "settings": { "captcha": { "provider": "hcaptcha" } } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn captcha_fails_closed_when_provider_unreachable() {
+    let app = common::spawn_app_with(|config| {
+        config.captcha_verify_url = Some("http://127.0.0.1:1/siteverify".to_string())
+    })
+    .await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "Contact",
+            "contact",
+            None,
+            Some(json!({ "captcha": { "provider": "recaptcha", "secret": "captcha-secret" } })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let (_, status) = app
+        .submit_form(endpoint_id, &[("message", "hi"), ("g-recaptcha-response", "any-token")])
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM submissions")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn tenant_quotas_enforced_and_warned() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let (tenant, _) = app.get_auth("/api/v1/tenant", &token).await;
+    let tenant_id = tenant["id"].as_str().unwrap().to_string();
+    let quotas_path = format!("/api/v1/admin/tenants/{tenant_id}/quotas");
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Form", "form", None, None)
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let (_, status) = app
+        .put_auth(&quotas_path, &token, &json!({ "daily_submissions": 0 }))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (updated, status) = app
+        .put_auth(&quotas_path, &token, &json!({ "daily_submissions": 5 }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["quota_daily_submissions"], 5);
+
+    for n in 0..5 {
+        let (_, status) = app.submit_json(endpoint_id, &json!({ "n": n })).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+    let (body, status) = app.submit_json(endpoint_id, &json!({ "n": 5 })).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["error"], "Daily submission quota reached");
+
+    // One warning at 80% (the 4th) and one at 100% (the 5th), raised in the background
+    let mut warnings: Vec<(String, i16)> = Vec::new();
+    for _ in 0..50 {
+        warnings = sqlx::query_as("SELECT quota, percent FROM tenant_quota_warnings ORDER BY percent")
+            .fetch_all(&app.pool)
+            .await
+            .unwrap();
+        if warnings.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert_eq!(
+        warnings,
+        vec![("daily_submissions".to_string(), 80), ("daily_submissions".to_string(), 100)]
+    );
+
+    let (usage, _) = app.get_auth(&format!("/api/v1/admin/tenants/{tenant_id}"), &token).await;
+    assert_eq!(usage["tenant"]["submissions_today"], 5);
+    assert_eq!(usage["tenant"]["stored_submissions"], 5);
+    assert!(usage["tenant"]["stored_bytes"].as_i64().unwrap() > 0);
+
+    let resp = app.client.get(app.url("/admin/tenants")).bearer_auth(&token).send().await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let page = resp.text().await.unwrap();
+    assert!(page.contains("5 / 5"));
+    assert!(page.contains("Over quota"));
+
+    // A batch that doesn't fit is refused whole
+    app.put_auth(&quotas_path, &token, &json!({ "daily_submissions": 7 })).await;
+    let resp = app
+        .client
+        .post(app.url(&format!("/v1/e/{endpoint_id}/batch")))
+        .json(&json!([{ "n": 1 }, { "n": 2 }, { "n": 3 }]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Stored totals answer 507, and deleting makes room right away
+    app.put_auth(&quotas_path, &token, &json!({ "stored_submissions": 5 })).await;
+    let (_, status) = app.submit_json(endpoint_id, &json!({ "n": 6 })).await;
+    assert_eq!(status, StatusCode::INSUFFICIENT_STORAGE);
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    let first = list["submissions"][0]["id"].as_str().unwrap();
+    let (_, status) = app.delete_auth(&format!("/api/v1/submissions/{first}"), &token).await;
+    assert_eq!(status, StatusCode::OK);
+    let (usage, _) = app.get_auth(&format!("/api/v1/admin/tenants/{tenant_id}"), &token).await;
+    assert_eq!(usage["tenant"]["stored_submissions"], 4);
+    let (_, status) = app.submit_json(endpoint_id, &json!({ "n": 6 })).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // The recount measures sizes the same way, so it changes nothing
+    let (before, _) = app.get_auth(&format!("/api/v1/admin/tenants/{tenant_id}"), &token).await;
+    webhooker::retention::recount_usage(&app.pool, &Default::default()).await.unwrap();
+    let (after, _) = app.get_auth(&format!("/api/v1/admin/tenants/{tenant_id}"), &token).await;
+    assert_eq!(after["tenant"]["stored_submissions"], 5);
+    assert_eq!(after["tenant"]["stored_bytes"], before["tenant"]["stored_bytes"]);
+
+    // Concurrent submissions can't overshoot: usage is checked under the tenant's row lock
+    app.put_auth(&quotas_path, &token, &json!({ "stored_submissions": 8 })).await;
+    let busy = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "Busy",
+            "busy",
+            None,
+            Some(json!({ "rate_limit": 100 })),
+        )
+        .await;
+    let busy_id = busy["id"].as_str().unwrap();
+    let bodies: Vec<_> = (0..10).map(|n| json!({ "concurrent": n })).collect();
+    let submits = bodies.iter().map(|body| app.submit_json(busy_id, body));
+    let statuses = futures_util::future::join_all(submits).await;
+    let created = statuses.iter().filter(|(_, status)| *status == StatusCode::CREATED).count();
+    assert_eq!(created, 3);
+    let refused = statuses.iter().filter(|(_, status)| *status == StatusCode::INSUFFICIENT_STORAGE);
+    assert_eq!(refused.count(), 7);
+    let (usage, _) = app.get_auth(&format!("/api/v1/admin/tenants/{tenant_id}"), &token).await;
+    assert_eq!(usage["tenant"]["stored_submissions"], 8);
+    for id in [endpoint_id, busy_id] {
+        let path = format!("/api/v1/endpoints/{id}/submissions");
+        let (_, status) = app.delete_auth(&path, &token).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (usage, _) = app.get_auth(&format!("/api/v1/admin/tenants/{tenant_id}"), &token).await;
+    assert_eq!(usage["tenant"]["stored_submissions"], 0);
+    assert_eq!(usage["tenant"]["stored_bytes"], 0);
+
+    // Storage is checked as used before the submission, so the last one in may go over
+    app.put_auth(&quotas_path, &token, &json!({ "storage_bytes": 1 })).await;
+    let (_, status) = app.submit_json(busy_id, &json!({ "n": 7 })).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (body, status) = app.submit_json(busy_id, &json!({ "n": 8 })).await;
+    assert_eq!(status, StatusCode::INSUFFICIENT_STORAGE);
+    assert_eq!(body["error"], "Storage quota reached");
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn endpoint_and_tenant_access_rules() {
+    let app = common::spawn_app_with(|config| {
+        config.trusted_proxies = vec!["127.0.0.1/32".parse().unwrap()];
+    })
+    .await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "Vendor",
+            "vendor",
+            None,
+            Some(json!({ "allowed_cidrs": ["192.30.252.1/22", "10.9.8.7"] })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    assert_eq!(endpoint["settings"]["allowed_cidrs"], json!(["192.30.252.0/22", "10.9.8.7/32"]));
+
+    let post = |forwarded_for: Option<&'static str>| {
+        let mut req = app
+            .client
+            .post(app.url(&format!("/v1/e/{endpoint_id}")))
+            .json(&json!({ "event": "ping" }));
+        if let Some(ip) = forwarded_for {
+            req = req.header("X-Forwarded-For", ip);
+        }
+        req.send()
+    };
+
+    // The client IP is the one resolved through trusted proxies
+    let resp = post(Some("192.30.253.4")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let resp = post(None).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = post(Some("198.51.100.1")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = app
+        .client
+        .post(app.url(&format!("/v1/e/{endpoint_id}/batch")))
+        .json(&json!([{ "event": "ping" }]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    assert_eq!(list["total"], 1);
+    let (ep, _) = app.get_auth(&format!("/api/v1/endpoints/{endpoint_id}"), &token).await;
+    assert_eq!(ep["access_denials"], 3);
+    assert!(ep["last_access_denial_at"].is_string());
+
+    // Denied ranges win over allowed ones
+    let endpoint_path = format!("/api/v1/endpoints/{endpoint_id}");
+    let update = async |settings: serde_json::Value| {
+        app.put_auth(&endpoint_path, &token, &json!({ "name": "Vendor", "settings": settings }))
+            .await
+    };
+    let (_, status) = update(json!({
+        "allowed_cidrs": ["192.30.252.0/22"],
+        "denied_cidrs": ["192.30.253.4"]
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let resp = post(Some("192.30.253.4")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = post(Some("192.30.253.5")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let (_, status) = update(json!({ "denied_cidrs": ["not an ip"] })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (_, status) = update(json!({ "allowed_countries": ["USA"] })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    // No GeoIP database is configured
+    let (body, status) = update(json!({ "denied_countries": ["kp"] })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("WEBHOOKER_GEOIP_DB"));
+
+    // The tenant denylist applies to every endpoint
+    let (_, status) = update(json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, status) = app
+        .put_auth("/api/v1/tenant/ip-denylist", &token, &json!({ "entries": ["300.1.1.1"] }))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (list, status) = app
+        .put_auth("/api/v1/tenant/ip-denylist", &token, &json!({ "entries": ["198.51.100.9/24"] }))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["entries"], json!(["198.51.100.0/24"]));
+    let (list, _) = app.get_auth("/api/v1/tenant/ip-denylist", &token).await;
+    assert_eq!(list["entries"], json!(["198.51.100.0/24"]));
+
+    let resp = post(Some("198.51.100.20")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let other = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Other", "other", None, None)
+        .await;
+    let resp = app
+        .client
+        .get(app.url(&format!("/v1/e/{}?event=ping", other["id"].as_str().unwrap())))
+        .header("X-Forwarded-For", "198.51.100.20")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let (_, status) = app.submit_json(other["id"].as_str().unwrap(), &json!({ "event": "ping" })).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (ep, _) = app.get_auth(&format!("/api/v1/endpoints/{endpoint_id}"), &token).await;
+    assert_eq!(ep["access_denials"], 5);
+
+    common::cleanup(app).await;
+}
+
+/// Run with a local MinIO, e.g.
+/// `WEBHOOKER_TEST_S3_ENDPOINT=http://localhost:9000 cargo test s3_blob_store -- --ignored`
+#[tokio::test]
+#[ignore = "requires an S3-compatible server such as MinIO"]
+async fn s3_blob_store_round_trip() {
+    use webhooker::storage::BlobStore;
+
+    let env = |k: &str, default: &str| std::env::var(k).unwrap_or_else(|_| default.to_string());
+    let store = webhooker::storage::s3::S3BlobStore::new(webhooker::config::S3Config {
+        endpoint: env("WEBHOOKER_TEST_S3_ENDPOINT", "http://localhost:9000"),
+        bucket: env("WEBHOOKER_TEST_S3_BUCKET", "webhooker"),
+        region: env("WEBHOOKER_TEST_S3_REGION", "us-east-1"),
+        access_key: env("WEBHOOKER_TEST_S3_ACCESS_KEY", "minioadmin"),
+        secret_key: env("WEBHOOKER_TEST_S3_SECRET_KEY", "minioadmin"),
+    });
+
+    let key = format!("test/{}", uuid::Uuid::now_v7());
+    store
+        .put(&key, bytes::Bytes::from_static(b"blob"), "text/plain")
+        .await
+        .unwrap();
+    assert_eq!(store.get(&key).await.unwrap().as_ref(), b"blob");
+    store.delete(&key).await.unwrap();
+    assert!(store.get(&key).await.is_err());
+}
+
+// ── Inbound Signatures ──────────────────────────────────────────
+
+fn hmac_sha256(secret: &str, payload: &[u8]) -> Vec<u8> {
+    use hmac::{Hmac, Mac};
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(payload);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[tokio::test]
+async fn github_signature_verification() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "GitHub",
+            "github",
+            None,
+            Some(json!({ "signature": { "scheme": "github", "secret": "gh-secret" } })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    // The plaintext secret is not stored or returned
+    assert!(endpoint["settings"]["signature"].get("secret").is_none());
+    assert!(endpoint["settings"]["signature"]["secret_enc"].is_string());
+
+    let body = r#"{"action":"opened"}"#;
+    let post = |sig: Option<String>| {
+        let mut req = app
+            .client
+            .post(app.url(&format!("/v1/e/{endpoint_id}")))
+            .header("content-type", "application/json")
+            .body(body);
+        if let Some(sig) = sig {
+            req = req.header("X-Hub-Signature-256", sig);
+        }
+        req.send()
+    };
+
+    let good = format!("sha256={}", hex::encode(hmac_sha256("gh-secret", body.as_bytes())));
+    let resp = post(Some(good)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let bad = format!("sha256={}", hex::encode(hmac_sha256("wrong", body.as_bytes())));
+    let resp = post(Some(bad)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = post(None).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    assert_eq!(list["total"], 1);
+
+    let (ep, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}"), &token)
+        .await;
+    assert_eq!(ep["signature_failures"], 2);
+    assert!(ep["last_signature_failure_at"].is_string());
+
+    // Saving settings without a new secret keeps the sealed one
+    let (updated, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "GitHub", "settings": ep["settings"] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["settings"]["signature"], ep["settings"]["signature"]);
+
+    // A scheme without any secret is rejected
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "GitHub", "settings": { "signature": { "scheme": "slack" } } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn stripe_slack_and_hmac_signature_verification() {
+    use base64::Engine;
+
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let project_id = project["id"].as_str().unwrap();
+    let body = r#"{"id":"evt_1"}"#;
+    let now = chrono::Utc::now().timestamp();
+
+    let send = |endpoint_id: String, headers: Vec<(&'static str, String)>| {
+        let mut req = app
+            .client
+            .post(app.url(&format!("/v1/e/{endpoint_id}")))
+            .header("content-type", "application/json")
+            .body(body);
+        for (k, v) in headers {
+            req = req.header(k, v);
+        }
+        req.send()
+    };
+
+    // Stripe: any matching v1 entry passes, stale timestamps fail
+    let stripe = app
+        .create_endpoint(
+            &token,
+            project_id,
+            "Stripe",
+            "stripe",
+            None,
+            Some(json!({ "signature": { "scheme": "stripe", "secret": "whsec_test", "tolerance_secs": 60 } })),
+        )
+        .await;
+    let stripe_id = stripe["id"].as_str().unwrap().to_string();
+    let sig = |ts: i64| hex::encode(hmac_sha256("whsec_test", format!("{ts}.{body}").as_bytes()));
+
+    let header = format!("t={now},v1={},v1={}", "00".repeat(32), sig(now));
+    let resp = send(stripe_id.clone(), vec![("Stripe-Signature", header)]).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let old = now - 600;
+    let header = format!("t={old},v1={}", sig(old));
+    let resp = send(stripe_id.clone(), vec![("Stripe-Signature", header)]).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Extreme timestamps are rejected rather than overflowing
+    for ts in [i64::MIN, i64::MAX] {
+        let header = format!("t={ts},v1={}", sig(ts));
+        let resp = send(stripe_id.clone(), vec![("Stripe-Signature", header)]).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    // Slack: v0=HMAC("v0:{ts}:{body}")
+    let slack = app
+        .create_endpoint(
+            &token,
+            project_id,
+            "Slack",
+            "slack",
+            None,
+            Some(json!({ "signature": { "scheme": "slack", "secret": "slack-secret" } })),
+        )
+        .await;
+    let slack_id = slack["id"].as_str().unwrap().to_string();
+    let slack_sig = format!(
+        "v0={}",
+        hex::encode(hmac_sha256("slack-secret", format!("v0:{now}:{body}").as_bytes()))
+    );
+    let resp = send(
+        slack_id.clone(),
+        vec![
+            ("X-Slack-Request-Timestamp", now.to_string()),
+            ("X-Slack-Signature", slack_sig.clone()),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let resp = send(
+        slack_id,
+        vec![
+            ("X-Slack-Request-Timestamp", (now + 1).to_string()),
+            ("X-Slack-Signature", slack_sig),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Generic HMAC with base64 encoding (Shopify style)
+    let shopify = app
+        .create_endpoint(
+            &token,
+            project_id,
+            "Shopify",
+            "shopify",
+            None,
+            Some(json!({ "signature": {
+                "scheme": "hmac",
+                "secret": "shop-secret",
+                "header": "X-Shopify-Hmac-Sha256",
+                "encoding": "base64"
+            } })),
+        )
+        .await;
+    let shopify_id = shopify["id"].as_str().unwrap().to_string();
+    let shop_sig = base64::engine::general_purpose::STANDARD
+        .encode(hmac_sha256("shop-secret", body.as_bytes()));
+    let resp = send(shopify_id, vec![("X-Shopify-Hmac-Sha256", shop_sig)]).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    common::cleanup(app).await;
+}
+
+// ── Raw Capture & Replay ────────────────────────────────────────
+
+#[tokio::test]
+async fn raw_request_capture_and_replay() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let project_id = project["id"].as_str().unwrap();
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project_id,
+            "Capture",
+            "capture",
+            None,
+            Some(json!({ "capture_raw": { "enabled": true } })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    // Field order and duplicate keys only survive in the verbatim body
+    let body = r#"{"b":1,"a":2,"a":3}"#;
+    let resp = app
+        .client
+        .post(app.url(&format!("/v1/e/{endpoint_id}")))
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer secret")
+        .header("x-source", "test-suite")
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let created: serde_json::Value = resp.json().await.unwrap();
+    let submission_id = created["submission_id"].as_str().unwrap();
+
+    let (captured, status) = app
+        .get_auth(&format!("/api/v1/submissions/{submission_id}/request"), &token)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(captured["body"], body);
+    assert_eq!(captured["method"], "POST");
+    assert_eq!(captured["content_type"], "application/json");
+    assert_eq!(captured["truncated"], false);
+    let headers = captured["headers"].as_array().unwrap();
+    assert!(headers.contains(&json!(["x-source", "test-suite"])));
+    assert!(!headers.iter().any(|h| h[0] == "authorization"));
+
+    let (receiver_url, received) = common::spawn_receiver().await;
+    let (result, status) = app
+        .post_auth(
+            &format!("/api/v1/submissions/{submission_id}/replay"),
+            &token,
+            &json!({ "url": format!("{receiver_url}/replay") }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result["status_code"], 200);
+    {
+        let reqs = received.lock().unwrap();
+        assert_eq!(reqs.len(), 1);
+        assert_eq!(&reqs[0].body[..], body.as_bytes());
+        assert_eq!(reqs[0].headers["x-source"], "test-suite");
+        assert_eq!(reqs[0].headers["content-type"], "application/json");
+        assert!(reqs[0].headers.get("authorization").is_none());
+    }
+
+    // Each stored batch item keeps its own copy of the whole request
+    let batch = r#"[{"n":1},{"n":2}]"#;
+    let resp = app
+        .client
+        .post(app.url(&format!("/v1/e/{endpoint_id}/batch")))
+        .header("content-type", "application/json")
+        .body(batch)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let created: serde_json::Value = resp.json().await.unwrap();
+    for item in created["results"].as_array().unwrap() {
+        let submission_id = item["submission_id"].as_str().unwrap();
+        let (captured, status) = app
+            .get_auth(&format!("/api/v1/submissions/{submission_id}/request"), &token)
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(captured["body"], batch);
+    }
+
+    // Oversized bodies are truncated and can't be replayed
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "Capture", "settings": { "capture_raw": { "enabled": true, "max_body_bytes": 8 } } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (created, _) = app.submit_json(endpoint_id, &json!({ "message": "longer than eight bytes" })).await;
+    let submission_id = created["submission_id"].as_str().unwrap();
+    let (captured, _) = app
+        .get_auth(&format!("/api/v1/submissions/{submission_id}/request"), &token)
+        .await;
+    assert_eq!(captured["truncated"], true);
+    assert_eq!(captured["body"].as_str().unwrap().len(), 8);
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/submissions/{submission_id}/replay"),
+            &token,
+            &json!({ "url": format!("{receiver_url}/replay") }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Capture would bypass redaction, so the combination is rejected
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "Capture", "settings": {
+                "capture_raw": { "enabled": true },
+                "redaction": [{ "field": "email", "action": "drop" }]
+            } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // So would the CAPTCHA token the pipeline strips before storing
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "Capture", "settings": {
+                "capture_raw": { "enabled": true },
+                "captcha": { "provider": "turnstile", "secret": "captcha-secret" }
+            } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    common::cleanup(app).await;
+}
+
 // ── Submissions API ─────────────────────────────────────────────
 
 #[tokio::test]
@@ -619,6 +2579,90 @@ async fn export_submissions_csv() {
     common::cleanup(app).await;
 }
 
+// ── Retention ───────────────────────────────────────────────────
+
+#[tokio::test]
+async fn retention_purges_expired_submissions() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "Form",
+            "form",
+            None,
+            Some(json!({ "retention_days": 7 })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let (old, _) = app.submit_json(endpoint_id, &json!({ "n": "old" })).await;
+    let (fresh, _) = app.submit_json(endpoint_id, &json!({ "n": "fresh" })).await;
+    let old_id: uuid::Uuid = old["submission_id"].as_str().unwrap().parse().unwrap();
+
+    sqlx::query("UPDATE submissions SET created_at = now() - interval '8 days' WHERE id = $1")
+        .bind(old_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+
+    // Only whole days in 1..=36500 are accepted
+    for days in [json!(0), json!(1.5), json!("7"), json!(36501), json!(1e30)] {
+        let (_, status) = app
+            .put_auth(
+                &format!("/api/v1/endpoints/{endpoint_id}"),
+                &token,
+                &json!({ "name": "Form", "settings": { "retention_days": days } }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "retention_days {days}");
+    }
+
+    // A stored value that doesn't fit is skipped, not fatal to the whole pass
+    let legacy = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Legacy", "legacy", None, None)
+        .await;
+    sqlx::query(
+        "UPDATE endpoints SET settings = '{\"retention_days\": 1e30}'::jsonb WHERE id = $1",
+    )
+    .bind(legacy["id"].as_str().unwrap().parse::<uuid::Uuid>().unwrap())
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let purged = webhooker::retention::purge_expired(&app.pool).await.unwrap();
+    assert_eq!(purged, 1);
+
+    let (_, status) = app
+        .get_auth(&format!("/api/v1/submissions/{old_id}"), &token)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let fresh_id = fresh["submission_id"].as_str().unwrap();
+    let (_, status) = app
+        .get_auth(&format!("/api/v1/submissions/{fresh_id}"), &token)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (ep, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}"), &token)
+        .await;
+    assert_eq!(ep["last_purge_count"], 1);
+    assert!(ep["last_purged_at"].is_string());
+
+    let audit_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM audit_events WHERE action = 'submissions.purged' AND resource_id = $1",
+    )
+    .bind(endpoint_id.parse::<uuid::Uuid>().unwrap())
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(audit_count, 1);
+
+    common::cleanup(app).await;
+}
+
 // ── Rate Limiting ───────────────────────────────────────────────
 
 #[tokio::test]
@@ -669,6 +2713,66 @@ async fn login_brute_force_protection() {
     common::cleanup(app).await;
 }
 
+#[tokio::test]
+async fn postgres_rate_limits_shared_across_replicas() {
+    let app = common::spawn_app_with(|config| config.rate_limit_store = RateLimitBackend::Postgres).await;
+    let replica = common::spawn_replica(&app).await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "Form",
+            "form",
+            None,
+            Some(json!({ "rate_limit": 3, "rate_limit_window_secs": 60 })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    let submit_to = |base: String| {
+        let client = app.client.clone();
+        let url = format!("{base}/v1/e/{endpoint_id}");
+        async move { client.post(url).json(&json!({ "x": 1 })).send().await.unwrap().status() }
+    };
+
+    // The limit holds across replicas instead of applying to each
+    assert_eq!(submit_to(app.url("")).await, StatusCode::CREATED);
+    assert_eq!(submit_to(replica.clone()).await, StatusCode::CREATED);
+    assert_eq!(submit_to(app.url("")).await, StatusCode::CREATED);
+    assert_eq!(submit_to(replica.clone()).await, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(submit_to(app.url("")).await, StatusCode::TOO_MANY_REQUESTS);
+
+    // Failed logins too
+    for base in [app.url(""), replica.clone(), app.url(""), replica.clone(), app.url("")] {
+        let resp = app
+            .client
+            .post(format!("{base}/api/v1/auth/login"))
+            .json(&json!({ "email": "admin@test.com", "password": "wrong" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+    let resp = app
+        .client
+        .post(format!("{replica}/api/v1/auth/login"))
+        .json(&json!({ "email": "admin@test.com", "password": "wrong" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let buckets: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM rate_limits")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(buckets, 2);
+    assert_eq!(app.state.rate_limit_store.cleanup().await, 0);
+
+    common::cleanup(app).await;
+}
+
 // ── Tenant Isolation ────────────────────────────────────────────
 
 #[tokio::test]
@@ -719,165 +2823,781 @@ async fn tenant_isolation() {
 
     // User2 should NOT be able to access admin's project directly
     let (_, status) = app
-        .get_auth(&format!("/api/v1/projects/{project_id}"), user2_token)
+        .get_auth(&format!("/api/v1/projects/{project_id}"), user2_token)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    common::cleanup(app).await;
+}
+
+// ── Admin API ───────────────────────────────────────────────────
+
+#[tokio::test]
+async fn admin_tenant_crud() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+
+    // Create tenant
+    let (tenant, status) = app
+        .post_auth(
+            "/api/v1/admin/tenants",
+            &token,
+            &json!({ "name": "New Tenant", "slug": "new-tenant" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let tenant_id = tenant["id"].as_str().unwrap();
+
+    // List tenants
+    let (list, status) = app.get_auth("/api/v1/admin/tenants", &token).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(list.as_array().unwrap().len() >= 2); // bootstrap tenant + new one
+
+    // Delete tenant
+    let (_, status) = app
+        .delete_auth(&format!("/api/v1/admin/tenants/{tenant_id}"), &token)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn non_admin_cannot_access_admin_routes() {
+    let app = common::spawn_app().await;
+    let admin_token = app.bootstrap().await;
+
+    // Create a second tenant with a non-admin user
+    let (tenant, _) = app
+        .post_auth(
+            "/api/v1/admin/tenants",
+            &admin_token,
+            &json!({ "name": "T2", "slug": "t2" }),
+        )
+        .await;
+    let (_, _) = app
+        .post_auth(
+            "/api/v1/admin/users",
+            &admin_token,
+            &json!({
+                "email": "regular@test.com",
+                "password": "password123",
+                "name": "Regular",
+                "tenant_id": tenant["id"],
+                "role": "member"
+            }),
+        )
+        .await;
+
+    let (login_body, _) = app.login("regular@test.com", "password123").await;
+    let user_token = login_body["access_token"].as_str().unwrap();
+
+    // Non-admin should be forbidden from admin routes
+    let (_, status) = app.get_auth("/api/v1/admin/tenants", user_token).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    common::cleanup(app).await;
+}
+
+// ── Modules ─────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn list_available_modules() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+
+    let (body, status) = app.get_auth("/api/v1/modules", &token).await;
+    assert_eq!(status, StatusCode::OK);
+    let modules = body["modules"].as_array().unwrap();
+    let ids: Vec<&str> = modules
+        .iter()
+        .filter_map(|m| m["id"].as_str())
+        .collect();
+    assert!(ids.contains(&"email"));
+    assert!(ids.contains(&"webhook"));
+
+    common::cleanup(app).await;
+}
+
+// ── Actions CRUD ────────────────────────────────────────────────
+
+#[tokio::test]
+async fn actions_crud() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Form", "form", None, None)
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    // Create a webhook action
+    let (action, status) = app
+        .post_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+            &token,
+            &json!({
+                "action_type": "webhook",
+                "config": {
+                    "url": "https://httpbin.org/post",
+                    "method": "POST"
+                },
+                "position": 0
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let action_id = action["id"].as_str().unwrap();
+    assert_eq!(action["action_type"], "webhook");
+
+    // List actions for endpoint
+    let (list, status) = app
+        .get_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+            &token,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list.as_array().unwrap().len(), 1);
+
+    // Update action (disable it)
+    let (updated, status) = app
+        .put_auth(
+            &format!("/api/v1/actions/{action_id}"),
+            &token,
+            &json!({
+                "action_type": "webhook",
+                "config": { "url": "https://httpbin.org/post" },
+                "enabled": false,
+                "position": 1
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["enabled"], false);
+    assert_eq!(updated["position"], 1);
+
+    // Delete action
+    let (_, status) = app
+        .delete_auth(&format!("/api/v1/actions/{action_id}"), &token)
         .await;
-    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(status, StatusCode::OK);
 
     common::cleanup(app).await;
 }
 
-// ── Admin API ───────────────────────────────────────────────────
+// ── Webhook Signing ─────────────────────────────────────────────
 
 #[tokio::test]
-async fn admin_tenant_crud() {
+async fn webhook_signing_and_secret_rotation() {
+    use webhooker::actions::signing;
+
     let app = common::spawn_app().await;
     let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Form", "form", None, None)
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    let (receiver_url, received) = common::spawn_receiver().await;
 
-    // Create tenant
-    let (tenant, status) = app
+    let (action, status) = app
         .post_auth(
-            "/api/v1/admin/tenants",
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
             &token,
-            &json!({ "name": "New Tenant", "slug": "new-tenant" }),
+            &json!({ "action_type": "webhook", "config": { "url": format!("{receiver_url}/hook") } }),
         )
         .await;
     assert_eq!(status, StatusCode::OK);
-    let tenant_id = tenant["id"].as_str().unwrap();
+    let action_id = action["id"].as_str().unwrap();
+    let secret = action["signing_secret"].as_str().unwrap().to_string();
+    assert!(secret.starts_with("whsec_"));
 
-    // List tenants
-    let (list, status) = app.get_auth("/api/v1/admin/tenants", &token).await;
-    assert_eq!(status, StatusCode::OK);
-    assert!(list.as_array().unwrap().len() >= 2); // bootstrap tenant + new one
+    // The secret is only returned once
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/actions"), &token)
+        .await;
+    assert!(list[0].get("signing_secret").is_none());
+    assert!(list[0].get("signing_secret_enc").is_none());
+
+    app.submit_json(endpoint_id, &json!({ "email": "a@example.com" })).await;
+    app.drain_queue().await;
+
+    {
+        let reqs = received.lock().unwrap();
+        assert_eq!(reqs.len(), 1);
+        let ts: i64 = reqs[0].headers["webhooker-timestamp"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let sig = reqs[0].headers["webhooker-signature"].to_str().unwrap();
+        assert_eq!(sig, format!("v1={}", signing::sign(&secret, ts, &reqs[0].body)));
+    }
 
-    // Delete tenant
-    let (_, status) = app
-        .delete_auth(&format!("/api/v1/admin/tenants/{tenant_id}"), &token)
+    // Rotate: both secrets sign during the grace period
+    let (rotated, status) = app
+        .post_auth(
+            &format!("/api/v1/actions/{action_id}/rotate-secret"),
+            &token,
+            &json!({ "grace_period_hours": 1 }),
+        )
         .await;
     assert_eq!(status, StatusCode::OK);
+    let new_secret = rotated["signing_secret"].as_str().unwrap().to_string();
+    assert_ne!(new_secret, secret);
+    assert!(rotated["previous_secret_expires_at"].is_string());
+
+    app.submit_json(endpoint_id, &json!({ "email": "b@example.com" })).await;
+    app.drain_queue().await;
+
+    {
+        let reqs = received.lock().unwrap();
+        assert_eq!(reqs.len(), 2);
+        let ts: i64 = reqs[1].headers["webhooker-timestamp"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let sig = reqs[1].headers["webhooker-signature"].to_str().unwrap();
+        let expected = format!(
+            "v1={},v1={}",
+            signing::sign(&new_secret, ts, &reqs[1].body),
+            signing::sign(&secret, ts, &reqs[1].body)
+        );
+        assert_eq!(sig, expected);
+    }
+
+    // Once the grace period ends only the new secret signs
+    sqlx::query("UPDATE actions SET previous_secret_expires_at = now() - interval '1 minute'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    app.submit_json(endpoint_id, &json!({ "email": "c@example.com" })).await;
+    app.drain_queue().await;
+
+    {
+        let reqs = received.lock().unwrap();
+        assert_eq!(reqs.len(), 3);
+        let sig = reqs[2].headers["webhooker-signature"].to_str().unwrap();
+        assert_eq!(sig.matches("v1=").count(), 1);
+    }
 
     common::cleanup(app).await;
 }
 
+// ── Chat Modules ────────────────────────────────────────────────
+
 #[tokio::test]
-async fn non_admin_cannot_access_admin_routes() {
+async fn discord_and_slack_modules_post_formatted_messages() {
     let app = common::spawn_app().await;
-    let admin_token = app.bootstrap().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Contact", "contact", None, None)
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    let (receiver_url, received) = common::spawn_receiver().await;
 
-    // Create a second tenant with a non-admin user
-    let (tenant, _) = app
+    // Config is validated against the module
+    let (_, status) = app
         .post_auth(
-            "/api/v1/admin/tenants",
-            &admin_token,
-            &json!({ "name": "T2", "slug": "t2" }),
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+            &token,
+            &json!({ "action_type": "slack", "config": { "webhook_url": "not a url" } }),
         )
         .await;
-    let (_, _) = app
-        .post_auth(
-            "/api/v1/admin/users",
-            &admin_token,
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    for (module, path) in [("discord", "discord"), ("slack", "slack")] {
+        let (_, status) = app
+            .post_auth(
+                &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+                &token,
+                &json!({
+                    "action_type": module,
+                    "config": { "webhook_url": format!("{receiver_url}/{path}"), "title": "From {{data.name}}" }
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (created, _) = app
+        .submit_json(endpoint_id, &json!({ "name": "Ada <!channel>", "message": "<@everyone> hi" }))
+        .await;
+    let submission_id = created["submission_id"].as_str().unwrap();
+    app.drain_queue().await;
+
+    let payloads: Vec<serde_json::Value> = received
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|r| serde_json::from_slice(&r.body).unwrap())
+        .collect();
+    assert_eq!(payloads.len(), 2);
+
+    let discord = payloads.iter().find(|p| p.get("embeds").is_some()).unwrap();
+    let embed = &discord["embeds"][0];
+    assert_eq!(embed["title"], "From Ada <!channel>");
+    assert_eq!(embed["fields"][0]["name"], "message");
+    assert_eq!(embed["fields"][0]["value"], "<@everyone> hi");
+    assert_eq!(embed["fields"][1]["name"], "name");
+    assert_eq!(embed["fields"][1]["value"], "Ada <!channel>");
+    assert_eq!(discord["allowed_mentions"]["parse"], json!([]));
+    assert!(embed["url"].as_str().unwrap().ends_with(&format!("/submissions/{submission_id}")));
+
+    let slack = payloads.iter().find(|p| p.get("blocks").is_some()).unwrap();
+    let blocks = slack["blocks"].as_array().unwrap();
+    assert_eq!(blocks[0]["type"], "header");
+    // The header is plain text; the fallback text and sections are mrkdwn
+    assert_eq!(blocks[0]["text"]["text"], "From Ada <!channel>");
+    assert_eq!(slack["text"], "From Ada &lt;!channel&gt;");
+    assert_eq!(blocks[1]["text"]["text"], "*message*\n&lt;@everyone&gt; hi");
+    assert_eq!(blocks[2]["text"]["text"], "*name*\nAda &lt;!channel&gt;");
+    let button = &blocks.last().unwrap()["elements"][0];
+    assert_eq!(button["text"]["text"], "View in Webhooker");
+    assert!(button["url"].as_str().unwrap().ends_with(&format!("/submissions/{submission_id}")));
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn telegram_teams_and_matrix_modules_with_encrypted_secrets() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Contact", "contact", None, None)
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    let (receiver_url, received) = common::spawn_receiver().await;
+
+    let configs = [
+        ("telegram", json!({
+            "bot_token": "123:SECRET",
+            "chat_id": -100,
+            "message": "Hi from {{data.name}}",
+            "api_url": receiver_url,
+        })),
+        ("teams", json!({ "webhook_url": format!("{receiver_url}/teams?sig=SECRET") })),
+        ("matrix", json!({
+            "homeserver_url": receiver_url,
+            "access_token": "syt_SECRET",
+            "room_id": "!room:example.org",
+        })),
+    ];
+    let mut telegram_id = String::new();
+    for (module, config) in configs {
+        let (action, status) = app
+            .post_auth(
+                &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+                &token,
+                &json!({ "action_type": module, "config": config }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{module}: {action}");
+        assert!(!action.to_string().contains("SECRET"), "{module} secret leaked");
+        if module == "telegram" {
+            telegram_id = action["id"].as_str().unwrap().to_string();
+        }
+    }
+
+    let stored: Vec<String> = sqlx::query_scalar("SELECT config::text FROM actions")
+        .fetch_all(&app.pool)
+        .await
+        .unwrap();
+    assert!(stored.iter().all(|c| !c.contains("SECRET")));
+
+    // Updating without resending the token keeps the stored one
+    let (updated, status) = app
+        .put_auth(
+            &format!("/api/v1/actions/{telegram_id}"),
+            &token,
             &json!({
-                "email": "regular@test.com",
-                "password": "password123",
-                "name": "Regular",
-                "tenant_id": tenant["id"],
-                "role": "member"
+                "action_type": "telegram",
+                "config": { "chat_id": -100, "message": "Hi from {{data.name}}", "api_url": receiver_url },
+                "position": 0,
+                "enabled": true
             }),
         )
         .await;
-
-    let (login_body, _) = app.login("regular@test.com", "password123").await;
-    let user_token = login_body["access_token"].as_str().unwrap();
-
-    // Non-admin should be forbidden from admin routes
-    let (_, status) = app.get_auth("/api/v1/admin/tenants", user_token).await;
-    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(status, StatusCode::OK, "{updated}");
+    assert!(updated["config"]["bot_token_enc"].is_string());
+
+    app.submit_json(endpoint_id, &json!({ "name": "Ada" })).await;
+    app.drain_queue().await;
+
+    {
+        let reqs = received.lock().unwrap();
+        assert_eq!(reqs.len(), 3);
+
+        let telegram = reqs.iter().find(|r| r.uri.path().contains("sendMessage")).unwrap();
+        assert_eq!(telegram.uri.path(), "/bot123:SECRET/sendMessage");
+        let body: serde_json::Value = serde_json::from_slice(&telegram.body).unwrap();
+        assert_eq!(body["chat_id"], -100);
+        assert_eq!(body["text"], "Hi from Ada");
+
+        let teams = reqs.iter().find(|r| r.uri.path() == "/teams").unwrap();
+        assert_eq!(teams.uri.query(), Some("sig=SECRET"));
+        let body: serde_json::Value = serde_json::from_slice(&teams.body).unwrap();
+        let card = &body["attachments"][0]["content"];
+        assert_eq!(card["type"], "AdaptiveCard");
+        assert_eq!(card["body"][1]["facts"][0], json!({ "title": "name", "value": "Ada" }));
+
+        let matrix = reqs.iter().find(|r| r.uri.path().starts_with("/_matrix")).unwrap();
+        assert_eq!(matrix.method, "PUT");
+        assert!(matrix.uri.path().contains("/rooms/!room:example.org/send/m.room.message/webhooker-"));
+        assert_eq!(matrix.headers["authorization"], "Bearer syt_SECRET");
+        let body: serde_json::Value = serde_json::from_slice(&matrix.body).unwrap();
+        assert_eq!(body["msgtype"], "m.notice");
+        assert!(body["body"].as_str().unwrap().contains("name: Ada"));
+    }
 
     common::cleanup(app).await;
 }
 
-// ── Modules ─────────────────────────────────────────────────────
+// ── Action Templates ────────────────────────────────────────────
 
 #[tokio::test]
-async fn list_available_modules() {
+async fn webhook_templates_with_loops_filters_and_escaping() {
     let app = common::spawn_app().await;
     let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Form", "form", None, None)
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+    let (receiver_url, received) = common::spawn_receiver().await;
+
+    // Template errors are caught when the action is saved
+    let nested = format!("{}{}", "{% if true %}".repeat(33), "{% endif %}".repeat(33));
+    for (tmpl, error) in [
+        ("{{ data.name | shout }}", "Unknown filter 'shout'"),
+        ("{% if data.name %}hi", "Unclosed {% if %} at position 0"),
+        ("{% for x in data %}{{ y }}{% endfor %}", "Unknown name 'y'"),
+        (&nested, "Blocks nested too deeply"),
+    ] {
+        let (body, status) = app
+            .post_auth(
+                &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+                &token,
+                &json!({
+                    "action_type": "webhook",
+                    "config": { "url": format!("{receiver_url}/hook"), "body_template": tmpl }
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{tmpl}");
+        assert!(body["error"].as_str().unwrap().contains(error), "{}", body["error"]);
+    }
 
-    let (body, status) = app.get_auth("/api/v1/modules", &token).await;
+    let body_template = r#"{
+        "name": "{{ data.name | upper }}",
+        "quote": "{{ data.quote }}",
+        "city": "{{ data.address.city }}",
+        "zip": "{{ data.address.zip | default("n/a") }}",
+        "first_tag": "{{ data.tags[0] }}",
+        "tags": {{ data.tags | json }},
+        "address": {{ data.address }},
+        "count": {{ data.count }},
+        "year": "{{ submission.created_at | date("%Y") }}",
+        "list": "{% for t in data.tags %}{{ loop.index }}:{{ t }}{% if not loop.last %},{% endif %}{% endfor %}",
+        "phone": "{% if data.phone %}yes{% else %}no{% endif %}"
+    }"#;
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+            &token,
+            &json!({
+                "action_type": "webhook",
+                "config": {
+                    "url": format!("{receiver_url}/hook?name={{{{ data.name | urlencode }}}}"),
+                    "headers": { "X-Name": "{{ data.name }}" },
+                    "body_template": body_template
+                }
+            }),
+        )
+        .await;
     assert_eq!(status, StatusCode::OK);
-    let modules = body["modules"].as_array().unwrap();
-    let ids: Vec<&str> = modules
-        .iter()
-        .filter_map(|m| m["id"].as_str())
-        .collect();
-    assert!(ids.contains(&"email"));
-    assert!(ids.contains(&"webhook"));
+
+    app.submit_json(
+        endpoint_id,
+        &json!({
+            "name": "Ada\nLovelace",
+            "quote": "say \"hi\"",
+            "address": { "city": "London" },
+            "tags": ["a", "b"],
+            "count": 2,
+            "phone": ""
+        }),
+    )
+    .await;
+    app.drain_queue().await;
+
+    let requests = std::mem::take(&mut *received.lock().unwrap());
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.uri.query(), Some("name=Ada%0ALovelace"));
+    assert_eq!(request.headers["x-name"], "Ada Lovelace");
+
+    let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(body["name"], "ADA\nLOVELACE");
+    assert_eq!(body["quote"], "say \"hi\"");
+    assert_eq!(body["city"], "London");
+    assert_eq!(body["zip"], "n/a");
+    assert_eq!(body["first_tag"], "a");
+    assert_eq!(body["tags"], json!(["a", "b"]));
+    assert_eq!(body["address"], json!({ "city": "London" }));
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["year"], chrono::Utc::now().format("%Y").to_string());
+    assert_eq!(body["list"], "1:a,2:b");
+    assert_eq!(body["phone"], "no");
+
+    // Unknown names that predate the check on save render as empty
+    let create = async |path: &str, body_template: &str| {
+        let (action, status) = app
+            .post_auth(
+                &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+                &token,
+                &json!({
+                    "action_type": "webhook",
+                    "config": {
+                        "url": format!("{receiver_url}/{path}"),
+                        "body_template": body_template
+                    }
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        action
+    };
+    let legacy = create("legacy", "{}").await;
+    sqlx::query(
+        "UPDATE actions SET config = jsonb_set(config, '{body_template}', to_jsonb($2::text))
+         WHERE id = $1",
+    )
+    .bind(legacy["id"].as_str().unwrap().parse::<uuid::Uuid>().unwrap())
+    .bind(r#"{"name": "{{ name }}", "city": "{{ data.city }}"}"#)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    // Runaway loops and output fail the action instead of exhausting memory
+    let loops = "{% for a in data.xs %}{% for b in data.xs %}{% for c in data.xs %}x\
+                 {% endfor %}{% endfor %}{% endfor %}";
+    let loops = create("loops", loops).await;
+    let big = create("big", "{% for x in data.xs %}{{ data.big }}{% endfor %}").await;
+    // A body that isn't JSON fails rather than being posted as a string
+    let broken = create("broken", r#"{"city": {{ data.city }}}"#).await;
+
+    app.submit_json(
+        endpoint_id,
+        &json!({ "city": "Paris", "xs": vec![0; 30], "big": "b".repeat(50_000) }),
+    )
+    .await;
+    app.drain_queue().await;
+
+    let requests = std::mem::take(&mut *received.lock().unwrap());
+    let legacy_request = requests.iter().find(|r| r.uri.path() == "/legacy").unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&legacy_request.body).unwrap();
+    assert_eq!(body, json!({ "name": "", "city": "Paris" }));
+    let failed = ["/loops", "/big", "/broken"];
+    assert!(!requests.iter().any(|r| failed.contains(&r.uri.path())));
+
+    for (action, error) in [
+        (loops, "loop iterations"),
+        (big, "larger than 1048576 bytes"),
+        (broken, "didn't render valid JSON"),
+    ] {
+        let logged: String = sqlx::query_scalar(
+            "SELECT response->>'error' FROM action_log WHERE action_id = $1 AND status = 'failed'",
+        )
+        .bind(action["id"].as_str().unwrap().parse::<uuid::Uuid>().unwrap())
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+        assert!(logged.contains(error), "{logged}");
+    }
 
     common::cleanup(app).await;
 }
 
-// ── Actions CRUD ────────────────────────────────────────────────
+// ── Action Conditions ───────────────────────────────────────────
 
 #[tokio::test]
-async fn actions_crud() {
+async fn action_conditions_filter_execution() {
     let app = common::spawn_app().await;
     let token = app.bootstrap().await;
     let project = app.create_project(&token, "Project", "project").await;
     let endpoint = app
-        .create_endpoint(&token, project["id"].as_str().unwrap(), "Form", "form", None, None)
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Leads", "leads", None, None)
         .await;
     let endpoint_id = endpoint["id"].as_str().unwrap();
+    let (receiver_url, received) = common::spawn_receiver().await;
+
+    // Parse errors are reported with a position
+    let (body, status) = app
+        .post_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+            &token,
+            &json!({
+                "action_type": "webhook",
+                "config": { "url": format!("{receiver_url}/hook") },
+                "condition": "data.country = \"US\""
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "Invalid condition: Unexpected '=' at position 13 (use '==')"
+    );
+
+    let (body, status) = app
+        .post_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+            &token,
+            &json!({
+                "action_type": "webhook",
+                "config": { "url": format!("{receiver_url}/hook") },
+                "condition": "form.country == \"US\""
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("Unknown name 'form' at position 0"));
+
+    // Deep nesting and huge sources are refused instead of overflowing the stack,
+    // in conditions and in template expressions alike
+    let hook = json!({ "url": format!("{receiver_url}/hook") });
+    let deep = format!("{}true{}", "(".repeat(65), ")".repeat(65));
+    let mut templated = hook.clone();
+    templated["body_template"] = json!(format!("{{{{ {deep} }}}}"));
+    let cases = [
+        (hook.clone(), deep.clone(), "Nested too deeply"),
+        (hook.clone(), format!("{}true", "!".repeat(100_000)), "the limit is 4096"),
+        (hook.clone(), format!("data.x in {}", "[".repeat(100)), "Nested too deeply"),
+        (templated, String::new(), "Nested too deeply"),
+    ];
+    for (config, condition, expected) in cases {
+        let (body, status) = app
+            .post_auth(
+                &format!("/api/v1/endpoints/{endpoint_id}/actions"),
+                &token,
+                &json!({ "action_type": "webhook", "config": config, "condition": condition }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains(expected), "{body}");
+    }
 
-    // Create a webhook action
     let (action, status) = app
         .post_auth(
             &format!("/api/v1/endpoints/{endpoint_id}/actions"),
             &token,
             &json!({
                 "action_type": "webhook",
-                "config": {
-                    "url": "https://httpbin.org/post",
-                    "method": "POST"
-                },
-                "position": 0
+                "config": { "url": format!("{receiver_url}/big-us") },
+                "condition": "data.country == \"US\" && data.budget > 5000"
             }),
         )
         .await;
     assert_eq!(status, StatusCode::OK);
+    assert_eq!(action["condition"], "data.country == \"US\" && data.budget > 5000");
     let action_id = action["id"].as_str().unwrap();
-    assert_eq!(action["action_type"], "webhook");
 
-    // List actions for endpoint
-    let (list, status) = app
-        .get_auth(
+    let (_, status) = app
+        .post_auth(
             &format!("/api/v1/endpoints/{endpoint_id}/actions"),
             &token,
+            &json!({
+                "action_type": "webhook",
+                "config": { "url": format!("{receiver_url}/paid") },
+                "condition": "data.utm_source in [\"ads\", \"fb\"] && !(data.tags contains \"test\")",
+                "position": 1
+            }),
         )
         .await;
     assert_eq!(status, StatusCode::OK);
-    assert_eq!(list.as_array().unwrap().len(), 1);
 
-    // Update action (disable it)
-    let (updated, status) = app
-        .put_auth(
-            &format!("/api/v1/actions/{action_id}"),
+    // `.0` after a dot is an index, not the start of a number
+    let (_, status) = app
+        .post_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}/actions"),
             &token,
             &json!({
                 "action_type": "webhook",
-                "config": { "url": "https://httpbin.org/post" },
-                "enabled": false,
-                "position": 1
+                "config": { "url": format!("{receiver_url}/first-item") },
+                "condition": "data.items.0.name == \"Ada\" && data.items.1.qty > 1.5",
+                "position": 2
             }),
         )
         .await;
     assert_eq!(status, StatusCode::OK);
-    assert_eq!(updated["enabled"], false);
-    assert_eq!(updated["position"], 1);
 
-    // Delete action
-    let (_, status) = app
-        .delete_auth(&format!("/api/v1/actions/{action_id}"), &token)
+    // Form values are strings; numeric comparisons still apply
+    app.submit_form(endpoint_id, &[("country", "US"), ("budget", "9000"), ("utm_source", "seo")])
+        .await;
+    app.submit_json(
+        endpoint_id,
+        &json!({ "country": "DE", "budget": 10000, "utm_source": "fb", "tags": ["new"] }),
+    )
+    .await;
+    app.submit_json(endpoint_id, &json!({ "utm_source": "ads", "tags": ["test"] }))
+        .await;
+    app.submit_json(
+        endpoint_id,
+        &json!({ "items": [{ "name": "Ada", "qty": 1 }, { "name": "Bob", "qty": 2 }] }),
+    )
+    .await;
+    app.drain_queue().await;
+
+    let mut paths: Vec<String> = received
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|r| r.uri.path().to_string())
+        .collect();
+    paths.sort();
+    assert_eq!(paths, vec!["/big-us", "/first-item", "/paid"]);
+
+    // Skipped runs are recorded in the action log
+    let (log, status) = app
+        .get_auth(&format!("/api/v1/actions/{action_id}/log"), &token)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let statuses: Vec<&str> = log
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| l["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses.iter().filter(|s| **s == "skipped").count(), 3);
+    let skipped = log
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|l| l["status"] == "skipped")
+        .unwrap();
+    assert_eq!(skipped["response"]["reason"], "condition false");
+
+    // Clearing the condition on update
+    let (updated, status) = app
+        .put_auth(
+            &format!("/api/v1/actions/{action_id}"),
+            &token,
+            &json!({
+                "action_type": "webhook",
+                "config": { "url": format!("{receiver_url}/big-us") },
+                "position": 0,
+                "enabled": true,
+                "condition": "  "
+            }),
+        )
         .await;
     assert_eq!(status, StatusCode::OK);
+    assert!(updated["condition"].is_null());
 
     common::cleanup(app).await;
 }
@@ -941,7 +3661,120 @@ async fn cors_preflight_options() {
         .unwrap();
     assert_eq!(resp.status(), StatusCode::NO_CONTENT);
     assert!(resp.headers().contains_key("access-control-allow-origin"));
-    assert!(resp.headers().contains_key("access-control-allow-methods"));
+    assert_eq!(resp.headers()["access-control-allow-methods"], "GET, POST, OPTIONS");
+    let allowed = resp.headers()["access-control-allow-headers"].to_str().unwrap().to_lowercase();
+    for header in ["content-type", "idempotency-key", "x-hub-signature-256", "stripe-signature"] {
+        assert!(allowed.split(", ").any(|h| h == header), "{header} not allowed: {allowed}");
+    }
+
+    common::cleanup(app).await;
+}
+
+#[tokio::test]
+async fn cors_origin_allowlist() {
+    let app = common::spawn_app().await;
+    let token = app.bootstrap().await;
+    let project = app.create_project(&token, "Project", "project").await;
+    let endpoint = app
+        .create_endpoint(
+            &token,
+            project["id"].as_str().unwrap(),
+            "Form",
+            "form",
+            None,
+            Some(json!({ "cors_origins": ["https://example.com", "https://*.shop.example"] })),
+        )
+        .await;
+    let endpoint_id = endpoint["id"].as_str().unwrap();
+
+    let post = |headers: &[(&'static str, &'static str)]| {
+        let mut req = app
+            .client
+            .post(app.url(&format!("/v1/e/{endpoint_id}")))
+            .json(&json!({ "message": "hi" }));
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        req.send()
+    };
+
+    // Only the matched origin is echoed
+    let resp = post(&[("Origin", "https://example.com")]).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(resp.headers()["access-control-allow-origin"], "https://example.com");
+    assert_eq!(resp.headers()["vary"], "Origin");
+    let resp = post(&[("Origin", "https://eu.shop.example")]).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(resp.headers()["access-control-allow-origin"], "https://eu.shop.example");
+
+    // Anything else is refused before storing
+    for origin in ["https://evil.example", "https://shop.example", "http://example.com", "null"] {
+        let resp = post(&[("Origin", origin)]).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{origin}");
+        assert!(!resp.headers().contains_key("access-control-allow-origin"));
+    }
+
+    let preflight = |origin: &'static str| {
+        app.client
+            .request(reqwest::Method::OPTIONS, app.url(&format!("/v1/e/{endpoint_id}")))
+            .header("Origin", origin)
+            .send()
+    };
+    let resp = preflight("https://a.b.shop.example").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert_eq!(resp.headers()["access-control-allow-origin"], "https://a.b.shop.example");
+    let resp = preflight("https://evil.example").await.unwrap();
+    assert!(!resp.headers().contains_key("access-control-allow-origin"));
+
+    // Requests without an Origin pass unless enforcement is on
+    let resp = post(&[]).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "Form", "settings": {
+                "cors_origins": ["https://example.com"],
+                "cors_enforce": true
+            } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let resp = post(&[]).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = post(&[("Referer", "https://evil.example/contact")]).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = post(&[("Referer", "https://example.com/contact?x=1")]).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let (list, _) = app
+        .get_auth(&format!("/api/v1/endpoints/{endpoint_id}/submissions"), &token)
+        .await;
+    assert_eq!(list["total"], 4);
+
+    let (_, status) = app
+        .put_auth(
+            &format!("/api/v1/endpoints/{endpoint_id}"),
+            &token,
+            &json!({ "name": "Form", "settings": { "cors_origins": ["example.com/contact"] } }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Without an allowlist any origin is accepted
+    let other = app
+        .create_endpoint(&token, project["id"].as_str().unwrap(), "Open", "open", None, None)
+        .await;
+    let resp = app
+        .client
+        .post(app.url(&format!("/v1/e/{}", other["id"].as_str().unwrap())))
+        .header("Origin", "https://anywhere.example")
+        .json(&json!({ "message": "hi" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(resp.headers()["access-control-allow-origin"], "*");
 
     common::cleanup(app).await;
 }
@@ -1,4 +1,8 @@
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::http::{HeaderMap, Method, Uri};
 
 use reqwest::{Client, StatusCode};
 use serde_json::{json, Value};
@@ -6,7 +10,8 @@ use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use webhooker::config::{Config, RegistrationMode};
+use webhooker::config::{Config, RateLimitBackend, RegistrationMode, StorageConfig};
+use webhooker::state::SharedState;
 
 /// A running test server instance with a dedicated test database.
 pub struct TestApp {
@@ -14,6 +19,7 @@ pub struct TestApp {
     pub pool: PgPool,
     pub client: Client,
     pub db_name: String,
+    pub state: SharedState,
 }
 
 impl TestApp {
@@ -127,6 +133,35 @@ impl TestApp {
         (body, status)
     }
 
+    /// Submit a raw multipart body to an endpoint, return (body, status).
+    pub async fn submit_multipart(
+        &self,
+        endpoint_id: &str,
+        boundary: &str,
+        body: String,
+    ) -> (Value, StatusCode) {
+        let resp = self
+            .client
+            .post(self.url(&format!("/v1/e/{endpoint_id}")))
+            .header("content-type", format!("multipart/form-data; boundary={boundary}"))
+            .body(body)
+            .send()
+            .await
+            .expect("submit multipart failed");
+        let status = resp.status();
+        let body: Value = resp.json().await.unwrap_or(json!(null));
+        (body, status)
+    }
+
+    /// Run the action worker until the queue is empty.
+    #[allow(dead_code)]
+    pub async fn drain_queue(&self) {
+        while webhooker::worker::process_next(&self.state)
+            .await
+            .expect("worker failed")
+        {}
+    }
+
     /// Make an authenticated GET request.
     pub async fn get_auth(&self, path: &str, token: &str) -> (Value, StatusCode) {
         let resp = self
@@ -188,6 +223,11 @@ impl TestApp {
 
 /// Spawn a test app with a fresh temporary database.
 pub async fn spawn_app() -> TestApp {
+    spawn_app_with(|_| {}).await
+}
+
+/// Like `spawn_app`, with config overrides.
+pub async fn spawn_app_with(configure: impl FnOnce(&mut Config)) -> TestApp {
     let _ = dotenvy::dotenv();
 
     let base_url = std::env::var("DATABASE_URL")
@@ -232,7 +272,7 @@ pub async fn spawn_app() -> TestApp {
         .await
         .expect("Failed to run migrations on test database");
 
-    let config = Config {
+    let mut config = Config {
         database_url: test_url,
         jwt_secret: "test-jwt-secret-that-is-long-enough".to_string(),
         encryption_key: "test-encryption-key-32-chars-ok!".to_string(),
@@ -247,9 +287,17 @@ pub async fn spawn_app() -> TestApp {
         worker_count: 1,
         log_level: "warn".to_string(),
         smtp: None,
+        storage: StorageConfig::Local {
+            path: storage_path(&db_name).to_string_lossy().into_owned(),
+        },
+        captcha_verify_url: None,
+        rate_limit_store: RateLimitBackend::Memory,
+        default_quotas: Default::default(),
+        geoip: None,
     };
+    configure(&mut config);
 
-    let (app, _state) = webhooker::build_app(pool.clone(), config);
+    let (app, state) = webhooker::build_app(pool.clone(), config);
 
     // Bind to random port
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
@@ -277,9 +325,110 @@ pub async fn spawn_app() -> TestApp {
         pool,
         client,
         db_name,
+        state,
     }
 }
 
+/// A request captured by a mock receiver.
+#[allow(dead_code)]
+pub struct ReceivedRequest {
+    pub method: Method,
+    pub uri: Uri,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+/// Spawn a local HTTP server that records every request it receives and responds 200.
+/// Returns the base URL and the captured requests.
+#[allow(dead_code)]
+pub async fn spawn_receiver() -> (String, Arc<Mutex<Vec<ReceivedRequest>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let captured = received.clone();
+    let app = axum::Router::new().fallback(
+        move |method: Method, uri: Uri, headers: HeaderMap, body: Bytes| {
+            let captured = captured.clone();
+            async move {
+                captured.lock().unwrap().push(ReceivedRequest {
+                    method,
+                    uri,
+                    headers,
+                    body,
+                });
+                "ok"
+            }
+        },
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind receiver");
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Receiver failed");
+    });
+
+    (format!("http://{addr}"), received)
+}
+
+/// A second server on the same database and config, like another replica
+/// behind a load balancer. Returns its base URL.
+#[allow(dead_code)]
+pub async fn spawn_replica(app: &TestApp) -> String {
+    let (router, _) = webhooker::build_app(app.pool.clone(), app.state.config.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind replica");
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .expect("Replica failed");
+    });
+    format!("http://{addr}")
+}
+
+/// A stand-in for a CAPTCHA provider's siteverify endpoint: succeeds only for
+/// the `valid` token. Returns its URL and the form bodies it received.
+#[allow(dead_code)]
+pub async fn spawn_captcha_verifier(valid: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let captured = received.clone();
+    let app = axum::Router::new().route(
+        "/siteverify",
+        axum::routing::post(move |body: String| {
+            let captured = captured.clone();
+            async move {
+                let success = body
+                    .split('&')
+                    .any(|pair| pair == format!("response={valid}"));
+                captured.lock().unwrap().push(body);
+                axum::Json(json!({
+                    "success": success,
+                    "error-codes": if success { json!([]) } else { json!(["invalid-input-response"]) },
+                }))
+            }
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind CAPTCHA verifier");
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("CAPTCHA verifier failed");
+    });
+
+    (format!("http://{addr}/siteverify"), received)
+}
+
+/// Per-test upload directory, removed in `cleanup`.
+fn storage_path(db_name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(db_name)
+}
+
 /// Drop stale test databases (useful after test crashes).
 #[allow(dead_code)]
 pub async fn cleanup_stale_test_dbs() {
@@ -315,6 +464,7 @@ pub async fn cleanup_stale_test_dbs() {
 pub async fn cleanup(app: TestApp) {
     let db_name = app.db_name.clone();
     app.pool.close().await;
+    let _ = std::fs::remove_dir_all(storage_path(&db_name));
 
     let base_url = std::env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set for tests");